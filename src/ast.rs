@@ -1,17 +1,41 @@
-use std::marker::PhantomData;
+pub use crate::lexer::{Delim, Span, Spanned, Token, TokenTree};
+pub use owned::MapStrings;
 
-use crate::lexer::{Spanned, TokenTree};
+pub mod arena;
+mod owned;
+pub mod visit;
+pub mod visit_mut;
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct Stylesheet<'tokens, 'src> {
-    pub items: ListOfItems<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stylesheet<S> {
+    pub items: ListOfItems<S>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct ListOfItems<'tokens, 'src>(pub Vec<Spanned<Item<'tokens, 'src>>>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListOfItems<S>(pub Vec<Spanned<Item<S>>>);
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct ListOfComponentValues<'tokens, 'src>(pub &'tokens [Spanned<TokenTree<'src>>]);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListOfComponentValues<S>(pub Vec<Spanned<TokenTree<S>>>);
+
+/// An identifier containing LESS interpolation (e.g. `@{name}` or `fade-@{name}`).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterpolatedIdent<S>(pub Vec<Spanned<InterpolationPart<S>>>);
+
+/// The contents of a string, which may contain LESS interpolation (e.g. `"@{themes}/dark.less"`).
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterpolatedString<S>(pub Vec<Spanned<InterpolationPart<S>>>);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InterpolationPart<S> {
+    Literal(S),
+    Variable(S),
+}
 
 /// Items:
 ///  - [`AtRule`]
@@ -30,106 +54,555 @@ pub struct ListOfComponentValues<'tokens, 'src>(pub &'tokens [Spanned<TokenTree<
 ///  - [`Call`]
 ///      - [`MixinCall`] (e.g. `.mixin(blue);`)
 ///      - [`VariableCall`] (e.g. `@detached-ruleset();`)
-///      - [`FunctionCall`] (e.g. `each(red blue green, {});`)
+///      - [`FunctionCallItem`] (e.g. `each(red blue green, {});`)
+///  - [`Extend`] (e.g. `&:extend(.foo all);`)
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum Item<'tokens, 'src> {
-    AtRule(AtRule<'tokens, 'src>),
-    QualifiedRule(QualifiedRule<'tokens, 'src>),
-    Declaration(Declaration<'tokens, 'src>),
-    Call(Call<'tokens, 'src>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Item<S> {
+    AtRule(AtRule<S>),
+    QualifiedRule(QualifiedRule<S>),
+    Declaration(Declaration<S>),
+    Call(Call<S>),
+    Extend(Extend<S>),
+}
+
+impl<S> Item<S> {
+    /// Returns the block of items nested in the item, if it has one (e.g. a rule's block or a
+    /// detached ruleset).
+    pub fn block(&self) -> Option<&ListOfItems<S>> {
+        match self {
+            Item::AtRule(rule) => rule.block(),
+            Item::QualifiedRule(rule) => match rule {
+                QualifiedRule::Generic(rule) => Some(&rule.block),
+                QualifiedRule::Style(rule) => Some(&rule.block),
+                QualifiedRule::Mixin(rule) => Some(&rule.block),
+                QualifiedRule::Keyframe(rule) => Some(&rule.block),
+            },
+            Item::Declaration(declaration) => match &declaration.value {
+                Value::ComponentValues(_) => None,
+                Value::DetachedRuleset(block) => Some(block),
+            },
+            Item::Call(_) | Item::Extend(_) => None,
+        }
+    }
+
+    /// Returns a mutable reference to the block of items nested in the item, if it has one.
+    pub fn block_mut(&mut self) -> Option<&mut ListOfItems<S>> {
+        match self {
+            Item::AtRule(rule) => rule.block_mut(),
+            Item::QualifiedRule(rule) => match rule {
+                QualifiedRule::Generic(rule) => Some(&mut rule.block),
+                QualifiedRule::Style(rule) => Some(&mut rule.block),
+                QualifiedRule::Mixin(rule) => Some(&mut rule.block),
+                QualifiedRule::Keyframe(rule) => Some(&mut rule.block),
+            },
+            Item::Declaration(declaration) => match &mut declaration.value {
+                Value::ComponentValues(_) => None,
+                Value::DetachedRuleset(block) => Some(block),
+            },
+            Item::Call(_) | Item::Extend(_) => None,
+        }
+    }
 }
 
 // AT-RULES
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum AtRule<'tokens, 'src> {
-    Generic(GenericAtRule<'tokens, 'src>),
-    // TODO: Media, Keyframes, etc.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtRule<S> {
+    Generic(GenericAtRule<S>),
+    Keyframes(KeyframesAtRule<S>),
+    Import(ImportAtRule<S>),
+    Supports(SupportsAtRule<S>),
+    Container(ContainerAtRule<S>),
+    Layer(LayerAtRule<S>),
+    FontFace(FontFaceAtRule<S>),
+    Page(PageAtRule<S>),
+    PageMargin(PageMarginAtRule<S>),
+    Namespace(NamespaceAtRule<S>),
+    Property(PropertyAtRule<S>),
+    // TODO: Media, etc.
+}
+
+impl<S> AtRule<S> {
+    /// Returns the block of items of the at-rule, if it has one.
+    pub fn block(&self) -> Option<&ListOfItems<S>> {
+        match self {
+            AtRule::Generic(rule) => rule.block.as_ref(),
+            AtRule::Keyframes(rule) => Some(&rule.block),
+            AtRule::Import(_) | AtRule::Namespace(_) => None,
+            AtRule::Supports(rule) => Some(&rule.block),
+            AtRule::Container(rule) => Some(&rule.block),
+            AtRule::Layer(rule) => rule.block.as_ref(),
+            AtRule::FontFace(rule) => Some(&rule.block),
+            AtRule::Page(rule) => Some(&rule.block),
+            AtRule::PageMargin(rule) => Some(&rule.block),
+            AtRule::Property(rule) => Some(&rule.block),
+        }
+    }
+
+    /// Returns a mutable reference to the block of items of the at-rule, if it has one.
+    pub fn block_mut(&mut self) -> Option<&mut ListOfItems<S>> {
+        match self {
+            AtRule::Generic(rule) => rule.block.as_mut(),
+            AtRule::Keyframes(rule) => Some(&mut rule.block),
+            AtRule::Import(_) | AtRule::Namespace(_) => None,
+            AtRule::Supports(rule) => Some(&mut rule.block),
+            AtRule::Container(rule) => Some(&mut rule.block),
+            AtRule::Layer(rule) => rule.block.as_mut(),
+            AtRule::FontFace(rule) => Some(&mut rule.block),
+            AtRule::Page(rule) => Some(&mut rule.block),
+            AtRule::PageMargin(rule) => Some(&mut rule.block),
+            AtRule::Property(rule) => Some(&mut rule.block),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenericAtRule<S> {
+    pub name: S,
+    /// The prelude, in which LESS interpolations (e.g. `@media @{query}`) are kept as
+    /// [`Token::Interpolation`] tokens.
+    pub prelude: ListOfComponentValues<S>,
+    pub block: Option<ListOfItems<S>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyframesAtRule<S> {
+    /// Vendor prefix of the at-rule name (e.g. `-webkit-` for `@-webkit-keyframes`).
+    pub vendor_prefix: Option<S>,
+    pub name: KeyframesName<S>,
+    pub block: ListOfItems<S>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyframesName<S> {
+    Ident(S),
+    String(S),
+    Variable(S),
+    InterpolatedIdent(InterpolatedIdent<S>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImportAtRule<S> {
+    pub options: Vec<Spanned<ImportOption>>,
+    pub url: Spanned<UrlOrString<S>>,
+    pub media_queries: ListOfComponentValues<S>,
+}
+
+/// See <https://lesscss.org/features/#import-atrules-feature-import-options>
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ImportOption {
+    Reference,
+    Inline,
+    Less,
+    Css,
+    Once,
+    Multiple,
+    Optional,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UrlOrString<S> {
+    /// A quoted URL (e.g. `"theme.less"`)
+    String(InterpolatedString<S>),
+    /// A `url()` (e.g. `url(theme.less)` or `url("theme.less")`)
+    Url(InterpolatedString<S>),
+}
+
+/// A boolean condition over features, as used by `@supports` and `@container`
+/// (e.g. `not (display: grid)` or `(min-width: 400px) and (orientation: landscape)`).
+///
+/// Parentheses around sub-conditions are implied by the structure.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Condition<T> {
+    Not(Box<Spanned<Condition<T>>>),
+    And(Vec<Spanned<Condition<T>>>),
+    Or(Vec<Spanned<Condition<T>>>),
+    Feature(T),
 }
 
+/// See <https://drafts.csswg.org/css-conditional-3/#at-supports>
 #[derive(Clone, Debug, PartialEq)]
-pub struct GenericAtRule<'tokens, 'src> {
-    pub name: &'src str,
-    // TODO: Support LESS interpolation in prelude.
-    pub prelude: ListOfComponentValues<'tokens, 'src>,
-    pub block: Option<ListOfItems<'tokens, 'src>>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupportsAtRule<S> {
+    pub condition: Spanned<Condition<SupportsFeature<S>>>,
+    pub block: ListOfItems<S>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SupportsFeature<S> {
+    /// A declaration in parentheses (e.g. `(display: grid)`)
+    Declaration(Declaration<S>),
+    /// A function (e.g. `selector(a > b)`)
+    Function(FunctionCall<S>),
+    /// Anything else in parentheses, which evaluates to false
+    GeneralEnclosed(ListOfComponentValues<S>),
+}
+
+/// See <https://drafts.csswg.org/css-contain-3/#container-rule>
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContainerAtRule<S> {
+    pub name: Option<S>,
+    pub condition: Option<Spanned<Condition<ContainerFeature<S>>>>,
+    pub block: ListOfItems<S>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContainerFeature<S> {
+    /// A size feature in parentheses (e.g. `(min-width: 400px)` or `(400px < width < 800px)`)
+    Size(ListOfComponentValues<S>),
+    /// A function (e.g. `style(--responsive: true)`)
+    Function(FunctionCall<S>),
+}
+
+/// See <https://drafts.csswg.org/css-cascade-5/#layering>
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerAtRule<S> {
+    /// The layer names. The statement form (e.g. `@layer reset, base;`) has at least one name, the
+    /// block form (e.g. `@layer base { ... }`) has at most one.
+    pub names: Vec<Spanned<LayerName<S>>>,
+    pub block: Option<ListOfItems<S>>,
+}
+
+/// A possibly nested layer name (e.g. `framework.base`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerName<S>(pub Vec<Spanned<S>>);
+
+/// See <https://drafts.csswg.org/css-fonts-4/#font-face-rule>
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontFaceAtRule<S> {
+    pub block: ListOfItems<S>,
+}
+
+/// See <https://drafts.csswg.org/css-page-3/#at-page-rule>
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageAtRule<S> {
+    pub selectors: Vec<Spanned<PageSelector<S>>>,
+    /// The declarations and margin rules (see [`PageMarginAtRule`]) of the page.
+    pub block: ListOfItems<S>,
+}
+
+/// A page selector (e.g. `:first`, `cover` or `chapter:left`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageSelector<S> {
+    pub name: Option<S>,
+    pub pseudo_classes: Vec<Spanned<S>>,
+}
+
+/// A margin rule inside of a [`PageAtRule`] (e.g. `@top-left { content: "Title"; }`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageMarginAtRule<S> {
+    pub name: S,
+    pub block: ListOfItems<S>,
+}
+
+/// See <https://drafts.csswg.org/css-namespaces-3/#declaration>
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NamespaceAtRule<S> {
+    pub prefix: Option<S>,
+    pub url: Spanned<UrlOrString<S>>,
+}
+
+/// See <https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule>
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertyAtRule<S> {
+    /// The custom property name (e.g. `--my-color`)
+    pub name: S,
+    /// The descriptors (e.g. `syntax: '<color>';`) as declarations.
+    pub block: ListOfItems<S>,
 }
 
 // QUALIFIED RULES
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum QualifiedRule<'tokens, 'src> {
-    Generic(GenericRule<'tokens, 'src>),
-    Style(StyleRule<'tokens, 'src>),
-    Mixin(MixinRule<'tokens, 'src>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QualifiedRule<S> {
+    Generic(GenericRule<S>),
+    Style(StyleRule<S>),
+    Mixin(MixinRule<S>),
+    Keyframe(KeyframeRule<S>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct GenericRule<'tokens, 'src> {
-    // TODO: Support LESS interpolation in prelude? We certainly don't want to do so for MixinRules.
-    pub prelude: ListOfComponentValues<'tokens, 'src>,
-    pub block: ListOfItems<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenericRule<S> {
+    /// The prelude, in which LESS interpolations are kept as
+    /// [`Token::Interpolation`] tokens.
+    pub prelude: ListOfComponentValues<S>,
+    pub block: ListOfItems<S>,
 }
 
 // TODO: Placeholder type
-type Guard<'tokens, 'src> = ListOfComponentValues<'tokens, 'src>;
+type Guard<S> = ListOfComponentValues<S>;
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct StyleRule<'tokens, 'src> {
-    pub selectors: ListOfComponentValues<'tokens, 'src>,
-    pub guard: Option<Guard<'tokens, 'src>>,
-    pub block: ListOfItems<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StyleRule<S> {
+    pub selectors: SelectorList<S>,
+    pub guard: Option<Guard<S>>,
+    pub block: ListOfItems<S>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct MixinRule<'tokens, 'src> {
-    pub name: &'src str,
-    pub arguments: ListOfComponentValues<'tokens, 'src>,
-    pub guard: Option<Guard<'tokens, 'src>>,
-    pub block: ListOfItems<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MixinRule<S> {
+    pub name: S,
+    pub arguments: ListOfComponentValues<S>,
+    pub guard: Option<Guard<S>>,
+    pub block: ListOfItems<S>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyframeRule<S> {
+    pub selectors: Vec<Spanned<KeyframeSelector<S>>>,
+    pub block: ListOfItems<S>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyframeSelector<S> {
+    From,
+    To,
+    /// A percentage, with the source text of its number (e.g. `33.333` in `33.333%`)
+    Percentage(S),
+}
+
+// SELECTORS
+
+/// A comma-separated list of selectors (e.g. `.a, .b > .c`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelectorList<S>(pub Vec<Spanned<Selector<S>>>);
+
+/// A complex selector with the extends attached to it (e.g. `.a > .b:extend(.c all)`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Selector<S> {
+    pub components: Vec<Spanned<SelectorComponent<S>>>,
+    pub extends: Vec<Spanned<Extend<S>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectorComponent<S> {
+    Combinator(Combinator),
+    /// The parent selector (`&`)
+    Parent,
+    /// The universal selector (`*`)
+    Universal,
+    Type(SelectorName<S>),
+    Class(SelectorName<S>),
+    Id(SelectorName<S>),
+    /// An attribute selector (e.g. `[type="text"]` or `[data-@{attr}]`)
+    Attribute {
+        name: SelectorName<S>,
+        matcher: Option<AttributeMatcher<S>>,
+    },
+    /// A pseudo-class (e.g. `:hover` or `:not(.a)`)
+    PseudoClass {
+        name: S,
+        arguments: Option<ListOfComponentValues<S>>,
+    },
+    /// A pseudo-element (e.g. `::before`)
+    PseudoElement {
+        name: S,
+        arguments: Option<ListOfComponentValues<S>>,
+    },
+}
+
+/// A name in a selector (e.g. `btn`), which may contain LESS interpolation (e.g. `@{prefix}-btn`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectorName<S> {
+    Ident(S),
+    InterpolatedIdent(InterpolatedIdent<S>),
+}
+
+/// The part of an attribute selector after the name (e.g. `="text" i` in `[type="text" i]`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeMatcher<S> {
+    pub operator: AttributeOperator,
+    pub value: AttributeValue<S>,
+    /// The case-sensitivity modifier (e.g. `i` or `s`)
+    pub modifier: Option<S>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeOperator {
+    /// `[a=b]`
+    Equals,
+    /// `[a~=b]`
+    Includes,
+    /// `[a|=b]`
+    DashMatch,
+    /// `[a^=b]`
+    Prefix,
+    /// `[a$=b]`
+    Suffix,
+    /// `[a*=b]`
+    Substring,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeValue<S> {
+    Ident(SelectorName<S>),
+    String(InterpolatedString<S>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Combinator {
+    /// `a b`
+    Descendant,
+    /// `a > b`
+    Child,
+    /// `a + b`
+    NextSibling,
+    /// `a ~ b`
+    SubsequentSibling,
+}
+
+// EXTENDS
+
+/// An extend, either attached to a selector (e.g. `.a:extend(.b all) {}`) or as an item in a
+/// rule's block (e.g. `&:extend(.b all);`), which extends the owning selectors.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Extend<S> {
+    pub targets: Vec<Spanned<ExtendTarget<S>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtendTarget<S> {
+    pub selector: Selector<S>,
+    /// Whether every occurrence of the selector should be extended (e.g. `.b all`), instead of
+    /// only exact matches.
+    pub all: bool,
 }
 
 // DECLARATIONS
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct Declaration<'tokens, 'src> {
-    pub name: DeclarationName<'tokens, 'src>,
-    pub value: ListOfComponentValues<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Declaration<S> {
+    pub name: DeclarationName<S>,
+    pub value: Value<S>,
     pub important: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum DeclarationName<'tokens, 'src> {
-    Ident(&'src str),
-    InterpolatedIdent(ListOfComponentValues<'tokens, 'src>),
-    Variable(&'src str),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeclarationName<S> {
+    Ident(S),
+    InterpolatedIdent(InterpolatedIdent<S>),
+    Variable(S),
+}
+
+/// The value of a variable declaration, or an argument of a mixin call or function call item
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value<S> {
+    ComponentValues(ListOfComponentValues<S>),
+    /// A detached ruleset (e.g. `{ color: blue; }`), which is also used for maps
+    DetachedRuleset(ListOfItems<S>),
 }
 
 // CALLS
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum Call<'tokens, 'src> {
-    Mixin(MixinCall<'tokens, 'src>),
-    Variable(VariableCall<'tokens, 'src>),
-    Function(FunctionCall<'tokens, 'src>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Call<S> {
+    Mixin(MixinCall<S>),
+    Variable(VariableCall<S>),
+    Function(FunctionCallItem<S>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MixinCall<S> {
+    pub selector: ListOfComponentValues<S>,
+    pub arguments: Vec<Spanned<MixinArgument<S>>>,
+    /// Whether the declarations of the mixin are made `!important` (e.g. `.mixin() !important;`)
+    pub important: bool,
+}
+
+/// An argument of a [`MixinCall`] (e.g. `blue`, `@color: blue` or `{ color: blue; }`)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MixinArgument<S> {
+    pub name: Option<S>,
+    pub value: Value<S>,
 }
 
+/// A call of a detached ruleset (e.g. `@detached-ruleset();`). Lookups in a detached ruleset (e.g.
+/// `@config[key]` or `@config[@dark][primary]`) are values, so they're kept as component values
+/// and evaluated by the compiler.
 #[derive(Clone, Debug, PartialEq)]
-pub struct MixinCall<'tokens, 'src> {
-    pub selector: ListOfComponentValues<'tokens, 'src>,
-    pub arguments: ListOfComponentValues<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableCall<S> {
+    pub name: S,
 }
 
+/// A function called as an item (e.g. `each(@list, { color: @value; });`), whose comma-separated
+/// arguments may be detached rulesets.
 #[derive(Clone, Debug, PartialEq)]
-pub struct VariableCall<'tokens, 'src> {
-    pub name: &'src str,
-    // TODO: Support lookups.
-    pub _lookups: PhantomData<&'tokens ()>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionCallItem<S> {
+    pub name: S,
+    pub arguments: Vec<Spanned<Value<S>>>,
 }
 
+/// A function in a value or condition (e.g. `selector(a > b)`), whose arguments are kept as-is.
 #[derive(Clone, Debug, PartialEq)]
-pub struct FunctionCall<'tokens, 'src> {
-    pub name: &'src str,
-    pub arguments: ListOfComponentValues<'tokens, 'src>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionCall<S> {
+    pub name: S,
+    pub arguments: ListOfComponentValues<S>,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_serde() {
+        let input =
+            r#".a:hover > .b { @{prop}: fade(@c, 50%) !important; } @import "@{dir}/x.less";"#;
+        let stylesheet = parse(input).unwrap();
+        let json = serde_json::to_value(&stylesheet).unwrap();
+
+        // Spanned nodes are serialized as `[node, span]`
+        assert_eq!(json["items"][0][1]["start"], 0);
+        assert_eq!(json["items"][0][1]["end"], 52);
+
+        // Strings can be deserialized into any string type
+        let deserialized: Stylesheet<String> = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, stylesheet.map_strings(&mut String::from));
+    }
 }
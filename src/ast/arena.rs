@@ -0,0 +1,956 @@
+//! An arena-allocated syntax tree, in which every node has a stable [`NodeId`].
+//!
+//! The parser builds the arena directly (see [`crate::parse_arena`]): each node is pushed as soon
+//! as it's parsed and refers to its children by id, so a stylesheet takes two flat `Vec`s instead
+//! of a `Vec` per block, selector, value and token tree. Ids are assigned in source order and can
+//! be used as keys of side tables ([`NodeMap`]), e.g. for resolved variables, types or diagnostics.
+//! Every node links to its parent.
+//!
+//! The nodes mirror the owned syntax tree: a [`NodeKind`] holds the fields of its node that aren't
+//! nodes themselves, and documents the kinds and order of its children. [`Arena::to_stylesheet`]
+//! converts the arena into an owned [`Stylesheet`].
+
+use std::ops::{Index, Range};
+
+use crate::ast::*;
+
+/// The index of a node in an [`Arena`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeId(u32);
+
+impl NodeId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The kind of a node, with the fields that aren't child nodes.
+///
+/// Names in selectors, declarations and keyframes at-rules are [`NodeKind::Ident`],
+/// [`NodeKind::String`], [`NodeKind::Variable`] or [`NodeKind::InterpolatedIdent`] nodes, and
+/// values are [`NodeKind::ComponentValues`] or, for detached rulesets, [`NodeKind::Block`] nodes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeKind<S> {
+    // STRUCTURE
+    /// The root node. Children: the items.
+    Stylesheet,
+    /// A block of items (see [`ListOfItems`]). Children: the items.
+    Block,
+    /// See [`ListOfComponentValues`]. Children: the [`NodeKind::Token`] and [`NodeKind::Tree`]
+    /// nodes.
+    ComponentValues,
+    Token(Token<S>),
+    /// A [`TokenTree::Tree`]. Children: the token trees inside of the delimiters.
+    Tree(Delim),
+    /// See [`InterpolatedIdent`]. Children: the [`NodeKind::InterpolationPart`] nodes.
+    InterpolatedIdent,
+    /// See [`InterpolatedString`]. Children: the [`NodeKind::InterpolationPart`] nodes.
+    InterpolatedString,
+    InterpolationPart(InterpolationPart<S>),
+    /// A name without interpolation (e.g. `btn` or `color`)
+    Ident(S),
+    /// A quoted name (e.g. `"fade"` in `@keyframes "fade"`)
+    String(S),
+    /// A variable name (e.g. `color` in `@color: blue;`)
+    Variable(S),
+
+    // AT-RULES
+    /// See [`GenericAtRule`]. Children: the prelude and the block, if any.
+    GenericAtRule {
+        name: S,
+    },
+    /// See [`KeyframesAtRule`]. Children: the name and the block.
+    KeyframesAtRule {
+        vendor_prefix: Option<S>,
+    },
+    /// See [`ImportAtRule`]. Children: the [`NodeKind::ImportOption`] nodes, the URL and the
+    /// media queries.
+    ImportAtRule,
+    ImportOption(ImportOption),
+    /// A [`UrlOrString::Url`]. Children: the [`NodeKind::InterpolatedString`]. A
+    /// [`UrlOrString::String`] is an [`NodeKind::InterpolatedString`] node.
+    Url,
+    /// See [`SupportsAtRule`]. Children: the condition and the block.
+    SupportsAtRule,
+    /// See [`ContainerAtRule`]. Children: the condition, if any, and the block.
+    ContainerAtRule {
+        name: Option<S>,
+    },
+    /// A [`Condition::Not`]. Children: the negated condition. The features of a condition are the
+    /// nodes of their kind (e.g. [`NodeKind::FunctionCall`]).
+    Not,
+    /// A [`Condition::And`]. Children: the conditions.
+    And,
+    /// A [`Condition::Or`]. Children: the conditions.
+    Or,
+    /// See [`LayerAtRule`]. Children: the [`NodeKind::LayerName`] nodes and the block, if any.
+    LayerAtRule,
+    /// See [`LayerName`]. Children: the [`NodeKind::Ident`] nodes.
+    LayerName,
+    /// See [`FontFaceAtRule`]. Children: the block.
+    FontFaceAtRule,
+    /// See [`PageAtRule`]. Children: the [`NodeKind::PageSelector`] nodes and the block.
+    PageAtRule,
+    /// See [`PageSelector`]. Children: the pseudo-classes, as [`NodeKind::Ident`] nodes.
+    PageSelector {
+        name: Option<S>,
+    },
+    /// See [`PageMarginAtRule`]. Children: the block.
+    PageMarginAtRule {
+        name: S,
+    },
+    /// See [`NamespaceAtRule`]. Children: the URL.
+    NamespaceAtRule {
+        prefix: Option<S>,
+    },
+    /// See [`PropertyAtRule`]. Children: the block.
+    PropertyAtRule {
+        name: S,
+    },
+
+    // QUALIFIED RULES
+    /// See [`GenericRule`]. Children: the prelude and the block.
+    GenericRule,
+    /// See [`StyleRule`]. Children: the [`NodeKind::SelectorList`], the guard, if any, and the
+    /// block.
+    StyleRule,
+    /// See [`MixinRule`]. Children: the arguments, the guard, if any, and the block.
+    MixinRule {
+        name: S,
+    },
+    /// See [`KeyframeRule`]. Children: the [`NodeKind::KeyframeSelector`] nodes and the block.
+    KeyframeRule,
+    KeyframeSelector(KeyframeSelector<S>),
+
+    // SELECTORS
+    /// See [`SelectorList`]. Children: the [`NodeKind::Selector`] nodes.
+    SelectorList,
+    /// See [`Selector`]. Children: the components and the [`NodeKind::Extend`] nodes.
+    Selector,
+    Combinator(Combinator),
+    /// The parent selector (`&`)
+    Parent,
+    /// The universal selector (`*`)
+    Universal,
+    /// A [`SelectorComponent::Type`]. Children: the name.
+    Type,
+    /// A [`SelectorComponent::Class`]. Children: the name.
+    Class,
+    /// A [`SelectorComponent::Id`]. Children: the name.
+    Id,
+    /// A [`SelectorComponent::Attribute`]. Children: the name and the
+    /// [`NodeKind::AttributeMatcher`], if any.
+    Attribute,
+    /// See [`AttributeMatcher`]. Children: the value, which is a name or a
+    /// [`NodeKind::InterpolatedString`].
+    AttributeMatcher {
+        operator: AttributeOperator,
+        modifier: Option<S>,
+    },
+    /// A [`SelectorComponent::PseudoClass`]. Children: the arguments, if any.
+    PseudoClass {
+        name: S,
+    },
+    /// A [`SelectorComponent::PseudoElement`]. Children: the arguments, if any.
+    PseudoElement {
+        name: S,
+    },
+
+    // EXTENDS
+    /// See [`Extend`]. Children: the [`NodeKind::ExtendTarget`] nodes.
+    Extend,
+    /// See [`ExtendTarget`]. Children: the [`NodeKind::Selector`].
+    ExtendTarget {
+        all: bool,
+    },
+
+    // DECLARATIONS
+    /// See [`Declaration`]. Children: the name and the value.
+    Declaration {
+        important: bool,
+    },
+
+    // CALLS
+    /// See [`MixinCall`]. Children: the selector and the [`NodeKind::MixinArgument`] nodes.
+    MixinCall {
+        important: bool,
+    },
+    /// See [`MixinArgument`]. Children: the value.
+    MixinArgument {
+        name: Option<S>,
+    },
+    /// See [`VariableCall`].
+    VariableCall {
+        name: S,
+    },
+    /// See [`FunctionCallItem`]. Children: the arguments.
+    FunctionCallItem {
+        name: S,
+    },
+    /// See [`FunctionCall`]. Children: the arguments.
+    FunctionCall {
+        name: S,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<S> {
+    pub kind: NodeKind<S>,
+    pub span: Span,
+    parent: Option<NodeId>,
+    /// The range of the node's children in [`Arena::children`]
+    children: Range<u32>,
+}
+
+impl<S> Node<S> {
+    pub fn parent(&self) -> Option<NodeId> {
+        self.parent
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Arena<S> {
+    nodes: Vec<Node<S>>,
+    /// The children of all nodes, with those of each node next to each other
+    children: Vec<NodeId>,
+}
+
+impl<S> Arena<S> {
+    pub(crate) fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the id of the [`NodeKind::Stylesheet`] node.
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns the ids of all nodes, in source order (i.e. each node before its children).
+    pub fn ids(&self) -> impl ExactSizeIterator<Item = NodeId> {
+        (0..self.nodes.len() as u32).map(NodeId)
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        let children = &self[id].children;
+        &self.children[children.start as usize..children.end as usize]
+    }
+
+    /// Returns the ids of the node's ancestors, from its parent up to the root.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self[id].parent, |&id| self[id].parent)
+    }
+
+    // BUILDING
+
+    /// Pushes a node whose children have already been pushed.
+    pub(crate) fn push(
+        &mut self,
+        kind: NodeKind<S>,
+        span: Span,
+        children: impl IntoIterator<Item = NodeId>,
+    ) -> NodeId {
+        let start = self.children.len() as u32;
+        self.children.extend(children);
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(Node {
+            kind,
+            span,
+            parent: None,
+            children: start..self.children.len() as u32,
+        });
+        id
+    }
+
+    /// Pushes a [`NodeKind::InterpolatedIdent`] or [`NodeKind::InterpolatedString`] node with its
+    /// parts.
+    pub(crate) fn push_interpolated(
+        &mut self,
+        kind: NodeKind<S>,
+        span: Span,
+        parts: Vec<Spanned<InterpolationPart<S>>>,
+    ) -> NodeId {
+        let start = self.nodes.len() as u32;
+        for (part, span) in parts {
+            self.push(NodeKind::InterpolationPart(part), span, []);
+        }
+        self.push(kind, span, (start..self.nodes.len() as u32).map(NodeId))
+    }
+
+    /// Pushes a [`NodeKind::ComponentValues`] node with a copy of the token trees.
+    pub(crate) fn push_component_values(
+        &mut self,
+        span: Span,
+        tts: &[Spanned<TokenTree<S>>],
+    ) -> NodeId
+    where
+        S: Clone,
+    {
+        let id = self.push(NodeKind::ComponentValues, span, []);
+        self.push_token_trees(id, tts);
+        id
+    }
+
+    /// Pushes token trees as the children of a node, before their own children.
+    fn push_token_trees(&mut self, parent: NodeId, tts: &[Spanned<TokenTree<S>>])
+    where
+        S: Clone,
+    {
+        // Reserve the children first, so they stay next to each other
+        let start = self.children.len();
+        self.children.extend(std::iter::repeat_n(parent, tts.len()));
+        self.nodes[parent.index()].children = start as u32..self.children.len() as u32;
+
+        for (i, (tt, span)) in tts.iter().enumerate() {
+            self.children[start + i] = match tt {
+                TokenTree::Token(token) => self.push(NodeKind::Token(token.clone()), *span, []),
+                TokenTree::Tree(delim, tts) => {
+                    let id = self.push(NodeKind::Tree(*delim), *span, []);
+                    self.push_token_trees(id, tts);
+                    id
+                }
+            };
+        }
+    }
+
+    pub(crate) fn set_span(&mut self, id: NodeId, span: Span) {
+        self.nodes[id.index()].span = span;
+    }
+
+    /// Finishes building the arena from its root: nodes that aren't descendants of the root (i.e.
+    /// that were pushed by a parser that backtracked) are removed, ids are reassigned in source
+    /// order and parents are linked.
+    pub(crate) fn finish(self, root: NodeId) -> Self {
+        // Number the nodes in pre-order, keeping each one's new parent
+        let mut order = Vec::new();
+        let mut ids = vec![u32::MAX; self.nodes.len()];
+        let mut stack = vec![(root, None)];
+        while let Some((id, parent)) = stack.pop() {
+            ids[id.index()] = order.len() as u32;
+            order.push((id, parent));
+            let new_id = Some(NodeId(ids[id.index()]));
+            let children = &self.nodes[id.index()].children;
+            let children = &self.children[children.start as usize..children.end as usize];
+            stack.extend(children.iter().rev().map(|&child| (child, new_id)));
+        }
+
+        let mut nodes: Vec<_> = self.nodes.into_iter().map(Some).collect();
+        let mut arena = Self {
+            nodes: Vec::with_capacity(order.len()),
+            children: Vec::with_capacity(order.len().saturating_sub(1)),
+        };
+        for (id, parent) in order {
+            let node = nodes[id.index()].take().unwrap();
+            let children = node.children.start as usize..node.children.end as usize;
+            let start = arena.children.len() as u32;
+            arena.children.extend(
+                self.children[children]
+                    .iter()
+                    .map(|child| NodeId(ids[child.index()])),
+            );
+            arena.nodes.push(Node {
+                kind: node.kind,
+                span: node.span,
+                parent,
+                children: start..arena.children.len() as u32,
+            });
+        }
+        arena
+    }
+}
+
+impl<S> Index<NodeId> for Arena<S> {
+    type Output = Node<S>;
+
+    fn index(&self, id: NodeId) -> &Node<S> {
+        &self.nodes[id.index()]
+    }
+}
+
+// CONVERSION
+
+impl<S: Clone> Arena<S> {
+    /// Converts the arena into an owned [`Stylesheet`].
+    pub fn to_stylesheet(&self) -> Stylesheet<S> {
+        Stylesheet {
+            items: self.list_of_items(self.root()),
+        }
+    }
+
+    fn kind(&self, id: NodeId) -> &NodeKind<S> {
+        &self[id].kind
+    }
+
+    fn spanned<T>(&self, id: NodeId, f: impl FnOnce(&Self, NodeId) -> T) -> Spanned<T> {
+        (f(self, id), self[id].span)
+    }
+
+    /// Splits off the last child if it's a block.
+    fn split_block(&self, id: NodeId) -> (&[NodeId], Option<ListOfItems<S>>) {
+        match self.children(id).split_last() {
+            Some((&block, rest)) if matches!(self.kind(block), NodeKind::Block) => {
+                (rest, Some(self.list_of_items(block)))
+            }
+            _ => (self.children(id), None),
+        }
+    }
+
+    fn list_of_items(&self, id: NodeId) -> ListOfItems<S> {
+        ListOfItems(
+            self.children(id)
+                .iter()
+                .map(|&item| self.spanned(item, Self::item))
+                .collect(),
+        )
+    }
+
+    fn list_of_component_values(&self, id: NodeId) -> ListOfComponentValues<S> {
+        ListOfComponentValues(self.token_trees(id))
+    }
+
+    fn token_trees(&self, id: NodeId) -> Vec<Spanned<TokenTree<S>>> {
+        self.children(id)
+            .iter()
+            .map(|&tt| {
+                self.spanned(tt, |arena, tt| match arena.kind(tt) {
+                    NodeKind::Token(token) => TokenTree::Token(token.clone()),
+                    NodeKind::Tree(delim) => TokenTree::Tree(*delim, arena.token_trees(tt)),
+                    _ => unreachable!("expected a token tree"),
+                })
+            })
+            .collect()
+    }
+
+    fn interpolation_parts(&self, id: NodeId) -> Vec<Spanned<InterpolationPart<S>>> {
+        self.children(id)
+            .iter()
+            .map(|&part| match self.kind(part) {
+                NodeKind::InterpolationPart(part_) => (part_.clone(), self[part].span),
+                _ => unreachable!("expected an interpolation part"),
+            })
+            .collect()
+    }
+
+    fn item(&self, id: NodeId) -> Item<S> {
+        let children = self.children(id);
+        match self.kind(id) {
+            NodeKind::GenericAtRule { name } => Item::AtRule(AtRule::Generic(GenericAtRule {
+                name: name.clone(),
+                prelude: self.list_of_component_values(children[0]),
+                block: self.split_block(id).1,
+            })),
+            NodeKind::KeyframesAtRule { vendor_prefix } => {
+                let name = match self.kind(children[0]) {
+                    NodeKind::Ident(name) => KeyframesName::Ident(name.clone()),
+                    NodeKind::String(name) => KeyframesName::String(name.clone()),
+                    NodeKind::Variable(name) => KeyframesName::Variable(name.clone()),
+                    _ => KeyframesName::InterpolatedIdent(self.interpolated_ident(children[0])),
+                };
+                Item::AtRule(AtRule::Keyframes(KeyframesAtRule {
+                    vendor_prefix: vendor_prefix.clone(),
+                    name,
+                    block: self.list_of_items(children[1]),
+                }))
+            }
+            NodeKind::ImportAtRule => {
+                let [options @ .., url, media_queries] = children else {
+                    unreachable!("an import at-rule has a URL and media queries")
+                };
+                let options = options
+                    .iter()
+                    .map(|&option| match self.kind(option) {
+                        NodeKind::ImportOption(option_) => (*option_, self[option].span),
+                        _ => unreachable!("expected an import option"),
+                    })
+                    .collect();
+                Item::AtRule(AtRule::Import(ImportAtRule {
+                    options,
+                    url: self.spanned(*url, Self::url_or_string),
+                    media_queries: self.list_of_component_values(*media_queries),
+                }))
+            }
+            NodeKind::SupportsAtRule => Item::AtRule(AtRule::Supports(SupportsAtRule {
+                condition: self.condition(children[0], &Self::supports_feature),
+                block: self.list_of_items(children[1]),
+            })),
+            NodeKind::ContainerAtRule { name } => {
+                let (condition, block) = self.split_block(id);
+                Item::AtRule(AtRule::Container(ContainerAtRule {
+                    name: name.clone(),
+                    condition: condition
+                        .first()
+                        .map(|&condition| self.condition(condition, &Self::container_feature)),
+                    block: block.unwrap(),
+                }))
+            }
+            NodeKind::LayerAtRule => {
+                let (names, block) = self.split_block(id);
+                let names = names
+                    .iter()
+                    .map(|&name| {
+                        self.spanned(name, |arena, name| {
+                            LayerName(
+                                arena
+                                    .children(name)
+                                    .iter()
+                                    .map(|&part| arena.spanned(part, Self::ident))
+                                    .collect(),
+                            )
+                        })
+                    })
+                    .collect();
+                Item::AtRule(AtRule::Layer(LayerAtRule { names, block }))
+            }
+            NodeKind::FontFaceAtRule => Item::AtRule(AtRule::FontFace(FontFaceAtRule {
+                block: self.list_of_items(children[0]),
+            })),
+            NodeKind::PageAtRule => {
+                let (selectors, block) = self.split_block(id);
+                let selectors = selectors
+                    .iter()
+                    .map(|&selector| {
+                        self.spanned(selector, |arena, selector| {
+                            let NodeKind::PageSelector { name } = arena.kind(selector) else {
+                                unreachable!("a page at-rule has page selectors")
+                            };
+                            PageSelector {
+                                name: name.clone(),
+                                pseudo_classes: arena
+                                    .children(selector)
+                                    .iter()
+                                    .map(|&pseudo_class| arena.spanned(pseudo_class, Self::ident))
+                                    .collect(),
+                            }
+                        })
+                    })
+                    .collect();
+                Item::AtRule(AtRule::Page(PageAtRule {
+                    selectors,
+                    block: block.unwrap(),
+                }))
+            }
+            NodeKind::PageMarginAtRule { name } => {
+                Item::AtRule(AtRule::PageMargin(PageMarginAtRule {
+                    name: name.clone(),
+                    block: self.list_of_items(children[0]),
+                }))
+            }
+            NodeKind::NamespaceAtRule { prefix } => {
+                Item::AtRule(AtRule::Namespace(NamespaceAtRule {
+                    prefix: prefix.clone(),
+                    url: self.spanned(children[0], Self::url_or_string),
+                }))
+            }
+            NodeKind::PropertyAtRule { name } => Item::AtRule(AtRule::Property(PropertyAtRule {
+                name: name.clone(),
+                block: self.list_of_items(children[0]),
+            })),
+            NodeKind::GenericRule => Item::QualifiedRule(QualifiedRule::Generic(GenericRule {
+                prelude: self.list_of_component_values(children[0]),
+                block: self.list_of_items(children[1]),
+            })),
+            NodeKind::StyleRule => {
+                let (selectors, guard, block) = match children {
+                    [selectors, block] => (selectors, None, block),
+                    [selectors, guard, block] => (selectors, Some(guard), block),
+                    _ => unreachable!("a style rule has selectors and a block"),
+                };
+                Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                    selectors: SelectorList(
+                        self.children(*selectors)
+                            .iter()
+                            .map(|&selector| self.spanned(selector, Self::selector))
+                            .collect(),
+                    ),
+                    guard: guard.map(|&guard| self.list_of_component_values(guard)),
+                    block: self.list_of_items(*block),
+                }))
+            }
+            NodeKind::MixinRule { name } => {
+                let (arguments, guard, block) = match children {
+                    [arguments, block] => (arguments, None, block),
+                    [arguments, guard, block] => (arguments, Some(guard), block),
+                    _ => unreachable!("a mixin rule has arguments and a block"),
+                };
+                Item::QualifiedRule(QualifiedRule::Mixin(MixinRule {
+                    name: name.clone(),
+                    arguments: self.list_of_component_values(*arguments),
+                    guard: guard.map(|&guard| self.list_of_component_values(guard)),
+                    block: self.list_of_items(*block),
+                }))
+            }
+            NodeKind::KeyframeRule => {
+                let (selectors, block) = self.split_block(id);
+                let selectors = selectors
+                    .iter()
+                    .map(|&selector| match self.kind(selector) {
+                        NodeKind::KeyframeSelector(selector_) => {
+                            (selector_.clone(), self[selector].span)
+                        }
+                        _ => unreachable!("expected a keyframe selector"),
+                    })
+                    .collect();
+                Item::QualifiedRule(QualifiedRule::Keyframe(KeyframeRule {
+                    selectors,
+                    block: block.unwrap(),
+                }))
+            }
+            NodeKind::Declaration { .. } => Item::Declaration(self.declaration(id)),
+            NodeKind::MixinCall { important } => Item::Call(Call::Mixin(MixinCall {
+                selector: self.list_of_component_values(children[0]),
+                arguments: children[1..]
+                    .iter()
+                    .map(|&argument| {
+                        self.spanned(argument, |arena, argument| {
+                            let NodeKind::MixinArgument { name } = arena.kind(argument) else {
+                                unreachable!("a mixin call has mixin arguments")
+                            };
+                            MixinArgument {
+                                name: name.clone(),
+                                value: arena.value(arena.children(argument)[0]),
+                            }
+                        })
+                    })
+                    .collect(),
+                important: *important,
+            })),
+            NodeKind::VariableCall { name } => {
+                Item::Call(Call::Variable(VariableCall { name: name.clone() }))
+            }
+            NodeKind::FunctionCallItem { name } => Item::Call(Call::Function(FunctionCallItem {
+                name: name.clone(),
+                arguments: children
+                    .iter()
+                    .map(|&argument| self.spanned(argument, Self::value))
+                    .collect(),
+            })),
+            NodeKind::Extend => Item::Extend(self.extend(id)),
+            _ => unreachable!("expected an item"),
+        }
+    }
+
+    fn ident(&self, id: NodeId) -> S {
+        match self.kind(id) {
+            NodeKind::Ident(ident) => ident.clone(),
+            _ => unreachable!("expected an identifier"),
+        }
+    }
+
+    fn interpolated_ident(&self, id: NodeId) -> InterpolatedIdent<S> {
+        InterpolatedIdent(self.interpolation_parts(id))
+    }
+
+    fn interpolated_string(&self, id: NodeId) -> InterpolatedString<S> {
+        InterpolatedString(self.interpolation_parts(id))
+    }
+
+    fn url_or_string(&self, id: NodeId) -> UrlOrString<S> {
+        match self.kind(id) {
+            NodeKind::Url => UrlOrString::Url(self.interpolated_string(self.children(id)[0])),
+            _ => UrlOrString::String(self.interpolated_string(id)),
+        }
+    }
+
+    fn condition<T>(
+        &self,
+        id: NodeId,
+        feature: &impl Fn(&Self, NodeId) -> T,
+    ) -> Spanned<Condition<T>> {
+        let conditions = || {
+            self.children(id)
+                .iter()
+                .map(|&condition| self.condition(condition, feature))
+                .collect()
+        };
+        let condition = match self.kind(id) {
+            NodeKind::Not => {
+                Condition::Not(Box::new(self.condition(self.children(id)[0], feature)))
+            }
+            NodeKind::And => Condition::And(conditions()),
+            NodeKind::Or => Condition::Or(conditions()),
+            _ => Condition::Feature(feature(self, id)),
+        };
+        (condition, self[id].span)
+    }
+
+    fn supports_feature(&self, id: NodeId) -> SupportsFeature<S> {
+        match self.kind(id) {
+            NodeKind::Declaration { .. } => SupportsFeature::Declaration(self.declaration(id)),
+            NodeKind::FunctionCall { .. } => SupportsFeature::Function(self.function_call(id)),
+            _ => SupportsFeature::GeneralEnclosed(self.list_of_component_values(id)),
+        }
+    }
+
+    fn container_feature(&self, id: NodeId) -> ContainerFeature<S> {
+        match self.kind(id) {
+            NodeKind::FunctionCall { .. } => ContainerFeature::Function(self.function_call(id)),
+            _ => ContainerFeature::Size(self.list_of_component_values(id)),
+        }
+    }
+
+    fn selector(&self, id: NodeId) -> Selector<S> {
+        let (mut components, mut extends) = (Vec::new(), Vec::new());
+        for &child in self.children(id) {
+            match self.kind(child) {
+                NodeKind::Extend => extends.push(self.spanned(child, Self::extend)),
+                _ => components.push(self.spanned(child, Self::selector_component)),
+            }
+        }
+        Selector {
+            components,
+            extends,
+        }
+    }
+
+    fn selector_component(&self, id: NodeId) -> SelectorComponent<S> {
+        let children = self.children(id);
+        let arguments = || {
+            children
+                .first()
+                .map(|&arguments| self.list_of_component_values(arguments))
+        };
+        match self.kind(id) {
+            NodeKind::Combinator(combinator) => SelectorComponent::Combinator(*combinator),
+            NodeKind::Parent => SelectorComponent::Parent,
+            NodeKind::Universal => SelectorComponent::Universal,
+            NodeKind::Type => SelectorComponent::Type(self.selector_name(children[0])),
+            NodeKind::Class => SelectorComponent::Class(self.selector_name(children[0])),
+            NodeKind::Id => SelectorComponent::Id(self.selector_name(children[0])),
+            NodeKind::Attribute => SelectorComponent::Attribute {
+                name: self.selector_name(children[0]),
+                matcher: children.get(1).map(|&matcher| {
+                    let NodeKind::AttributeMatcher { operator, modifier } = self.kind(matcher)
+                    else {
+                        unreachable!("an attribute selector has an attribute matcher")
+                    };
+                    let value = self.children(matcher)[0];
+                    AttributeMatcher {
+                        operator: *operator,
+                        value: match self.kind(value) {
+                            NodeKind::InterpolatedString => {
+                                AttributeValue::String(self.interpolated_string(value))
+                            }
+                            _ => AttributeValue::Ident(self.selector_name(value)),
+                        },
+                        modifier: modifier.clone(),
+                    }
+                }),
+            },
+            NodeKind::PseudoClass { name } => SelectorComponent::PseudoClass {
+                name: name.clone(),
+                arguments: arguments(),
+            },
+            NodeKind::PseudoElement { name } => SelectorComponent::PseudoElement {
+                name: name.clone(),
+                arguments: arguments(),
+            },
+            _ => unreachable!("expected a selector component"),
+        }
+    }
+
+    fn selector_name(&self, id: NodeId) -> SelectorName<S> {
+        match self.kind(id) {
+            NodeKind::Ident(name) => SelectorName::Ident(name.clone()),
+            _ => SelectorName::InterpolatedIdent(self.interpolated_ident(id)),
+        }
+    }
+
+    fn extend(&self, id: NodeId) -> Extend<S> {
+        let targets = self
+            .children(id)
+            .iter()
+            .map(|&target| {
+                self.spanned(target, |arena, target| {
+                    let NodeKind::ExtendTarget { all } = arena.kind(target) else {
+                        unreachable!("an extend has extend targets")
+                    };
+                    ExtendTarget {
+                        selector: arena.selector(arena.children(target)[0]),
+                        all: *all,
+                    }
+                })
+            })
+            .collect();
+        Extend { targets }
+    }
+
+    fn declaration(&self, id: NodeId) -> Declaration<S> {
+        let NodeKind::Declaration { important } = self.kind(id) else {
+            unreachable!("expected a declaration")
+        };
+        let [name, value] = self.children(id) else {
+            unreachable!("a declaration has a name and a value")
+        };
+        let name = match self.kind(*name) {
+            NodeKind::Ident(name) => DeclarationName::Ident(name.clone()),
+            NodeKind::Variable(name) => DeclarationName::Variable(name.clone()),
+            _ => DeclarationName::InterpolatedIdent(self.interpolated_ident(*name)),
+        };
+        Declaration {
+            name,
+            value: self.value(*value),
+            important: *important,
+        }
+    }
+
+    fn value(&self, id: NodeId) -> Value<S> {
+        match self.kind(id) {
+            NodeKind::Block => Value::DetachedRuleset(self.list_of_items(id)),
+            _ => Value::ComponentValues(self.list_of_component_values(id)),
+        }
+    }
+
+    fn function_call(&self, id: NodeId) -> FunctionCall<S> {
+        let NodeKind::FunctionCall { name } = self.kind(id) else {
+            unreachable!("expected a function call")
+        };
+        FunctionCall {
+            name: name.clone(),
+            arguments: self.list_of_component_values(self.children(id)[0]),
+        }
+    }
+}
+
+/// A side table of values for the nodes of an [`Arena`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeMap<T> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        if id.index() >= self.values.len() {
+            self.values.resize_with(id.index() + 1, || None);
+        }
+        self.values[id.index()].replace(value)
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.values.get(id.index())?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.values.get_mut(id.index())?.as_mut()
+    }
+
+    pub fn remove(&mut self, id: NodeId) -> Option<T> {
+        self.values.get_mut(id.index())?.take()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &T)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| Some((NodeId(i as u32), value.as_ref()?)))
+    }
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, parse_arena};
+
+    #[test]
+    fn test_arena() {
+        let input = ".a { color: red; .b, .c { color: blue; } } .d { margin: 0 auto; }";
+        let arena = parse_arena(input).unwrap();
+        assert_eq!(arena.to_stylesheet(), parse(input).unwrap());
+
+        let root = arena.root();
+        assert_eq!(arena[root].kind, NodeKind::Stylesheet);
+        assert_eq!(arena.children(root).len(), 2);
+
+        // Every node has an id, in source order
+        let kinds: Vec<_> = arena
+            .ids()
+            .map(|id| &arena[id].kind)
+            .filter(|kind| !matches!(kind, NodeKind::Token(_) | NodeKind::ComponentValues))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                &NodeKind::Stylesheet,
+                &NodeKind::StyleRule,
+                &NodeKind::SelectorList,
+                &NodeKind::Selector,
+                &NodeKind::Class,
+                &NodeKind::Ident("a"),
+                &NodeKind::Block,
+                &NodeKind::Declaration { important: false },
+                &NodeKind::Ident("color"),
+                &NodeKind::StyleRule,
+                &NodeKind::SelectorList,
+                &NodeKind::Selector,
+                &NodeKind::Class,
+                &NodeKind::Ident("b"),
+                &NodeKind::Selector,
+                &NodeKind::Class,
+                &NodeKind::Ident("c"),
+                &NodeKind::Block,
+                &NodeKind::Declaration { important: false },
+                &NodeKind::Ident("color"),
+                &NodeKind::StyleRule,
+                &NodeKind::SelectorList,
+                &NodeKind::Selector,
+                &NodeKind::Class,
+                &NodeKind::Ident("d"),
+                &NodeKind::Block,
+                &NodeKind::Declaration { important: false },
+                &NodeKind::Ident("margin"),
+            ]
+        );
+
+        // Record the depth of each declaration's value tokens in a side table
+        let mut depths = NodeMap::new();
+        for id in arena.ids() {
+            if let NodeKind::Token(Token::Ident(ident)) = arena[id].kind {
+                depths.insert(id, (ident, arena.ancestors(id).count()));
+            }
+        }
+        let depths: Vec<_> = depths.iter().map(|(_, depth)| *depth).collect();
+        assert_eq!(depths, [("red", 5), ("blue", 7), ("auto", 5)]);
+
+        // Nodes link to their parents
+        let selectors = arena
+            .ids()
+            .filter(|&id| arena[id].kind == NodeKind::Selector)
+            .collect::<Vec<_>>();
+        let parent = arena[selectors[2]].parent().unwrap();
+        assert_eq!(arena.children(parent), &selectors[1..3]);
+        assert_eq!(&input[arena[selectors[2]].span.into_range()], ".c");
+        assert_eq!(arena.ancestors(selectors[2]).last(), Some(root));
+
+        // Nodes of parsers that backtracked (here, a declaration named `a`) are removed
+        let input = "a:hover { color: red; }";
+        let arena = parse_arena(input).unwrap();
+        assert!(arena.ids().skip(1).all(|id| arena[id].parent().is_some()));
+        let idents = arena
+            .ids()
+            .filter(|&id| matches!(arena[id].kind, NodeKind::Ident(_)))
+            .count();
+        assert_eq!(idents, 2);
+        assert_eq!(arena.to_stylesheet(), parse(input).unwrap());
+    }
+}
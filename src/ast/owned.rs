@@ -0,0 +1,736 @@
+//! Conversion of the AST between string types, e.g. from strings borrowed from the source into
+//! owned, interned strings with [`Stylesheet::into_owned`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::*;
+use crate::lexer::{Span, Token};
+
+impl<'src> Stylesheet<&'src str> {
+    /// Converts the stylesheet into one that doesn't borrow from the source, so it can be stored
+    /// independently. Equal strings share a single allocation.
+    pub fn into_owned(self) -> Stylesheet<Arc<str>> {
+        let mut interner: HashMap<&'src str, Arc<str>> = HashMap::new();
+        self.map_strings(&mut |s| interner.entry(s).or_insert_with(|| Arc::from(s)).clone())
+    }
+}
+
+/// A node of the AST (or a token) whose strings can be converted from `S` into `T`.
+pub trait MapStrings<S, T> {
+    type Output;
+
+    /// Converts every string in the node with `f`, in source order.
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output;
+}
+
+impl<S, T, U: MapStrings<S, T>> MapStrings<S, T> for Vec<U> {
+    type Output = Vec<U::Output>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        self.into_iter().map(|node| node.map_strings(f)).collect()
+    }
+}
+
+impl<S, T, U: MapStrings<S, T>> MapStrings<S, T> for Option<U> {
+    type Output = Option<U::Output>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        self.map(|node| node.map_strings(f))
+    }
+}
+
+impl<S, T, U: MapStrings<S, T>> MapStrings<S, T> for Box<U> {
+    type Output = Box<U::Output>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        Box::new((*self).map_strings(f))
+    }
+}
+
+impl<S, T, U: MapStrings<S, T>> MapStrings<S, T> for (U, Span) {
+    type Output = (U::Output, Span);
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        (self.0.map_strings(f), self.1)
+    }
+}
+
+// TOKENS
+
+impl<S, T> MapStrings<S, T> for Token<S> {
+    type Output = Token<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            Token::Whitespace => Token::Whitespace,
+            Token::Comment(s) => Token::Comment(f(s)),
+            Token::Ident(s) => Token::Ident(f(s)),
+            Token::Hash(s) => Token::Hash(f(s)),
+            Token::String(s) => Token::String(f(s)),
+            Token::Url(s) => Token::Url(f(s)),
+            Token::Interpolation(s) => Token::Interpolation(f(s)),
+            Token::Number(s) => Token::Number(f(s)),
+            Token::Symbol(symbol) => Token::Symbol(symbol),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for TokenTree<S> {
+    type Output = TokenTree<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            TokenTree::Token(token) => TokenTree::Token(token.map_strings(f)),
+            TokenTree::Tree(delim, tts) => TokenTree::Tree(delim, tts.map_strings(f)),
+        }
+    }
+}
+
+// GENERAL
+
+impl<S, T> MapStrings<S, T> for Stylesheet<S> {
+    type Output = Stylesheet<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        Stylesheet {
+            items: self.items.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for ListOfItems<S> {
+    type Output = ListOfItems<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        ListOfItems(self.0.map_strings(f))
+    }
+}
+
+impl<S, T> MapStrings<S, T> for ListOfComponentValues<S> {
+    type Output = ListOfComponentValues<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        ListOfComponentValues(self.0.map_strings(f))
+    }
+}
+
+impl<S, T> MapStrings<S, T> for InterpolatedIdent<S> {
+    type Output = InterpolatedIdent<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        InterpolatedIdent(self.0.map_strings(f))
+    }
+}
+
+impl<S, T> MapStrings<S, T> for InterpolatedString<S> {
+    type Output = InterpolatedString<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        InterpolatedString(self.0.map_strings(f))
+    }
+}
+
+impl<S, T> MapStrings<S, T> for InterpolationPart<S> {
+    type Output = InterpolationPart<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            InterpolationPart::Literal(s) => InterpolationPart::Literal(f(s)),
+            InterpolationPart::Variable(s) => InterpolationPart::Variable(f(s)),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for Item<S> {
+    type Output = Item<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            Item::AtRule(at_rule) => Item::AtRule(at_rule.map_strings(f)),
+            Item::QualifiedRule(rule) => Item::QualifiedRule(rule.map_strings(f)),
+            Item::Declaration(declaration) => Item::Declaration(declaration.map_strings(f)),
+            Item::Call(call) => Item::Call(call.map_strings(f)),
+            Item::Extend(extend) => Item::Extend(extend.map_strings(f)),
+        }
+    }
+}
+
+// AT-RULES
+
+impl<S, T> MapStrings<S, T> for AtRule<S> {
+    type Output = AtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            AtRule::Generic(rule) => AtRule::Generic(rule.map_strings(f)),
+            AtRule::Keyframes(rule) => AtRule::Keyframes(rule.map_strings(f)),
+            AtRule::Import(rule) => AtRule::Import(rule.map_strings(f)),
+            AtRule::Supports(rule) => AtRule::Supports(rule.map_strings(f)),
+            AtRule::Container(rule) => AtRule::Container(rule.map_strings(f)),
+            AtRule::Layer(rule) => AtRule::Layer(rule.map_strings(f)),
+            AtRule::FontFace(rule) => AtRule::FontFace(rule.map_strings(f)),
+            AtRule::Page(rule) => AtRule::Page(rule.map_strings(f)),
+            AtRule::PageMargin(rule) => AtRule::PageMargin(rule.map_strings(f)),
+            AtRule::Namespace(rule) => AtRule::Namespace(rule.map_strings(f)),
+            AtRule::Property(rule) => AtRule::Property(rule.map_strings(f)),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for GenericAtRule<S> {
+    type Output = GenericAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        GenericAtRule {
+            name: f(self.name),
+            prelude: self.prelude.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for KeyframesAtRule<S> {
+    type Output = KeyframesAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        KeyframesAtRule {
+            vendor_prefix: self.vendor_prefix.map(&mut *f),
+            name: self.name.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for KeyframesName<S> {
+    type Output = KeyframesName<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            KeyframesName::Ident(s) => KeyframesName::Ident(f(s)),
+            KeyframesName::String(s) => KeyframesName::String(f(s)),
+            KeyframesName::Variable(s) => KeyframesName::Variable(f(s)),
+            KeyframesName::InterpolatedIdent(ident) => {
+                KeyframesName::InterpolatedIdent(ident.map_strings(f))
+            }
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for ImportAtRule<S> {
+    type Output = ImportAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        ImportAtRule {
+            options: self.options,
+            url: self.url.map_strings(f),
+            media_queries: self.media_queries.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for UrlOrString<S> {
+    type Output = UrlOrString<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            UrlOrString::String(string) => UrlOrString::String(string.map_strings(f)),
+            UrlOrString::Url(url) => UrlOrString::Url(url.map_strings(f)),
+        }
+    }
+}
+
+impl<S, T, U: MapStrings<S, T>> MapStrings<S, T> for Condition<U> {
+    type Output = Condition<U::Output>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            Condition::Not(condition) => Condition::Not(condition.map_strings(f)),
+            Condition::And(conditions) => Condition::And(conditions.map_strings(f)),
+            Condition::Or(conditions) => Condition::Or(conditions.map_strings(f)),
+            Condition::Feature(feature) => Condition::Feature(feature.map_strings(f)),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for SupportsAtRule<S> {
+    type Output = SupportsAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        SupportsAtRule {
+            condition: self.condition.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for SupportsFeature<S> {
+    type Output = SupportsFeature<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            SupportsFeature::Declaration(declaration) => {
+                SupportsFeature::Declaration(declaration.map_strings(f))
+            }
+            SupportsFeature::Function(function) => {
+                SupportsFeature::Function(function.map_strings(f))
+            }
+            SupportsFeature::GeneralEnclosed(values) => {
+                SupportsFeature::GeneralEnclosed(values.map_strings(f))
+            }
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for ContainerAtRule<S> {
+    type Output = ContainerAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        ContainerAtRule {
+            name: self.name.map(&mut *f),
+            condition: self.condition.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for ContainerFeature<S> {
+    type Output = ContainerFeature<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            ContainerFeature::Size(values) => ContainerFeature::Size(values.map_strings(f)),
+            ContainerFeature::Function(function) => {
+                ContainerFeature::Function(function.map_strings(f))
+            }
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for LayerAtRule<S> {
+    type Output = LayerAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        LayerAtRule {
+            names: self.names.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for LayerName<S> {
+    type Output = LayerName<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        LayerName(map_spanned_strings(self.0, f))
+    }
+}
+
+impl<S, T> MapStrings<S, T> for FontFaceAtRule<S> {
+    type Output = FontFaceAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        FontFaceAtRule {
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for PageAtRule<S> {
+    type Output = PageAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        PageAtRule {
+            selectors: self.selectors.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for PageSelector<S> {
+    type Output = PageSelector<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        PageSelector {
+            name: self.name.map(&mut *f),
+            pseudo_classes: map_spanned_strings(self.pseudo_classes, f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for PageMarginAtRule<S> {
+    type Output = PageMarginAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        PageMarginAtRule {
+            name: f(self.name),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for NamespaceAtRule<S> {
+    type Output = NamespaceAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        NamespaceAtRule {
+            prefix: self.prefix.map(&mut *f),
+            url: self.url.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for PropertyAtRule<S> {
+    type Output = PropertyAtRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        PropertyAtRule {
+            name: f(self.name),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+// QUALIFIED RULES
+
+impl<S, T> MapStrings<S, T> for QualifiedRule<S> {
+    type Output = QualifiedRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            QualifiedRule::Generic(rule) => QualifiedRule::Generic(rule.map_strings(f)),
+            QualifiedRule::Style(rule) => QualifiedRule::Style(rule.map_strings(f)),
+            QualifiedRule::Mixin(rule) => QualifiedRule::Mixin(rule.map_strings(f)),
+            QualifiedRule::Keyframe(rule) => QualifiedRule::Keyframe(rule.map_strings(f)),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for GenericRule<S> {
+    type Output = GenericRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        GenericRule {
+            prelude: self.prelude.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for StyleRule<S> {
+    type Output = StyleRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        StyleRule {
+            selectors: self.selectors.map_strings(f),
+            guard: self.guard.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for MixinRule<S> {
+    type Output = MixinRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        MixinRule {
+            name: f(self.name),
+            arguments: self.arguments.map_strings(f),
+            guard: self.guard.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for KeyframeRule<S> {
+    type Output = KeyframeRule<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        KeyframeRule {
+            selectors: self.selectors.map_strings(f),
+            block: self.block.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for KeyframeSelector<S> {
+    type Output = KeyframeSelector<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            KeyframeSelector::From => KeyframeSelector::From,
+            KeyframeSelector::To => KeyframeSelector::To,
+            KeyframeSelector::Percentage(s) => KeyframeSelector::Percentage(f(s)),
+        }
+    }
+}
+
+// SELECTORS
+
+impl<S, T> MapStrings<S, T> for SelectorList<S> {
+    type Output = SelectorList<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        SelectorList(self.0.map_strings(f))
+    }
+}
+
+impl<S, T> MapStrings<S, T> for Selector<S> {
+    type Output = Selector<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        Selector {
+            components: self.components.map_strings(f),
+            extends: self.extends.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for SelectorComponent<S> {
+    type Output = SelectorComponent<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            SelectorComponent::Combinator(combinator) => SelectorComponent::Combinator(combinator),
+            SelectorComponent::Parent => SelectorComponent::Parent,
+            SelectorComponent::Universal => SelectorComponent::Universal,
+            SelectorComponent::Type(name) => SelectorComponent::Type(name.map_strings(f)),
+            SelectorComponent::Class(name) => SelectorComponent::Class(name.map_strings(f)),
+            SelectorComponent::Id(name) => SelectorComponent::Id(name.map_strings(f)),
+            SelectorComponent::Attribute { name, matcher } => SelectorComponent::Attribute {
+                name: name.map_strings(f),
+                matcher: matcher.map_strings(f),
+            },
+            SelectorComponent::PseudoClass { name, arguments } => SelectorComponent::PseudoClass {
+                name: f(name),
+                arguments: arguments.map_strings(f),
+            },
+            SelectorComponent::PseudoElement { name, arguments } => {
+                SelectorComponent::PseudoElement {
+                    name: f(name),
+                    arguments: arguments.map_strings(f),
+                }
+            }
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for SelectorName<S> {
+    type Output = SelectorName<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            SelectorName::Ident(s) => SelectorName::Ident(f(s)),
+            SelectorName::InterpolatedIdent(ident) => {
+                SelectorName::InterpolatedIdent(ident.map_strings(f))
+            }
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for AttributeMatcher<S> {
+    type Output = AttributeMatcher<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        AttributeMatcher {
+            operator: self.operator,
+            value: self.value.map_strings(f),
+            modifier: self.modifier.map(&mut *f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for AttributeValue<S> {
+    type Output = AttributeValue<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            AttributeValue::Ident(name) => AttributeValue::Ident(name.map_strings(f)),
+            AttributeValue::String(string) => AttributeValue::String(string.map_strings(f)),
+        }
+    }
+}
+
+// EXTENDS
+
+impl<S, T> MapStrings<S, T> for Extend<S> {
+    type Output = Extend<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        Extend {
+            targets: self.targets.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for ExtendTarget<S> {
+    type Output = ExtendTarget<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        ExtendTarget {
+            selector: self.selector.map_strings(f),
+            all: self.all,
+        }
+    }
+}
+
+// DECLARATIONS
+
+impl<S, T> MapStrings<S, T> for Declaration<S> {
+    type Output = Declaration<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        Declaration {
+            name: self.name.map_strings(f),
+            value: self.value.map_strings(f),
+            important: self.important,
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for DeclarationName<S> {
+    type Output = DeclarationName<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            DeclarationName::Ident(s) => DeclarationName::Ident(f(s)),
+            DeclarationName::InterpolatedIdent(ident) => {
+                DeclarationName::InterpolatedIdent(ident.map_strings(f))
+            }
+            DeclarationName::Variable(s) => DeclarationName::Variable(f(s)),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for Value<S> {
+    type Output = Value<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            Value::ComponentValues(values) => Value::ComponentValues(values.map_strings(f)),
+            Value::DetachedRuleset(items) => Value::DetachedRuleset(items.map_strings(f)),
+        }
+    }
+}
+
+// CALLS
+
+impl<S, T> MapStrings<S, T> for Call<S> {
+    type Output = Call<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        match self {
+            Call::Mixin(call) => Call::Mixin(call.map_strings(f)),
+            Call::Variable(call) => Call::Variable(call.map_strings(f)),
+            Call::Function(call) => Call::Function(call.map_strings(f)),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for MixinCall<S> {
+    type Output = MixinCall<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        MixinCall {
+            selector: self.selector.map_strings(f),
+            arguments: self.arguments.map_strings(f),
+            important: self.important,
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for MixinArgument<S> {
+    type Output = MixinArgument<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        MixinArgument {
+            name: self.name.map(&mut *f),
+            value: self.value.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for VariableCall<S> {
+    type Output = VariableCall<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        VariableCall { name: f(self.name) }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for FunctionCallItem<S> {
+    type Output = FunctionCallItem<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        FunctionCallItem {
+            name: f(self.name),
+            arguments: self.arguments.map_strings(f),
+        }
+    }
+}
+
+impl<S, T> MapStrings<S, T> for FunctionCall<S> {
+    type Output = FunctionCall<T>;
+
+    fn map_strings(self, f: &mut impl FnMut(S) -> T) -> Self::Output {
+        FunctionCall {
+            name: f(self.name),
+            arguments: self.arguments.map_strings(f),
+        }
+    }
+}
+
+/// Converts spanned strings, which can't implement [`MapStrings`] themselves.
+fn map_spanned_strings<S, T>(
+    strings: Vec<Spanned<S>>,
+    f: &mut impl FnMut(S) -> T,
+) -> Vec<Spanned<T>> {
+    strings.into_iter().map(|(s, span)| (f(s), span)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_into_owned() {
+        fn owned_stylesheet(input: &str) -> Stylesheet<Arc<str>> {
+            parse(input).unwrap().into_owned()
+        }
+
+        let input = String::from(".a { color: @color; } @color: blue;");
+        let borrowed = parse(&input).unwrap();
+        let owned = owned_stylesheet(&input);
+
+        // The owned stylesheet has the same structure, outlives the source, and shares repeated
+        // strings
+        assert_eq!(owned, borrowed.map_strings(&mut |s: &str| Arc::from(s)));
+        drop(input);
+        let Item::Declaration(Declaration {
+            name: DeclarationName::Variable(definition),
+            ..
+        }) = &owned.items.0[1].0
+        else {
+            panic!("expected a variable declaration");
+        };
+        let Item::QualifiedRule(QualifiedRule::Style(rule)) = &owned.items.0[0].0 else {
+            panic!("expected a style rule");
+        };
+        let Item::Declaration(Declaration {
+            value: Value::ComponentValues(value),
+            ..
+        }) = &rule.block.0[0].0
+        else {
+            panic!("expected a declaration");
+        };
+        let Some((TokenTree::Token(Token::Ident(usage)), _)) = value.0.last() else {
+            panic!("expected an identifier");
+        };
+        assert_eq!(&**definition, "color");
+        assert!(Arc::ptr_eq(definition, usage));
+
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+        assert_send_static(&owned);
+    }
+}
@@ -0,0 +1,500 @@
+//! Traversal of the AST with [`Visit`] (by reference). See [`visit_mut`](super::visit_mut) for
+//! traversal by mutable reference.
+//!
+//! Each `visit_*` method defaults to the matching `walk_*` function, which visits the children of
+//! the node. Override a method to handle a node, and call the `walk_*` function from it to keep
+//! traversing into its children.
+//!
+//! Nodes that are always spanned in the AST (items, mixin arguments, selector components, token
+//! trees and interpolation parts) are visited along with their span.
+
+use super::*;
+use crate::lexer::{Span, Token};
+
+/// Visits the nodes of the AST by reference (e.g. to find all variable uses).
+pub trait Visit<'ast, S> {
+    fn visit_stylesheet(&mut self, stylesheet: &'ast Stylesheet<S>) {
+        walk_stylesheet(self, stylesheet)
+    }
+
+    fn visit_list_of_items(&mut self, items: &'ast ListOfItems<S>) {
+        walk_list_of_items(self, items)
+    }
+
+    fn visit_item(&mut self, item: &'ast Item<S>, span: Span) {
+        walk_item(self, item, span)
+    }
+
+    fn visit_at_rule(&mut self, at_rule: &'ast AtRule<S>) {
+        walk_at_rule(self, at_rule)
+    }
+
+    fn visit_url_or_string(&mut self, url: &'ast UrlOrString<S>) {
+        walk_url_or_string(self, url)
+    }
+
+    fn visit_supports_feature(&mut self, feature: &'ast SupportsFeature<S>) {
+        walk_supports_feature(self, feature)
+    }
+
+    fn visit_container_feature(&mut self, feature: &'ast ContainerFeature<S>) {
+        walk_container_feature(self, feature)
+    }
+
+    fn visit_qualified_rule(&mut self, rule: &'ast QualifiedRule<S>) {
+        walk_qualified_rule(self, rule)
+    }
+
+    fn visit_style_rule(&mut self, rule: &'ast StyleRule<S>) {
+        walk_style_rule(self, rule)
+    }
+
+    fn visit_mixin_rule(&mut self, rule: &'ast MixinRule<S>) {
+        walk_mixin_rule(self, rule)
+    }
+
+    fn visit_selector_list(&mut self, selectors: &'ast SelectorList<S>) {
+        walk_selector_list(self, selectors)
+    }
+
+    fn visit_selector(&mut self, selector: &'ast Selector<S>) {
+        walk_selector(self, selector)
+    }
+
+    fn visit_selector_component(&mut self, component: &'ast SelectorComponent<S>, span: Span) {
+        walk_selector_component(self, component, span)
+    }
+
+    fn visit_selector_name(&mut self, name: &'ast SelectorName<S>) {
+        walk_selector_name(self, name)
+    }
+
+    fn visit_extend(&mut self, extend: &'ast Extend<S>) {
+        walk_extend(self, extend)
+    }
+
+    fn visit_declaration(&mut self, declaration: &'ast Declaration<S>) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_declaration_name(&mut self, name: &'ast DeclarationName<S>) {
+        walk_declaration_name(self, name)
+    }
+
+    fn visit_value(&mut self, value: &'ast Value<S>) {
+        walk_value(self, value)
+    }
+
+    fn visit_call(&mut self, call: &'ast Call<S>) {
+        walk_call(self, call)
+    }
+
+    fn visit_mixin_call(&mut self, call: &'ast MixinCall<S>) {
+        walk_mixin_call(self, call)
+    }
+
+    fn visit_mixin_argument(&mut self, argument: &'ast MixinArgument<S>, span: Span) {
+        walk_mixin_argument(self, argument, span)
+    }
+
+    fn visit_variable_call(&mut self, _call: &'ast VariableCall<S>) {}
+
+    fn visit_function_call_item(&mut self, call: &'ast FunctionCallItem<S>) {
+        walk_function_call_item(self, call)
+    }
+
+    fn visit_function_call(&mut self, call: &'ast FunctionCall<S>) {
+        walk_function_call(self, call)
+    }
+
+    fn visit_interpolated_ident(&mut self, ident: &'ast InterpolatedIdent<S>) {
+        walk_interpolated_ident(self, ident)
+    }
+
+    fn visit_interpolated_string(&mut self, string: &'ast InterpolatedString<S>) {
+        walk_interpolated_string(self, string)
+    }
+
+    fn visit_interpolation_part(&mut self, _part: &'ast InterpolationPart<S>, _span: Span) {}
+
+    fn visit_list_of_component_values(&mut self, values: &'ast ListOfComponentValues<S>) {
+        walk_list_of_component_values(self, values)
+    }
+
+    fn visit_token_tree(&mut self, tt: &'ast TokenTree<S>, span: Span) {
+        walk_token_tree(self, tt, span)
+    }
+
+    fn visit_token(&mut self, _token: &'ast Token<S>, _span: Span) {}
+}
+
+pub fn walk_stylesheet<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    stylesheet: &'ast Stylesheet<S>,
+) {
+    visitor.visit_list_of_items(&stylesheet.items);
+}
+
+pub fn walk_list_of_items<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    items: &'ast ListOfItems<S>,
+) {
+    for (item, span) in &items.0 {
+        visitor.visit_item(item, *span);
+    }
+}
+
+pub fn walk_item<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    item: &'ast Item<S>,
+    _span: Span,
+) {
+    match item {
+        Item::AtRule(at_rule) => visitor.visit_at_rule(at_rule),
+        Item::QualifiedRule(rule) => visitor.visit_qualified_rule(rule),
+        Item::Declaration(declaration) => visitor.visit_declaration(declaration),
+        Item::Call(call) => visitor.visit_call(call),
+        Item::Extend(extend) => visitor.visit_extend(extend),
+    }
+}
+
+pub fn walk_at_rule<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    at_rule: &'ast AtRule<S>,
+) {
+    match at_rule {
+        AtRule::Generic(rule) => {
+            visitor.visit_list_of_component_values(&rule.prelude);
+            if let Some(block) = &rule.block {
+                visitor.visit_list_of_items(block);
+            }
+        }
+        AtRule::Keyframes(rule) => {
+            if let KeyframesName::InterpolatedIdent(ident) = &rule.name {
+                visitor.visit_interpolated_ident(ident);
+            }
+            visitor.visit_list_of_items(&rule.block);
+        }
+        AtRule::Import(rule) => {
+            visitor.visit_url_or_string(&rule.url.0);
+            visitor.visit_list_of_component_values(&rule.media_queries);
+        }
+        AtRule::Supports(rule) => {
+            walk_condition(&rule.condition.0, &mut |feature| {
+                visitor.visit_supports_feature(feature)
+            });
+            visitor.visit_list_of_items(&rule.block);
+        }
+        AtRule::Container(rule) => {
+            if let Some((condition, _)) = &rule.condition {
+                walk_condition(condition, &mut |feature| {
+                    visitor.visit_container_feature(feature)
+                });
+            }
+            visitor.visit_list_of_items(&rule.block);
+        }
+        AtRule::Layer(rule) => {
+            if let Some(block) = &rule.block {
+                visitor.visit_list_of_items(block);
+            }
+        }
+        AtRule::FontFace(FontFaceAtRule { block })
+        | AtRule::Page(PageAtRule { block, .. })
+        | AtRule::PageMargin(PageMarginAtRule { block, .. })
+        | AtRule::Property(PropertyAtRule { block, .. }) => visitor.visit_list_of_items(block),
+        AtRule::Namespace(rule) => visitor.visit_url_or_string(&rule.url.0),
+    }
+}
+
+fn walk_condition<'ast, T>(condition: &'ast Condition<T>, visit_feature: &mut impl FnMut(&'ast T)) {
+    match condition {
+        Condition::Not(condition) => walk_condition(&condition.0, visit_feature),
+        Condition::And(conditions) | Condition::Or(conditions) => {
+            for (condition, _) in conditions {
+                walk_condition(condition, visit_feature);
+            }
+        }
+        Condition::Feature(feature) => visit_feature(feature),
+    }
+}
+
+pub fn walk_url_or_string<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    url: &'ast UrlOrString<S>,
+) {
+    match url {
+        UrlOrString::String(string) | UrlOrString::Url(string) => {
+            visitor.visit_interpolated_string(string)
+        }
+    }
+}
+
+pub fn walk_supports_feature<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    feature: &'ast SupportsFeature<S>,
+) {
+    match feature {
+        SupportsFeature::Declaration(declaration) => visitor.visit_declaration(declaration),
+        SupportsFeature::Function(function) => visitor.visit_function_call(function),
+        SupportsFeature::GeneralEnclosed(values) => visitor.visit_list_of_component_values(values),
+    }
+}
+
+pub fn walk_container_feature<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    feature: &'ast ContainerFeature<S>,
+) {
+    match feature {
+        ContainerFeature::Size(values) => visitor.visit_list_of_component_values(values),
+        ContainerFeature::Function(function) => visitor.visit_function_call(function),
+    }
+}
+
+pub fn walk_qualified_rule<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    rule: &'ast QualifiedRule<S>,
+) {
+    match rule {
+        QualifiedRule::Generic(rule) => {
+            visitor.visit_list_of_component_values(&rule.prelude);
+            visitor.visit_list_of_items(&rule.block);
+        }
+        QualifiedRule::Style(rule) => visitor.visit_style_rule(rule),
+        QualifiedRule::Mixin(rule) => visitor.visit_mixin_rule(rule),
+        QualifiedRule::Keyframe(rule) => visitor.visit_list_of_items(&rule.block),
+    }
+}
+
+pub fn walk_style_rule<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    rule: &'ast StyleRule<S>,
+) {
+    visitor.visit_selector_list(&rule.selectors);
+    if let Some(guard) = &rule.guard {
+        visitor.visit_list_of_component_values(guard);
+    }
+    visitor.visit_list_of_items(&rule.block);
+}
+
+pub fn walk_mixin_rule<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    rule: &'ast MixinRule<S>,
+) {
+    visitor.visit_list_of_component_values(&rule.arguments);
+    if let Some(guard) = &rule.guard {
+        visitor.visit_list_of_component_values(guard);
+    }
+    visitor.visit_list_of_items(&rule.block);
+}
+
+pub fn walk_selector_list<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    selectors: &'ast SelectorList<S>,
+) {
+    for (selector, _) in &selectors.0 {
+        visitor.visit_selector(selector);
+    }
+}
+
+pub fn walk_selector<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    selector: &'ast Selector<S>,
+) {
+    for (component, span) in &selector.components {
+        visitor.visit_selector_component(component, *span);
+    }
+    for (extend, _) in &selector.extends {
+        visitor.visit_extend(extend);
+    }
+}
+
+pub fn walk_selector_component<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    component: &'ast SelectorComponent<S>,
+    _span: Span,
+) {
+    match component {
+        SelectorComponent::Combinator(_)
+        | SelectorComponent::Parent
+        | SelectorComponent::Universal => {}
+        SelectorComponent::Type(name)
+        | SelectorComponent::Class(name)
+        | SelectorComponent::Id(name) => visitor.visit_selector_name(name),
+        SelectorComponent::Attribute { name, matcher } => {
+            visitor.visit_selector_name(name);
+            match matcher.as_ref().map(|matcher| &matcher.value) {
+                Some(AttributeValue::Ident(name)) => visitor.visit_selector_name(name),
+                Some(AttributeValue::String(string)) => visitor.visit_interpolated_string(string),
+                None => {}
+            }
+        }
+        SelectorComponent::PseudoClass { arguments, .. }
+        | SelectorComponent::PseudoElement { arguments, .. } => {
+            if let Some(arguments) = arguments {
+                visitor.visit_list_of_component_values(arguments);
+            }
+        }
+    }
+}
+
+pub fn walk_selector_name<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    name: &'ast SelectorName<S>,
+) {
+    match name {
+        SelectorName::Ident(_) => {}
+        SelectorName::InterpolatedIdent(ident) => visitor.visit_interpolated_ident(ident),
+    }
+}
+
+pub fn walk_extend<'ast, S, V: Visit<'ast, S> + ?Sized>(visitor: &mut V, extend: &'ast Extend<S>) {
+    for (target, _) in &extend.targets {
+        visitor.visit_selector(&target.selector);
+    }
+}
+
+pub fn walk_declaration<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    declaration: &'ast Declaration<S>,
+) {
+    visitor.visit_declaration_name(&declaration.name);
+    visitor.visit_value(&declaration.value);
+}
+
+pub fn walk_declaration_name<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    name: &'ast DeclarationName<S>,
+) {
+    match name {
+        DeclarationName::Ident(_) | DeclarationName::Variable(_) => {}
+        DeclarationName::InterpolatedIdent(ident) => visitor.visit_interpolated_ident(ident),
+    }
+}
+
+pub fn walk_value<'ast, S, V: Visit<'ast, S> + ?Sized>(visitor: &mut V, value: &'ast Value<S>) {
+    match value {
+        Value::ComponentValues(values) => visitor.visit_list_of_component_values(values),
+        Value::DetachedRuleset(items) => visitor.visit_list_of_items(items),
+    }
+}
+
+pub fn walk_call<'ast, S, V: Visit<'ast, S> + ?Sized>(visitor: &mut V, call: &'ast Call<S>) {
+    match call {
+        Call::Mixin(call) => visitor.visit_mixin_call(call),
+        Call::Variable(call) => visitor.visit_variable_call(call),
+        Call::Function(call) => visitor.visit_function_call_item(call),
+    }
+}
+
+pub fn walk_mixin_call<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    call: &'ast MixinCall<S>,
+) {
+    visitor.visit_list_of_component_values(&call.selector);
+    for (argument, span) in &call.arguments {
+        visitor.visit_mixin_argument(argument, *span);
+    }
+}
+
+pub fn walk_mixin_argument<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    argument: &'ast MixinArgument<S>,
+    _span: Span,
+) {
+    visitor.visit_value(&argument.value);
+}
+
+pub fn walk_function_call_item<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    call: &'ast FunctionCallItem<S>,
+) {
+    for (argument, _) in &call.arguments {
+        visitor.visit_value(argument);
+    }
+}
+
+pub fn walk_function_call<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    call: &'ast FunctionCall<S>,
+) {
+    visitor.visit_list_of_component_values(&call.arguments);
+}
+
+pub fn walk_interpolated_ident<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    ident: &'ast InterpolatedIdent<S>,
+) {
+    for (part, span) in &ident.0 {
+        visitor.visit_interpolation_part(part, *span);
+    }
+}
+
+pub fn walk_interpolated_string<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    string: &'ast InterpolatedString<S>,
+) {
+    for (part, span) in &string.0 {
+        visitor.visit_interpolation_part(part, *span);
+    }
+}
+
+pub fn walk_list_of_component_values<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    values: &'ast ListOfComponentValues<S>,
+) {
+    for (tt, span) in &values.0 {
+        visitor.visit_token_tree(tt, *span);
+    }
+}
+
+pub fn walk_token_tree<'ast, S, V: Visit<'ast, S> + ?Sized>(
+    visitor: &mut V,
+    tt: &'ast TokenTree<S>,
+    span: Span,
+) {
+    match tt {
+        TokenTree::Token(token) => visitor.visit_token(token, span),
+        TokenTree::Tree(_, tts) => {
+            for (tt, span) in tts {
+                visitor.visit_token_tree(tt, *span);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_visit() {
+        // Collect the variables used in interpolations, in selectors, names, strings and values
+        #[derive(Default)]
+        struct Interpolations<'src>(Vec<&'src str>);
+
+        impl<'src> Visit<'_, &'src str> for Interpolations<'src> {
+            fn visit_interpolation_part(&mut self, part: &InterpolationPart<&'src str>, _: Span) {
+                if let InterpolationPart::Variable(name) = part {
+                    self.0.push(name);
+                }
+            }
+
+            fn visit_token(&mut self, token: &Token<&'src str>, _: Span) {
+                if let Token::Interpolation(name) = token {
+                    self.0.push(name);
+                }
+            }
+        }
+
+        let input = r#"
+            .@{a} { @{b}-color: red; @media @{c} { content: @{d}; } }
+            @import "@{e}/theme.less";
+            .mixin({ width: @{f}; });
+        "#;
+        let stylesheet = parse(input).unwrap();
+        let mut visitor = Interpolations::default();
+        visitor.visit_stylesheet(&stylesheet);
+        assert_eq!(visitor.0, vec!["a", "b", "c", "d", "e", "f"]);
+    }
+}
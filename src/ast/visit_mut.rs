@@ -0,0 +1,491 @@
+//! Traversal of the AST by mutable reference. See [`visit`](super::visit) for details.
+
+use super::*;
+use crate::lexer::{Span, Token};
+
+/// Visits the nodes of the AST by mutable reference (e.g. to rewrite URLs).
+pub trait VisitMut<S> {
+    fn visit_stylesheet_mut(&mut self, stylesheet: &mut Stylesheet<S>) {
+        walk_stylesheet_mut(self, stylesheet)
+    }
+
+    fn visit_list_of_items_mut(&mut self, items: &mut ListOfItems<S>) {
+        walk_list_of_items_mut(self, items)
+    }
+
+    fn visit_item_mut(&mut self, item: &mut Item<S>, span: Span) {
+        walk_item_mut(self, item, span)
+    }
+
+    fn visit_at_rule_mut(&mut self, at_rule: &mut AtRule<S>) {
+        walk_at_rule_mut(self, at_rule)
+    }
+
+    fn visit_url_or_string_mut(&mut self, url: &mut UrlOrString<S>) {
+        walk_url_or_string_mut(self, url)
+    }
+
+    fn visit_supports_feature_mut(&mut self, feature: &mut SupportsFeature<S>) {
+        walk_supports_feature_mut(self, feature)
+    }
+
+    fn visit_container_feature_mut(&mut self, feature: &mut ContainerFeature<S>) {
+        walk_container_feature_mut(self, feature)
+    }
+
+    fn visit_qualified_rule_mut(&mut self, rule: &mut QualifiedRule<S>) {
+        walk_qualified_rule_mut(self, rule)
+    }
+
+    fn visit_style_rule_mut(&mut self, rule: &mut StyleRule<S>) {
+        walk_style_rule_mut(self, rule)
+    }
+
+    fn visit_mixin_rule_mut(&mut self, rule: &mut MixinRule<S>) {
+        walk_mixin_rule_mut(self, rule)
+    }
+
+    fn visit_selector_list_mut(&mut self, selectors: &mut SelectorList<S>) {
+        walk_selector_list_mut(self, selectors)
+    }
+
+    fn visit_selector_mut(&mut self, selector: &mut Selector<S>) {
+        walk_selector_mut(self, selector)
+    }
+
+    fn visit_selector_component_mut(&mut self, component: &mut SelectorComponent<S>, span: Span) {
+        walk_selector_component_mut(self, component, span)
+    }
+
+    fn visit_selector_name_mut(&mut self, name: &mut SelectorName<S>) {
+        walk_selector_name_mut(self, name)
+    }
+
+    fn visit_extend_mut(&mut self, extend: &mut Extend<S>) {
+        walk_extend_mut(self, extend)
+    }
+
+    fn visit_declaration_mut(&mut self, declaration: &mut Declaration<S>) {
+        walk_declaration_mut(self, declaration)
+    }
+
+    fn visit_declaration_name_mut(&mut self, name: &mut DeclarationName<S>) {
+        walk_declaration_name_mut(self, name)
+    }
+
+    fn visit_value_mut(&mut self, value: &mut Value<S>) {
+        walk_value_mut(self, value)
+    }
+
+    fn visit_call_mut(&mut self, call: &mut Call<S>) {
+        walk_call_mut(self, call)
+    }
+
+    fn visit_mixin_call_mut(&mut self, call: &mut MixinCall<S>) {
+        walk_mixin_call_mut(self, call)
+    }
+
+    fn visit_mixin_argument_mut(&mut self, argument: &mut MixinArgument<S>, span: Span) {
+        walk_mixin_argument_mut(self, argument, span)
+    }
+
+    fn visit_variable_call_mut(&mut self, _call: &mut VariableCall<S>) {}
+
+    fn visit_function_call_item_mut(&mut self, call: &mut FunctionCallItem<S>) {
+        walk_function_call_item_mut(self, call)
+    }
+
+    fn visit_function_call_mut(&mut self, call: &mut FunctionCall<S>) {
+        walk_function_call_mut(self, call)
+    }
+
+    fn visit_interpolated_ident_mut(&mut self, ident: &mut InterpolatedIdent<S>) {
+        walk_interpolated_ident_mut(self, ident)
+    }
+
+    fn visit_interpolated_string_mut(&mut self, string: &mut InterpolatedString<S>) {
+        walk_interpolated_string_mut(self, string)
+    }
+
+    fn visit_interpolation_part_mut(&mut self, _part: &mut InterpolationPart<S>, _span: Span) {}
+
+    fn visit_list_of_component_values_mut(&mut self, values: &mut ListOfComponentValues<S>) {
+        walk_list_of_component_values_mut(self, values)
+    }
+
+    fn visit_token_tree_mut(&mut self, tt: &mut TokenTree<S>, span: Span) {
+        walk_token_tree_mut(self, tt, span)
+    }
+
+    fn visit_token_mut(&mut self, _token: &mut Token<S>, _span: Span) {}
+}
+
+pub fn walk_stylesheet_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    stylesheet: &mut Stylesheet<S>,
+) {
+    visitor.visit_list_of_items_mut(&mut stylesheet.items);
+}
+
+pub fn walk_list_of_items_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    items: &mut ListOfItems<S>,
+) {
+    for (item, span) in &mut items.0 {
+        visitor.visit_item_mut(item, *span);
+    }
+}
+
+pub fn walk_item_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, item: &mut Item<S>, _span: Span) {
+    match item {
+        Item::AtRule(at_rule) => visitor.visit_at_rule_mut(at_rule),
+        Item::QualifiedRule(rule) => visitor.visit_qualified_rule_mut(rule),
+        Item::Declaration(declaration) => visitor.visit_declaration_mut(declaration),
+        Item::Call(call) => visitor.visit_call_mut(call),
+        Item::Extend(extend) => visitor.visit_extend_mut(extend),
+    }
+}
+
+pub fn walk_at_rule_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, at_rule: &mut AtRule<S>) {
+    match at_rule {
+        AtRule::Generic(rule) => {
+            visitor.visit_list_of_component_values_mut(&mut rule.prelude);
+            if let Some(block) = &mut rule.block {
+                visitor.visit_list_of_items_mut(block);
+            }
+        }
+        AtRule::Keyframes(rule) => {
+            if let KeyframesName::InterpolatedIdent(ident) = &mut rule.name {
+                visitor.visit_interpolated_ident_mut(ident);
+            }
+            visitor.visit_list_of_items_mut(&mut rule.block);
+        }
+        AtRule::Import(rule) => {
+            visitor.visit_url_or_string_mut(&mut rule.url.0);
+            visitor.visit_list_of_component_values_mut(&mut rule.media_queries);
+        }
+        AtRule::Supports(rule) => {
+            walk_condition_mut(&mut rule.condition.0, &mut |feature| {
+                visitor.visit_supports_feature_mut(feature)
+            });
+            visitor.visit_list_of_items_mut(&mut rule.block);
+        }
+        AtRule::Container(rule) => {
+            if let Some((condition, _)) = &mut rule.condition {
+                walk_condition_mut(condition, &mut |feature| {
+                    visitor.visit_container_feature_mut(feature)
+                });
+            }
+            visitor.visit_list_of_items_mut(&mut rule.block);
+        }
+        AtRule::Layer(rule) => {
+            if let Some(block) = &mut rule.block {
+                visitor.visit_list_of_items_mut(block);
+            }
+        }
+        AtRule::FontFace(FontFaceAtRule { block })
+        | AtRule::Page(PageAtRule { block, .. })
+        | AtRule::PageMargin(PageMarginAtRule { block, .. })
+        | AtRule::Property(PropertyAtRule { block, .. }) => visitor.visit_list_of_items_mut(block),
+        AtRule::Namespace(rule) => visitor.visit_url_or_string_mut(&mut rule.url.0),
+    }
+}
+
+fn walk_condition_mut<T>(condition: &mut Condition<T>, visit_feature: &mut impl FnMut(&mut T)) {
+    match condition {
+        Condition::Not(condition) => walk_condition_mut(&mut condition.0, visit_feature),
+        Condition::And(conditions) | Condition::Or(conditions) => {
+            for (condition, _) in conditions {
+                walk_condition_mut(condition, visit_feature);
+            }
+        }
+        Condition::Feature(feature) => visit_feature(feature),
+    }
+}
+
+pub fn walk_url_or_string_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    url: &mut UrlOrString<S>,
+) {
+    match url {
+        UrlOrString::String(string) | UrlOrString::Url(string) => {
+            visitor.visit_interpolated_string_mut(string)
+        }
+    }
+}
+
+pub fn walk_supports_feature_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    feature: &mut SupportsFeature<S>,
+) {
+    match feature {
+        SupportsFeature::Declaration(declaration) => visitor.visit_declaration_mut(declaration),
+        SupportsFeature::Function(function) => visitor.visit_function_call_mut(function),
+        SupportsFeature::GeneralEnclosed(values) => {
+            visitor.visit_list_of_component_values_mut(values)
+        }
+    }
+}
+
+pub fn walk_container_feature_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    feature: &mut ContainerFeature<S>,
+) {
+    match feature {
+        ContainerFeature::Size(values) => visitor.visit_list_of_component_values_mut(values),
+        ContainerFeature::Function(function) => visitor.visit_function_call_mut(function),
+    }
+}
+
+pub fn walk_qualified_rule_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    rule: &mut QualifiedRule<S>,
+) {
+    match rule {
+        QualifiedRule::Generic(rule) => {
+            visitor.visit_list_of_component_values_mut(&mut rule.prelude);
+            visitor.visit_list_of_items_mut(&mut rule.block);
+        }
+        QualifiedRule::Style(rule) => visitor.visit_style_rule_mut(rule),
+        QualifiedRule::Mixin(rule) => visitor.visit_mixin_rule_mut(rule),
+        QualifiedRule::Keyframe(rule) => visitor.visit_list_of_items_mut(&mut rule.block),
+    }
+}
+
+pub fn walk_style_rule_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, rule: &mut StyleRule<S>) {
+    visitor.visit_selector_list_mut(&mut rule.selectors);
+    if let Some(guard) = &mut rule.guard {
+        visitor.visit_list_of_component_values_mut(guard);
+    }
+    visitor.visit_list_of_items_mut(&mut rule.block);
+}
+
+pub fn walk_mixin_rule_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, rule: &mut MixinRule<S>) {
+    visitor.visit_list_of_component_values_mut(&mut rule.arguments);
+    if let Some(guard) = &mut rule.guard {
+        visitor.visit_list_of_component_values_mut(guard);
+    }
+    visitor.visit_list_of_items_mut(&mut rule.block);
+}
+
+pub fn walk_selector_list_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    selectors: &mut SelectorList<S>,
+) {
+    for (selector, _) in &mut selectors.0 {
+        visitor.visit_selector_mut(selector);
+    }
+}
+
+pub fn walk_selector_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, selector: &mut Selector<S>) {
+    for (component, span) in &mut selector.components {
+        visitor.visit_selector_component_mut(component, *span);
+    }
+    for (extend, _) in &mut selector.extends {
+        visitor.visit_extend_mut(extend);
+    }
+}
+
+pub fn walk_selector_component_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    component: &mut SelectorComponent<S>,
+    _span: Span,
+) {
+    match component {
+        SelectorComponent::Combinator(_)
+        | SelectorComponent::Parent
+        | SelectorComponent::Universal => {}
+        SelectorComponent::Type(name)
+        | SelectorComponent::Class(name)
+        | SelectorComponent::Id(name) => visitor.visit_selector_name_mut(name),
+        SelectorComponent::Attribute { name, matcher } => {
+            visitor.visit_selector_name_mut(name);
+            match matcher.as_mut().map(|matcher| &mut matcher.value) {
+                Some(AttributeValue::Ident(name)) => visitor.visit_selector_name_mut(name),
+                Some(AttributeValue::String(string)) => {
+                    visitor.visit_interpolated_string_mut(string)
+                }
+                None => {}
+            }
+        }
+        SelectorComponent::PseudoClass { arguments, .. }
+        | SelectorComponent::PseudoElement { arguments, .. } => {
+            if let Some(arguments) = arguments {
+                visitor.visit_list_of_component_values_mut(arguments);
+            }
+        }
+    }
+}
+
+pub fn walk_selector_name_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    name: &mut SelectorName<S>,
+) {
+    match name {
+        SelectorName::Ident(_) => {}
+        SelectorName::InterpolatedIdent(ident) => visitor.visit_interpolated_ident_mut(ident),
+    }
+}
+
+pub fn walk_extend_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, extend: &mut Extend<S>) {
+    for (target, _) in &mut extend.targets {
+        visitor.visit_selector_mut(&mut target.selector);
+    }
+}
+
+pub fn walk_declaration_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    declaration: &mut Declaration<S>,
+) {
+    visitor.visit_declaration_name_mut(&mut declaration.name);
+    visitor.visit_value_mut(&mut declaration.value);
+}
+
+pub fn walk_declaration_name_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    name: &mut DeclarationName<S>,
+) {
+    match name {
+        DeclarationName::Ident(_) | DeclarationName::Variable(_) => {}
+        DeclarationName::InterpolatedIdent(ident) => visitor.visit_interpolated_ident_mut(ident),
+    }
+}
+
+pub fn walk_value_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, value: &mut Value<S>) {
+    match value {
+        Value::ComponentValues(values) => visitor.visit_list_of_component_values_mut(values),
+        Value::DetachedRuleset(items) => visitor.visit_list_of_items_mut(items),
+    }
+}
+
+pub fn walk_call_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, call: &mut Call<S>) {
+    match call {
+        Call::Mixin(call) => visitor.visit_mixin_call_mut(call),
+        Call::Variable(call) => visitor.visit_variable_call_mut(call),
+        Call::Function(call) => visitor.visit_function_call_item_mut(call),
+    }
+}
+
+pub fn walk_mixin_call_mut<S, V: VisitMut<S> + ?Sized>(visitor: &mut V, call: &mut MixinCall<S>) {
+    visitor.visit_list_of_component_values_mut(&mut call.selector);
+    for (argument, span) in &mut call.arguments {
+        visitor.visit_mixin_argument_mut(argument, *span);
+    }
+}
+
+pub fn walk_mixin_argument_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    argument: &mut MixinArgument<S>,
+    _span: Span,
+) {
+    visitor.visit_value_mut(&mut argument.value);
+}
+
+pub fn walk_function_call_item_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    call: &mut FunctionCallItem<S>,
+) {
+    for (argument, _) in &mut call.arguments {
+        visitor.visit_value_mut(argument);
+    }
+}
+
+pub fn walk_function_call_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    call: &mut FunctionCall<S>,
+) {
+    visitor.visit_list_of_component_values_mut(&mut call.arguments);
+}
+
+pub fn walk_interpolated_ident_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    ident: &mut InterpolatedIdent<S>,
+) {
+    for (part, span) in &mut ident.0 {
+        visitor.visit_interpolation_part_mut(part, *span);
+    }
+}
+
+pub fn walk_interpolated_string_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    string: &mut InterpolatedString<S>,
+) {
+    for (part, span) in &mut string.0 {
+        visitor.visit_interpolation_part_mut(part, *span);
+    }
+}
+
+pub fn walk_list_of_component_values_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    values: &mut ListOfComponentValues<S>,
+) {
+    for (tt, span) in &mut values.0 {
+        visitor.visit_token_tree_mut(tt, *span);
+    }
+}
+
+pub fn walk_token_tree_mut<S, V: VisitMut<S> + ?Sized>(
+    visitor: &mut V,
+    tt: &mut TokenTree<S>,
+    span: Span,
+) {
+    match tt {
+        TokenTree::Token(token) => visitor.visit_token_mut(token, span),
+        TokenTree::Tree(_, tts) => {
+            for (tt, span) in tts {
+                visitor.visit_token_tree_mut(tt, *span);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_visit_mut() {
+        // Rewrite the URLs of imports and namespaces, keeping interpolations
+        struct RewriteUrls;
+
+        impl<'src> VisitMut<&'src str> for RewriteUrls {
+            fn visit_url_or_string_mut(&mut self, url: &mut UrlOrString<&'src str>) {
+                let (UrlOrString::String(string) | UrlOrString::Url(string)) = url;
+                for (part, _) in &mut string.0 {
+                    if let InterpolationPart::Literal(literal) = part {
+                        if let Some(rest) = literal.strip_prefix("/old") {
+                            *literal = rest;
+                        }
+                    }
+                }
+            }
+        }
+
+        let input = r#"
+            @import "/old/theme.less";
+            @namespace svg url(/old/@{svg}.xml);
+            .a { color: red; }
+        "#;
+        let mut stylesheet = parse(input).unwrap();
+        RewriteUrls.visit_stylesheet_mut(&mut stylesheet);
+
+        let urls: Vec<_> = stylesheet.items.0[..2]
+            .iter()
+            .map(|(item, _)| match item {
+                Item::AtRule(AtRule::Import(ImportAtRule { url, .. }))
+                | Item::AtRule(AtRule::Namespace(NamespaceAtRule { url, .. })) => &url.0,
+                _ => panic!("expected an import or namespace at-rule"),
+            })
+            .collect();
+        assert!(matches!(
+            urls[0],
+            UrlOrString::String(InterpolatedString(parts)) if parts[0].0 == InterpolationPart::Literal("/theme.less")
+        ));
+        assert!(matches!(
+            urls[1],
+            UrlOrString::Url(InterpolatedString(parts)) if parts[0].0 == InterpolationPart::Literal("/")
+                && parts[1].0 == InterpolationPart::Variable("svg")
+        ));
+    }
+}
@@ -0,0 +1,477 @@
+//! Colors, with conversions between the RGB, HSL and HSV color models.
+
+use std::fmt;
+
+use super::value::format_number;
+
+/// The named colors of CSS, sorted by name.
+const NAMED_COLORS: [(&str, u32); 148] = [
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+/// How a color is output.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Notation {
+    /// `#rrggbb`, or `rgba()` if the color is transparent
+    Rgb,
+    /// `hsl()`, or `hsla()` if the color is transparent, like the functions that create it
+    Hsl,
+    /// The hex color or keyword the color was written as, which is output as-is
+    Literal(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Color {
+    /// The red, green and blue channels, from 0 to 255 (but not clamped until output)
+    pub rgb: [f64; 3],
+    /// The alpha channel, from 0 to 1
+    pub alpha: f64,
+    pub notation: Notation,
+}
+
+/// A color in the HSL model, with the hue in degrees and the other channels from 0 to 1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Hsl {
+    pub h: f64,
+    pub s: f64,
+    pub l: f64,
+    pub a: f64,
+}
+
+/// A color in the HSV model, with the hue in degrees and the other channels from 0 to 1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Hsv {
+    pub h: f64,
+    pub s: f64,
+    pub v: f64,
+    pub a: f64,
+}
+
+impl Color {
+    pub fn new(rgb: [f64; 3], alpha: f64) -> Self {
+        Self {
+            rgb,
+            alpha,
+            notation: Notation::Rgb,
+        }
+    }
+
+    /// Parses the digits of a hex color (e.g. `fff` or `336699cc`).
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let digits: Vec<f64> = match hex.len() {
+            3 | 4 => hex
+                .chars()
+                .map(|c| (c.to_digit(16).unwrap() * 17) as f64)
+                .collect(),
+            6 | 8 => (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap() as f64)
+                .collect(),
+            _ => return None,
+        };
+        let alpha = digits.get(3).map_or(1.0, |alpha| alpha / 255.0);
+        Some(Self {
+            rgb: [digits[0], digits[1], digits[2]],
+            alpha,
+            notation: Notation::Literal(format!("#{hex}")),
+        })
+    }
+
+    /// Looks up a named color (e.g. `red`) or `transparent`, case-insensitively.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        let name = keyword.to_ascii_lowercase();
+        let (rgb, alpha) = if name == "transparent" {
+            ([0.0; 3], 0.0)
+        } else {
+            let i = NAMED_COLORS
+                .binary_search_by(|(named, _)| named.cmp(&name.as_str()))
+                .ok()?;
+            let [_, r, g, b] = NAMED_COLORS[i].1.to_be_bytes();
+            ([r, g, b].map(f64::from), 1.0)
+        };
+        Some(Self {
+            rgb,
+            alpha,
+            notation: Notation::Literal(keyword.to_string()),
+        })
+    }
+
+    pub fn from_hsl(hsl: Hsl) -> Self {
+        let h = (hsl.h % 360.0) / 360.0;
+        let s = hsl.s.clamp(0.0, 1.0);
+        let l = hsl.l.clamp(0.0, 1.0);
+        let m2 = if l <= 0.5 {
+            l * (s + 1.0)
+        } else {
+            l + s - l * s
+        };
+        let m1 = l * 2.0 - m2;
+        let hue = |h: f64| {
+            let h = if h < 0.0 {
+                h + 1.0
+            } else if h > 1.0 {
+                h - 1.0
+            } else {
+                h
+            };
+            if h * 6.0 < 1.0 {
+                m1 + (m2 - m1) * h * 6.0
+            } else if h * 2.0 < 1.0 {
+                m2
+            } else if h * 3.0 < 2.0 {
+                m1 + (m2 - m1) * (2.0 / 3.0 - h) * 6.0
+            } else {
+                m1
+            }
+        };
+        let rgb = [h + 1.0 / 3.0, h, h - 1.0 / 3.0].map(|h| hue(h) * 255.0);
+        Self::new(rgb, hsl.a.clamp(0.0, 1.0))
+    }
+
+    pub fn from_hsv(hsv: Hsv) -> Self {
+        let h = hsv.h % 360.0;
+        let (s, v) = (hsv.s, hsv.v);
+        let i = ((h / 60.0) % 6.0).floor();
+        let f = h / 60.0 - i;
+        let vs = [
+            v,
+            v * (1.0 - s),
+            v * (1.0 - f * s),
+            v * (1.0 - (1.0 - f) * s),
+        ];
+        let permutation = match i as i32 {
+            0 => [0, 3, 1],
+            1 => [2, 0, 1],
+            2 => [1, 0, 3],
+            3 => [1, 2, 0],
+            4 => [3, 1, 0],
+            _ => [0, 1, 2],
+        };
+        Self::new(permutation.map(|j| vs[j] * 255.0), hsv.a)
+    }
+
+    /// Creates a color from a hue in degrees and whiteness and blackness from 0 to 1.
+    pub fn from_hwb(h: f64, w: f64, b: f64, alpha: f64) -> Self {
+        let (w, b) = (w.clamp(0.0, 1.0), b.clamp(0.0, 1.0));
+        if w + b >= 1.0 {
+            let gray = w / (w + b) * 255.0;
+            return Self::new([gray; 3], alpha);
+        }
+        let hue = Self::from_hsl(Hsl {
+            h,
+            s: 1.0,
+            l: 0.5,
+            a: alpha,
+        });
+        Self::new(hue.rgb.map(|c| c * (1.0 - w - b) + w * 255.0), alpha)
+    }
+
+    pub fn to_hsl(&self) -> Hsl {
+        let [r, g, b] = self.rgb.map(|c| c / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let d = max - min;
+        let s = if max == min {
+            0.0
+        } else if l > 0.5 {
+            d / (2.0 - max - min)
+        } else {
+            d / (max + min)
+        };
+        Hsl {
+            h: self.hue(),
+            s,
+            l,
+            a: self.alpha,
+        }
+    }
+
+    pub fn to_hsv(&self) -> Hsv {
+        let [r, g, b] = self.rgb.map(|c| c / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let s = if max == 0.0 { 0.0 } else { (max - min) / max };
+        Hsv {
+            h: self.hue(),
+            s,
+            v: max,
+            a: self.alpha,
+        }
+    }
+
+    /// Returns the relative luminance of the color, as defined by WCAG.
+    pub fn luma(&self) -> f64 {
+        let [r, g, b] = self.rgb.map(|c| {
+            let c = c / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// Returns the hue in degrees, which is the same in the HSL and HSV models.
+    fn hue(&self) -> f64 {
+        let [r, g, b] = self.rgb.map(|c| c / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let d = max - min;
+        let h = if max == min {
+            0.0
+        } else if max == r {
+            (g - b) / d + if g < b { 6.0 } else { 0.0 }
+        } else if max == g {
+            (b - r) / d + 2.0
+        } else {
+            (r - g) / d + 4.0
+        };
+        h / 6.0 * 360.0
+    }
+
+    /// Returns the color as `#rrggbb`, with the channels rounded and clamped.
+    pub fn to_hex(&self) -> String {
+        let [r, g, b] = self.rgb.map(|c| c.round().clamp(0.0, 255.0) as u8);
+        format!("#{r:02x}{g:02x}{b:02x}")
+    }
+}
+
+/// Outputs the color like Less.js. The alternate flag (`{:#}`) outputs the compressed form, where
+/// hex colors are shortened if possible (e.g. `#aabbcc` to `#abc`).
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if f.alternate() { "," } else { ", " };
+        let alpha = format_number(self.alpha.clamp(0.0, 1.0));
+        match &self.notation {
+            Notation::Literal(literal) => f.write_str(literal),
+            Notation::Hsl => {
+                let hsl = self.to_hsl();
+                let name = if self.alpha < 1.0 { "hsla" } else { "hsl" };
+                let mut arguments = vec![
+                    format_number(hsl.h),
+                    format!("{}%", format_number(hsl.s * 100.0)),
+                    format!("{}%", format_number(hsl.l * 100.0)),
+                ];
+                if self.alpha < 1.0 {
+                    arguments.push(alpha);
+                }
+                write!(f, "{name}({})", arguments.join(separator))
+            }
+            Notation::Rgb if self.alpha < 1.0 => {
+                // Adding 0 turns a rounded `-0` into `0`
+                let [r, g, b] = self.rgb.map(|c| c.round().clamp(0.0, 255.0) + 0.0);
+                write!(f, "rgba({r}{separator}{g}{separator}{b}{separator}{alpha})")
+            }
+            Notation::Rgb => {
+                let hex = self.to_hex();
+                let digits = hex.as_bytes();
+                if f.alternate()
+                    && digits[1] == digits[2]
+                    && digits[3] == digits[4]
+                    && digits[5] == digits[6]
+                {
+                    let short = [digits[1], digits[3], digits[5]].map(char::from);
+                    write!(f, "#{}{}{}", short[0], short[1], short[2])
+                } else {
+                    f.write_str(&hex)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_colors() {
+        let input = "
+            .a {
+              a: #FFF;
+              b: Red;
+              c: transparent;
+              d: rgb(255, 0, 0);
+              e: rgba(255, 0, 0, 0.5);
+              f: rgb(0 128 255 / 50%);
+              g: rgb(50%, 0, 0);
+              h: hsl(120, 100%, 25%);
+              i: hsla(120deg, 100%, 25%, 0.5);
+              j: hwb(120 20% 30%);
+              k: hsv(240, 100%, 50%);
+              l: #ff0000 + #000;
+              m: rgb(var(--r), 0, 0);
+              n: rgba(#336699, 0.2);
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: #FFF;\n  b: Red;\n  c: transparent;\n  d: #ff0000;\n  \
+             e: rgba(255, 0, 0, 0.5);\n  f: rgba(0, 128, 255, 0.5);\n  g: #800000;\n  \
+             h: hsl(120, 100%, 25%);\n  i: hsla(120, 100%, 25%, 0.5);\n  j: #33b333;\n  \
+             k: #000080;\n  l: #ff0000;\n  m: rgb(var(--r), 0, 0);\n  \
+             n: rgba(51, 102, 153, 0.2);\n}\n"
+        );
+
+        let options = Options {
+            compress: true,
+            ..Options::default()
+        };
+        let input = ".a { b: #aabbcc; c: rgb(170, 187, 204); d: rgba(0, 0, 0, 0.5), #123; }";
+        let css = compile(input, options).unwrap();
+        assert_eq!(css, ".a{b:#aabbcc;c:#abc;d:rgba(0,0,0,0.5),#123}");
+    }
+}
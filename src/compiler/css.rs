@@ -0,0 +1,129 @@
+//! The CSS output of the compiler, and writing it as text.
+
+/// A node of the CSS output, in which all LESS features have been evaluated.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Node {
+    Rule {
+        selectors: Vec<String>,
+        children: Vec<Node>,
+    },
+    AtRule {
+        /// The at-rule up to its block (e.g. `@media screen`)
+        header: String,
+        block: Option<Vec<Node>>,
+    },
+    Declaration {
+        name: String,
+        value: String,
+        important: bool,
+    },
+    /// A block comment, including its delimiters
+    Comment(String),
+}
+
+/// Writes the nodes as CSS, with a line per declaration, or without optional whitespace and
+/// comments if `compress` is set.
+pub(crate) fn write(nodes: &[Node], compress: bool) -> String {
+    let mut output = String::new();
+    if compress {
+        write_compressed(&mut output, nodes);
+    } else {
+        write_nodes(&mut output, nodes, 0);
+    }
+    output
+}
+
+fn write_nodes(output: &mut String, nodes: &[Node], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for node in nodes {
+        output.push_str(&indent);
+        match node {
+            Node::Rule {
+                selectors,
+                children,
+            } => {
+                output.push_str(&selectors.join(&format!(",\n{indent}")));
+                write_block(output, children, depth);
+            }
+            Node::AtRule { header, block } => {
+                output.push_str(header);
+                match block {
+                    Some(block) => write_block(output, block, depth),
+                    None => output.push_str(";\n"),
+                }
+            }
+            Node::Declaration {
+                name,
+                value,
+                important,
+            } => {
+                output.push_str(name);
+                output.push_str(": ");
+                output.push_str(value);
+                if *important {
+                    output.push_str(" !important");
+                }
+                output.push_str(";\n");
+            }
+            Node::Comment(comment) => {
+                output.push_str(comment);
+                output.push('\n');
+            }
+        }
+    }
+}
+
+fn write_block(output: &mut String, nodes: &[Node], depth: usize) {
+    output.push_str(" {\n");
+    write_nodes(output, nodes, depth + 1);
+    output.push_str(&"  ".repeat(depth));
+    output.push_str("}\n");
+}
+
+fn write_compressed(output: &mut String, nodes: &[Node]) {
+    let mut nodes = nodes
+        .iter()
+        .filter(|node| !matches!(node, Node::Comment(_)))
+        .peekable();
+    while let Some(node) = nodes.next() {
+        match node {
+            Node::Rule {
+                selectors,
+                children,
+            } => {
+                output.push_str(&selectors.join(","));
+                output.push('{');
+                write_compressed(output, children);
+                output.push('}');
+            }
+            Node::AtRule { header, block } => {
+                output.push_str(header);
+                match block {
+                    Some(block) => {
+                        output.push('{');
+                        write_compressed(output, block);
+                        output.push('}');
+                    }
+                    None => output.push(';'),
+                }
+            }
+            Node::Declaration {
+                name,
+                value,
+                important,
+            } => {
+                output.push_str(name);
+                output.push(':');
+                output.push_str(value);
+                if *important {
+                    output.push_str("!important");
+                }
+                // The last declaration of a block doesn't need a semicolon
+                if nodes.peek().is_some() {
+                    output.push(';');
+                }
+            }
+            Node::Comment(_) => {}
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Blend mode functions, which blend two colors channel by channel like the blend modes of image
+//! editors.
+
+use super::{color, wrong_arguments, Result};
+use crate::compiler::color::Color;
+use crate::compiler::value::Value;
+
+/// Blends two colors with a function of their channels from 0 to 1, compositing the result with
+/// the opacity of both colors.
+fn blend(arguments: &[Value], mode: fn(f64, f64) -> f64) -> Result {
+    let [backdrop, source] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let (backdrop, source) = (color(backdrop)?, color(source)?);
+    let (ab, as_) = (backdrop.alpha, source.alpha);
+    let alpha = as_ + ab * (1.0 - as_);
+    let rgb = [0, 1, 2].map(|i| {
+        let cb = backdrop.rgb[i] / 255.0;
+        let cs = source.rgb[i] / 255.0;
+        let mut cr = mode(cb, cs);
+        if alpha != 0.0 {
+            cr = (as_ * cs + ab * (cb - as_ * (cb + cs - cr))) / alpha;
+        }
+        cr * 255.0
+    });
+    Ok(Some(Value::Color(Color::new(rgb, alpha))))
+}
+
+fn multiply_channel(cb: f64, cs: f64) -> f64 {
+    cb * cs
+}
+
+fn screen_channel(cb: f64, cs: f64) -> f64 {
+    cb + cs - cb * cs
+}
+
+fn overlay_channel(cb: f64, cs: f64) -> f64 {
+    let cb = cb * 2.0;
+    if cb <= 1.0 {
+        multiply_channel(cb, cs)
+    } else {
+        screen_channel(cb - 1.0, cs)
+    }
+}
+
+pub(super) fn multiply(arguments: &[Value]) -> Result {
+    blend(arguments, multiply_channel)
+}
+
+pub(super) fn screen(arguments: &[Value]) -> Result {
+    blend(arguments, screen_channel)
+}
+
+pub(super) fn overlay(arguments: &[Value]) -> Result {
+    blend(arguments, overlay_channel)
+}
+
+pub(super) fn softlight(arguments: &[Value]) -> Result {
+    blend(arguments, |cb, cs| {
+        let (d, e) = match cs > 0.5 {
+            true if cb > 0.25 => (cb.sqrt(), 1.0),
+            true => (((16.0 * cb - 12.0) * cb + 4.0) * cb, 1.0),
+            false => (1.0, cb),
+        };
+        cb - (1.0 - 2.0 * cs) * e * (d - cb)
+    })
+}
+
+pub(super) fn hardlight(arguments: &[Value]) -> Result {
+    blend(arguments, |cb, cs| overlay_channel(cs, cb))
+}
+
+pub(super) fn difference(arguments: &[Value]) -> Result {
+    blend(arguments, |cb, cs| (cb - cs).abs())
+}
+
+pub(super) fn exclusion(arguments: &[Value]) -> Result {
+    blend(arguments, |cb, cs| cb + cs - 2.0 * cb * cs)
+}
+
+pub(super) fn average(arguments: &[Value]) -> Result {
+    blend(arguments, |cb, cs| (cb + cs) / 2.0)
+}
+
+pub(super) fn negation(arguments: &[Value]) -> Result {
+    blend(arguments, |cb, cs| 1.0 - (cb + cs - 1.0).abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_blending() {
+        let input = "
+            @a: #ff6600;
+            @b: #3399cc;
+            .a {
+              a: multiply(@a, @b);
+              b: screen(@a, @b);
+              c: overlay(@a, @b);
+              d: softlight(@a, @b);
+              e: hardlight(@a, @b);
+              f: difference(@a, @b);
+              g: exclusion(@a, @b);
+              h: average(@a, @b);
+              i: negation(@a, @b);
+              j: multiply(fade(@a, 50%), @b);
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: #333d00;\n  b: #ffc2cc;\n  c: #ff7a00;\n  d: #ff7200;\n  \
+             e: #668599;\n  f: #cc33cc;\n  g: #cc85cc;\n  h: #998066;\n  i: #ccffcc;\n  \
+             j: #336b66;\n}\n"
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Color definition and operation functions.
+
+use super::{color, dimension, number, scaled, wrong_arguments, Result};
+use crate::compiler::color::{Color, Hsl, Hsv, Notation};
+use crate::compiler::unit::Unit;
+use crate::compiler::value::{Operator, Separator, Value};
+
+// DEFINITION
+
+/// Splits the components of the space separated syntax of the color functions (e.g. `rgb(0 0 0 /
+/// 50%)`).
+fn components(arguments: &[Value]) -> Vec<Value> {
+    let [Value::List(values, Separator::Space)] = arguments else {
+        return arguments.to_vec();
+    };
+    let mut values = values.clone();
+    if let Some(Value::Operation(Operator::Divide, last, alpha, _)) = values.last().cloned() {
+        values.pop();
+        values.extend([*last, *alpha]);
+    }
+    values
+}
+
+/// Returns the optional alpha channel of a color function, which defaults to 1.
+fn alpha_argument(arguments: &[Value], index: usize) -> Option<f64> {
+    arguments.get(index).map_or(Some(1.0), number)
+}
+
+pub(super) fn rgba(arguments: &[Value]) -> Result {
+    let arguments = components(arguments);
+    let color = match arguments.as_slice() {
+        [Value::Color(color)] => Some(Color::new(color.rgb, color.alpha)),
+        [Value::Color(color), alpha] => number(alpha).map(|alpha| Color::new(color.rgb, alpha)),
+        [r, g, b] | [r, g, b, _] => (|| {
+            let rgb = [scaled(r, 255.0)?, scaled(g, 255.0)?, scaled(b, 255.0)?];
+            Some(Color::new(rgb, alpha_argument(&arguments, 3)?))
+        })(),
+        _ => None,
+    };
+    Ok(color.map(Value::Color))
+}
+
+pub(super) fn hsla(arguments: &[Value]) -> Result {
+    let arguments = components(arguments);
+    let color = match arguments.as_slice() {
+        [Value::Color(color)] => Some(Color::new(color.rgb, color.alpha)),
+        [Value::Color(color), alpha] => number(alpha).map(|alpha| Color::new(color.rgb, alpha)),
+        [h, s, l] | [h, s, l, _] => (|| {
+            Some(Color::from_hsl(Hsl {
+                h: number(h)?,
+                s: number(s)?,
+                l: number(l)?,
+                a: alpha_argument(&arguments, 3)?,
+            }))
+        })(),
+        _ => None,
+    };
+    Ok(color.map(|color| {
+        Value::Color(Color {
+            notation: Notation::Hsl,
+            ..color
+        })
+    }))
+}
+
+pub(super) fn hsva(arguments: &[Value]) -> Result {
+    let arguments = components(arguments);
+    let color = match arguments.as_slice() {
+        [h, s, v] | [h, s, v, _] => (|| {
+            Some(Color::from_hsv(Hsv {
+                h: number(h)?,
+                s: number(s)?,
+                v: number(v)?,
+                a: alpha_argument(&arguments, 3)?,
+            }))
+        })(),
+        _ => None,
+    };
+    Ok(color.map(Value::Color))
+}
+
+pub(super) fn hwb(arguments: &[Value]) -> Result {
+    let arguments = components(arguments);
+    let color = match arguments.as_slice() {
+        [h, w, b] | [h, w, b, _] => (|| {
+            let alpha = alpha_argument(&arguments, 3)?;
+            Some(Color::from_hwb(number(h)?, number(w)?, number(b)?, alpha))
+        })(),
+        _ => None,
+    };
+    Ok(color.map(Value::Color))
+}
+
+// OPERATIONS
+
+/// Returns a color with the channels of `hsl`, output in the notation of the original color if it
+/// was created with `hsl()`.
+fn with_hsl(original: &Color, hsl: Hsl) -> Value {
+    let notation = match original.notation {
+        Notation::Hsl => Notation::Hsl,
+        _ => Notation::Rgb,
+    };
+    Value::Color(Color {
+        notation,
+        ..Color::from_hsl(hsl)
+    })
+}
+
+/// Increases a channel of a color in the HSL model by an amount in percent, or by a percentage of
+/// the channel with the `relative` method (e.g. `lighten(@color, 10%, relative)`).
+fn adjust(arguments: &[Value], channel: fn(&mut Hsl) -> &mut f64, sign: f64) -> Result {
+    let (color, amount, method) = match arguments {
+        [color, amount] => (super::color(color)?, dimension(amount)?, None),
+        [color, amount, method] => (super::color(color)?, dimension(amount)?, Some(method)),
+        _ => return Err(wrong_arguments()),
+    };
+    let relative = matches!(method, Some(Value::Keyword(method)) if method == "relative");
+    let mut hsl = color.to_hsl();
+    let value = channel(&mut hsl);
+    *value += sign
+        * if relative {
+            *value * amount / 100.0
+        } else {
+            amount / 100.0
+        };
+    *value = value.clamp(0.0, 1.0);
+    Ok(Some(with_hsl(color, hsl)))
+}
+
+pub(super) fn lighten(arguments: &[Value]) -> Result {
+    adjust(arguments, |hsl| &mut hsl.l, 1.0)
+}
+
+pub(super) fn darken(arguments: &[Value]) -> Result {
+    adjust(arguments, |hsl| &mut hsl.l, -1.0)
+}
+
+pub(super) fn saturate(arguments: &[Value]) -> Result {
+    // `saturate()` is also a CSS filter function
+    if !matches!(arguments.first(), Some(Value::Color(_))) {
+        return Ok(None);
+    }
+    adjust(arguments, |hsl| &mut hsl.s, 1.0)
+}
+
+pub(super) fn desaturate(arguments: &[Value]) -> Result {
+    adjust(arguments, |hsl| &mut hsl.s, -1.0)
+}
+
+pub(super) fn fadein(arguments: &[Value]) -> Result {
+    adjust(arguments, |hsl| &mut hsl.a, 1.0)
+}
+
+pub(super) fn fadeout(arguments: &[Value]) -> Result {
+    adjust(arguments, |hsl| &mut hsl.a, -1.0)
+}
+
+pub(super) fn fade(arguments: &[Value]) -> Result {
+    let [color, amount] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let color = super::color(color)?;
+    let mut hsl = color.to_hsl();
+    hsl.a = (dimension(amount)? / 100.0).clamp(0.0, 1.0);
+    Ok(Some(with_hsl(color, hsl)))
+}
+
+pub(super) fn spin(arguments: &[Value]) -> Result {
+    let [color, amount] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let color = super::color(color)?;
+    let mut hsl = color.to_hsl();
+    let hue = (hsl.h + dimension(amount)?) % 360.0;
+    hsl.h = if hue < 0.0 { hue + 360.0 } else { hue };
+    Ok(Some(with_hsl(color, hsl)))
+}
+
+/// Mixes two colors, with the weight of the first color in percent, taking their opacity into
+/// account like Sass and Less.js.
+fn mix_colors(first: &Color, second: &Color, weight: f64) -> Color {
+    let p = weight / 100.0;
+    let w = p * 2.0 - 1.0;
+    let a = first.alpha - second.alpha;
+    let w1 = (if w * a == -1.0 {
+        w
+    } else {
+        (w + a) / (1.0 + w * a)
+    } + 1.0)
+        / 2.0;
+    let w2 = 1.0 - w1;
+    let rgb = [0, 1, 2].map(|i| first.rgb[i] * w1 + second.rgb[i] * w2);
+    Color::new(rgb, first.alpha * p + second.alpha * (1.0 - p))
+}
+
+pub(super) fn mix(arguments: &[Value]) -> Result {
+    let (first, second, weight) = match arguments {
+        [first, second] => (color(first)?, color(second)?, 50.0),
+        [first, second, weight] => (color(first)?, color(second)?, dimension(weight)?),
+        _ => return Err(wrong_arguments()),
+    };
+    Ok(Some(Value::Color(mix_colors(first, second, weight))))
+}
+
+/// Mixes a color with white (`tint()`) or black (`shade()`), with the weight of white or black in
+/// percent.
+fn mix_with(arguments: &[Value], with: [f64; 3]) -> Result {
+    let (color, weight) = match arguments {
+        [color] => (super::color(color)?, 50.0),
+        [color, weight] => (super::color(color)?, dimension(weight)?),
+        _ => return Err(wrong_arguments()),
+    };
+    let with = Color::new(with, 1.0);
+    Ok(Some(Value::Color(mix_colors(&with, color, weight))))
+}
+
+pub(super) fn tint(arguments: &[Value]) -> Result {
+    mix_with(arguments, [255.0; 3])
+}
+
+pub(super) fn shade(arguments: &[Value]) -> Result {
+    mix_with(arguments, [0.0; 3])
+}
+
+pub(super) fn greyscale(arguments: &[Value]) -> Result {
+    let [color] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let color = super::color(color)?;
+    let mut hsl = color.to_hsl();
+    hsl.s = 0.0;
+    Ok(Some(with_hsl(color, hsl)))
+}
+
+/// Chooses the dark or the light color (black and white by default), whichever contrasts most with
+/// the color, based on its luma compared to the threshold (43% by default).
+pub(super) fn contrast(arguments: &[Value]) -> Result {
+    let Some(Value::Color(color)) = arguments.first() else {
+        return Ok(None);
+    };
+    if arguments.len() > 4 {
+        return Err(wrong_arguments());
+    }
+    let black = Value::Color(Color::new([0.0; 3], 1.0));
+    let white = Value::Color(Color::new([255.0; 3], 1.0));
+    let mut dark = arguments.get(1).unwrap_or(&black);
+    let mut light = arguments.get(2).unwrap_or(&white);
+    if super::color(dark)?.luma() > super::color(light)?.luma() {
+        std::mem::swap(&mut dark, &mut light);
+    }
+    let threshold = match arguments.get(3) {
+        Some(threshold) => number(threshold).ok_or("argument must be a number")?,
+        None => 0.43,
+    };
+    Ok(Some(
+        if color.luma() < threshold {
+            light
+        } else {
+            dark
+        }
+        .clone(),
+    ))
+}
+
+// CHANNELS
+
+/// Returns a channel of a color as a number with the given unit.
+fn channel(arguments: &[Value], channel: fn(&Color) -> f64, unit: &str) -> Result {
+    let [color] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let value = channel(super::color(color)?);
+    Ok(Some(Value::Number(value, Unit::new(unit))))
+}
+
+pub(super) fn red(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.rgb[0], "")
+}
+
+pub(super) fn green(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.rgb[1], "")
+}
+
+pub(super) fn blue(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.rgb[2], "")
+}
+
+pub(super) fn alpha(arguments: &[Value]) -> Result {
+    // `alpha(opacity=50)` is an old Internet Explorer filter
+    if !matches!(arguments, [Value::Color(_)]) {
+        return Ok(None);
+    }
+    channel(arguments, |color| color.alpha, "")
+}
+
+pub(super) fn hue(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.to_hsl().h, "")
+}
+
+pub(super) fn saturation(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.to_hsl().s * 100.0, "%")
+}
+
+pub(super) fn lightness(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.to_hsl().l * 100.0, "%")
+}
+
+pub(super) fn hsvhue(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.to_hsv().h, "")
+}
+
+pub(super) fn hsvsaturation(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.to_hsv().s * 100.0, "%")
+}
+
+pub(super) fn hsvvalue(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.to_hsv().v * 100.0, "%")
+}
+
+/// Returns the relative luminance of the color (with gamma correction), as a percentage.
+pub(super) fn luma(arguments: &[Value]) -> Result {
+    channel(arguments, |color| color.luma() * color.alpha * 100.0, "%")
+}
+
+/// Returns the luminance of the color without gamma correction, as a percentage.
+pub(super) fn luminance(arguments: &[Value]) -> Result {
+    channel(
+        arguments,
+        |color| {
+            let [r, g, b] = color.rgb.map(|c| c / 255.0);
+            (0.2126 * r + 0.7152 * g + 0.0722 * b) * color.alpha * 100.0
+        },
+        "%",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_color_functions() {
+        let input = "
+            @c: #336699;
+            .a {
+              a: lighten(@c, 20%);
+              b: darken(@c, 10%);
+              c: lighten(@c, 20%, relative);
+              d: saturate(@c, 20%);
+              e: desaturate(@c, 20%);
+              f: fade(@c, 50%);
+              g: fadein(fade(@c, 50%), 10%);
+              h: fadeout(@c, 10%);
+              i: spin(@c, -210);
+              j: mix(#ff0000, #0000ff, 25%);
+              k: mix(rgba(255, 0, 0, 0.5), #0000ff);
+              l: tint(@c, 10%);
+              m: shade(@c);
+              n: greyscale(@c);
+              o: contrast(@c);
+              p: contrast(#eee, navy, ivory);
+              q: lighten(hsl(90, 80%, 50%), 10%);
+              r: saturate(50%);
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: #6699cc;\n  b: #264c73;\n  c: #3d7ab8;\n  d: #1f66ad;\n  \
+             e: #476685;\n  f: rgba(51, 102, 153, 0.5);\n  g: rgba(51, 102, 153, 0.6);\n  \
+             h: rgba(51, 102, 153, 0.9);\n  i: #993333;\n  j: #4000bf;\n  \
+             k: rgba(64, 0, 191, 0.75);\n  l: #4775a3;\n  m: #1a334d;\n  n: #666666;\n  \
+             o: #ffffff;\n  p: navy;\n  q: hsl(90, 80%, 60%);\n  r: saturate(50%);\n}\n"
+        );
+
+        let input = "
+            @c: #336699;
+            .a {
+              a: red(@c) green(@c) blue(@c) alpha(fade(@c, 20%));
+              b: hue(@c) saturation(@c) lightness(@c);
+              c: hsvhue(@c) hsvsaturation(@c) hsvvalue(@c);
+              d: luma(@c) luminance(@c);
+              e: alpha(opacity=50);
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: 51 102 153 0.2;\n  b: 210 50% 40%;\n  c: 210 66.66666667% 60%;\n  \
+             d: 12.50645743% 37.192%;\n  e: alpha(opacity=50);\n}\n"
+        );
+
+        let error = compile(".a { b: lighten(foo, 10%); }", Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "error evaluating function `lighten`: argument cannot be evaluated to a color at \
+             8..25"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! List functions.
+
+use super::{dimension, wrong_arguments, Result};
+use crate::compiler::unit::Unit;
+use crate::compiler::value::{Separator, Value};
+
+/// Returns the values of a comma or space separated list, where other values are lists of one.
+fn values(value: &Value) -> &[Value] {
+    match value {
+        Value::List(values, Separator::Comma | Separator::Space) => values,
+        value => std::slice::from_ref(value),
+    }
+}
+
+pub(super) fn length(arguments: &[Value]) -> Result {
+    let [list] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let length = values(list).len() as f64;
+    Ok(Some(Value::Number(length, Unit::default())))
+}
+
+/// Returns the value at an index (from 1) of a list, or `None` if it's out of range.
+pub(super) fn extract(arguments: &[Value]) -> Result {
+    let [list, Value::Number(index, _)] = arguments else {
+        return Err("arguments must be a list and an index".to_string());
+    };
+    if *index < 1.0 {
+        return Ok(None);
+    }
+    Ok(values(list).get(*index as usize - 1).cloned())
+}
+
+/// Generates a list of numbers from `start` (1 by default) to `end` by `step` (1 by default), with
+/// the unit of `end` (e.g. `range(10px, 30px, 10)` is `10px 20px 30px`).
+pub(super) fn range(arguments: &[Value]) -> Result {
+    let (start, end, step) = match arguments {
+        [end] => (1.0, end, 1.0),
+        [start, end] => (dimension(start)?, end, 1.0),
+        [start, end, step] => (dimension(start)?, end, dimension(step)?),
+        _ => return Err(wrong_arguments()),
+    };
+    let Value::Number(end, unit) = end else {
+        return Err("argument must be a number".to_string());
+    };
+    if step <= 0.0 {
+        return Err("the step must be positive".to_string());
+    }
+
+    let mut values = Vec::new();
+    let mut number = start;
+    while number <= *end {
+        values.push(Value::Number(number, unit.clone()));
+        number += step;
+    }
+    Ok(Some(Value::List(values, Separator::Space)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_list_functions() {
+        let input = "
+            @list: apple, pear, coconut;
+            @sizes: {
+              small: 10px;
+              @large: 20px;
+            };
+            .a {
+              a: length(@list) length(1px solid red) length(a);
+              b: extract(@list, 2) extract(8px 4px, 1) range(4) range(10px, 30px, 10);
+              each(@list, {
+                .sel-@{value} { index: @index; }
+              });
+              each(@sizes, {
+                @{key}-size: @value;
+              });
+            }
+            each(range(2), {
+              .col-@{value} { width: (@value * 50%); }
+            });
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: 3 3 1;\n  b: pear 8px 1 2 3 4 10px 20px 30px;\n  \
+             small-size: 10px;\n  @large-size: 20px;\n}\n\
+             .a .sel-apple {\n  index: 1;\n}\n\
+             .a .sel-pear {\n  index: 2;\n}\n\
+             .a .sel-coconut {\n  index: 3;\n}\n\
+             .col-1 {\n  width: 50%;\n}\n\
+             .col-2 {\n  width: 100%;\n}\n"
+        );
+
+        // The declarations of each iteration are output in order with those of the rule
+        let input = ".x { a: 1; each(red blue, { b: @value; }); c: 3; }";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(css, ".x {\n  a: 1;\n  b: red;\n  b: blue;\n  c: 3;\n}\n");
+    }
+}
@@ -0,0 +1,191 @@
+//! Math functions.
+
+use std::f64::consts::PI;
+
+use super::{dimension, wrong_arguments, Result};
+use crate::compiler::unit::Unit;
+use crate::compiler::value::{Separator, Value};
+
+/// Applies a function to a number, keeping its unit.
+fn apply(arguments: &[Value], function: fn(f64) -> f64) -> Result {
+    let [Value::Number(number, unit)] = arguments else {
+        return Err(number_error(arguments));
+    };
+    Ok(Some(Value::Number(function(*number), unit.clone())))
+}
+
+/// Applies a trigonometric function to a number in radians (converting degrees, gradians and
+/// turns), giving a number with the given unit.
+fn trigonometry(arguments: &[Value], function: fn(f64) -> f64, unit: &str) -> Result {
+    let [Value::Number(number, number_unit)] = arguments else {
+        return Err(number_error(arguments));
+    };
+    let (number, _) = number_unit.unify(*number);
+    Ok(Some(Value::Number(function(number), Unit::new(unit))))
+}
+
+fn number_error(arguments: &[Value]) -> String {
+    match arguments.len() {
+        1 => "argument must be a number".to_string(),
+        _ => wrong_arguments(),
+    }
+}
+
+pub(super) fn ceil(arguments: &[Value]) -> Result {
+    apply(arguments, f64::ceil)
+}
+
+pub(super) fn floor(arguments: &[Value]) -> Result {
+    apply(arguments, f64::floor)
+}
+
+pub(super) fn sqrt(arguments: &[Value]) -> Result {
+    apply(arguments, f64::sqrt)
+}
+
+pub(super) fn abs(arguments: &[Value]) -> Result {
+    apply(arguments, f64::abs)
+}
+
+/// Rounds a number to a number of decimal places (0 by default), rounding halves up like
+/// `Number.prototype.toFixed()`.
+pub(super) fn round(arguments: &[Value]) -> Result {
+    let (number, unit, places) = match arguments {
+        [Value::Number(number, unit)] => (number, unit, 0.0),
+        [Value::Number(number, unit), places] => (number, unit, dimension(places)?),
+        [_] | [_, _] => return Err("argument must be a number".to_string()),
+        _ => return Err(wrong_arguments()),
+    };
+    let factor = 10f64.powf(places);
+    Ok(Some(Value::Number(
+        (number * factor).round() / factor,
+        unit.clone(),
+    )))
+}
+
+pub(super) fn percentage(arguments: &[Value]) -> Result {
+    let [Value::Number(number, unit)] = arguments else {
+        return Err(number_error(arguments));
+    };
+    let (number, _) = unit.unify(*number);
+    Ok(Some(Value::Number(number * 100.0, Unit::new("%"))))
+}
+
+pub(super) fn sin(arguments: &[Value]) -> Result {
+    trigonometry(arguments, f64::sin, "")
+}
+
+pub(super) fn cos(arguments: &[Value]) -> Result {
+    trigonometry(arguments, f64::cos, "")
+}
+
+pub(super) fn tan(arguments: &[Value]) -> Result {
+    trigonometry(arguments, f64::tan, "")
+}
+
+pub(super) fn asin(arguments: &[Value]) -> Result {
+    trigonometry(arguments, f64::asin, "rad")
+}
+
+pub(super) fn acos(arguments: &[Value]) -> Result {
+    trigonometry(arguments, f64::acos, "rad")
+}
+
+pub(super) fn atan(arguments: &[Value]) -> Result {
+    trigonometry(arguments, f64::atan, "rad")
+}
+
+pub(super) fn pi(arguments: &[Value]) -> Result {
+    if !arguments.is_empty() {
+        return Err(wrong_arguments());
+    }
+    Ok(Some(Value::Number(PI, Unit::default())))
+}
+
+pub(super) fn pow(arguments: &[Value]) -> Result {
+    let [Value::Number(base, unit), Value::Number(exponent, _)] = arguments else {
+        return Err("arguments must be numbers".to_string());
+    };
+    Ok(Some(Value::Number(base.powf(*exponent), unit.clone())))
+}
+
+pub(super) fn r#mod(arguments: &[Value]) -> Result {
+    let [Value::Number(dividend, unit), Value::Number(divisor, _)] = arguments else {
+        return Err("arguments must be numbers".to_string());
+    };
+    Ok(Some(Value::Number(dividend % divisor, unit.clone())))
+}
+
+/// Returns the smallest (or largest) of numbers, which may be given as a list, comparing them in
+/// compatible units (e.g. `min(1cm, 5mm)` is `5mm`).
+///
+/// Like in Less.js, a call with numbers that can't be compared (e.g. `min(100%, 500px)`) is output
+/// as-is, as it's also a CSS function.
+fn min_max(arguments: &[Value], is_min: bool) -> Result {
+    let arguments = match arguments {
+        [Value::List(values, Separator::Space | Separator::Comma)] => values.as_slice(),
+        arguments => arguments,
+    };
+    if arguments.is_empty() {
+        return Err("one or more arguments required".to_string());
+    }
+
+    let mut best: Option<(&Value, f64)> = None;
+    let mut base_unit = None;
+    for argument in arguments {
+        let Value::Number(number, unit) = argument else {
+            return Ok(None);
+        };
+        let (number, unit) = unit.unify(*number);
+        if !unit.is_empty() {
+            let unit = unit.describe();
+            match &base_unit {
+                Some(base_unit) if *base_unit != unit => return Ok(None),
+                _ => base_unit = Some(unit),
+            }
+        }
+        let is_better = match best {
+            None => true,
+            Some((_, best)) if is_min => number < best,
+            Some((_, best)) => number > best,
+        };
+        if is_better {
+            best = Some((argument, number));
+        }
+    }
+    Ok(best.map(|(value, _)| value.clone()))
+}
+
+pub(super) fn min(arguments: &[Value]) -> Result {
+    min_max(arguments, true)
+}
+
+pub(super) fn max(arguments: &[Value]) -> Result {
+    min_max(arguments, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_math_functions() {
+        let input = "
+            .a {
+              a: ceil(2.4px) floor(2.6) round(1.67, 1) round(2.5);
+              b: percentage(0.5) sqrt(25cm) abs(-18.6%);
+              c: sin(90deg) cos(0) tan(45deg) asin(1) pi();
+              d: pow(2px, 3) mod(11px, 3);
+              e: min(1cm, 5mm) max(3px, 1, 5px) min(100%, 500px);
+              f: pow(0, -1) (-1 * pow(0, -1)) mod(1, 0);
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: 3px 2 1.7 3;\n  b: 50% 5cm 18.6%;\n  c: 1 1 1 1.57079633rad 3.14159265;\n  \
+             d: 8px 2px;\n  e: 5mm 5px min(100%, 500px);\n  \
+             f: Infinity -Infinity NaN;\n}\n"
+        );
+    }
+}
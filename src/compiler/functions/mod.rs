@@ -0,0 +1,153 @@
+//! The built-in functions of Less.
+
+use super::color::Color;
+use super::value::Value;
+
+mod blending;
+mod color;
+mod list;
+mod math;
+mod string;
+mod types;
+
+/// The result of a function, which is `None` if it doesn't apply to the arguments and is output
+/// as-is (e.g. `saturate(50%)`, which is a CSS filter function).
+type Result = std::result::Result<Option<Value>, String>;
+
+/// Calls a built-in function with the evaluated arguments, returning `None` if there's no such
+/// function or it doesn't apply to the arguments, in which case the call is output as-is (e.g.
+/// `rgb(var(--r), 0, 0)`). Function names are case-insensitive, like in CSS.
+pub(crate) fn call(name: &str, arguments: &[Value]) -> Result {
+    let result = match name.to_ascii_lowercase().as_str() {
+        "rgb" | "rgba" => color::rgba(arguments),
+        "hsl" | "hsla" => color::hsla(arguments),
+        "hsv" | "hsva" => color::hsva(arguments),
+        "hwb" => color::hwb(arguments),
+        "lighten" => color::lighten(arguments),
+        "darken" => color::darken(arguments),
+        "saturate" => color::saturate(arguments),
+        "desaturate" => color::desaturate(arguments),
+        "fadein" => color::fadein(arguments),
+        "fadeout" => color::fadeout(arguments),
+        "fade" => color::fade(arguments),
+        "spin" => color::spin(arguments),
+        "mix" => color::mix(arguments),
+        "tint" => color::tint(arguments),
+        "shade" => color::shade(arguments),
+        "greyscale" => color::greyscale(arguments),
+        "contrast" => color::contrast(arguments),
+        "red" => color::red(arguments),
+        "green" => color::green(arguments),
+        "blue" => color::blue(arguments),
+        "alpha" => color::alpha(arguments),
+        "hue" => color::hue(arguments),
+        "saturation" => color::saturation(arguments),
+        "lightness" => color::lightness(arguments),
+        "hsvhue" => color::hsvhue(arguments),
+        "hsvsaturation" => color::hsvsaturation(arguments),
+        "hsvvalue" => color::hsvvalue(arguments),
+        "luma" => color::luma(arguments),
+        "luminance" => color::luminance(arguments),
+        "multiply" => blending::multiply(arguments),
+        "screen" => blending::screen(arguments),
+        "overlay" => blending::overlay(arguments),
+        "softlight" => blending::softlight(arguments),
+        "hardlight" => blending::hardlight(arguments),
+        "difference" => blending::difference(arguments),
+        "exclusion" => blending::exclusion(arguments),
+        "average" => blending::average(arguments),
+        "negation" => blending::negation(arguments),
+        "ceil" => math::ceil(arguments),
+        "floor" => math::floor(arguments),
+        "round" => math::round(arguments),
+        "percentage" => math::percentage(arguments),
+        "sqrt" => math::sqrt(arguments),
+        "abs" => math::abs(arguments),
+        "sin" => math::sin(arguments),
+        "cos" => math::cos(arguments),
+        "tan" => math::tan(arguments),
+        "asin" => math::asin(arguments),
+        "acos" => math::acos(arguments),
+        "atan" => math::atan(arguments),
+        "pi" => math::pi(arguments),
+        "pow" => math::pow(arguments),
+        "mod" => math::r#mod(arguments),
+        "min" => math::min(arguments),
+        "max" => math::max(arguments),
+        "isnumber" => types::isnumber(arguments),
+        "isstring" => types::isstring(arguments),
+        "iscolor" => types::iscolor(arguments),
+        "iskeyword" => types::iskeyword(arguments),
+        "isurl" => types::isurl(arguments),
+        "ispixel" => types::ispixel(arguments),
+        "isem" => types::isem(arguments),
+        "ispercentage" => types::ispercentage(arguments),
+        "isunit" => types::isunit(arguments),
+        "unit" => types::unit(arguments),
+        "get-unit" => types::get_unit(arguments),
+        "convert" => types::convert(arguments),
+        "escape" => string::escape(arguments),
+        "e" => string::e(arguments),
+        "%" => string::format(arguments),
+        "replace" => string::replace(arguments),
+        "length" => list::length(arguments),
+        "extract" => list::extract(arguments),
+        "range" => list::range(arguments),
+        _ => return Ok(None),
+    };
+    result.map_err(|message| format!("error evaluating function `{name}`: {message}"))
+}
+
+fn wrong_arguments() -> String {
+    "wrong number of arguments".to_string()
+}
+
+/// Returns the value of a number, where percentages are fractions (e.g. `50%` is 0.5).
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number, unit) if unit.is("%") => Some(number / 100.0),
+        Value::Number(number, _) => Some(*number),
+        _ => None,
+    }
+}
+
+/// Returns the value of a number, where percentages are fractions of `size`.
+fn scaled(value: &Value, size: f64) -> Option<f64> {
+    match value {
+        Value::Number(number, unit) if unit.is("%") => Some(number * size / 100.0),
+        value => number(value),
+    }
+}
+
+/// Returns the value of a number argument regardless of its unit (e.g. 10 for `10%`).
+fn dimension(value: &Value) -> std::result::Result<f64, String> {
+    match value {
+        Value::Number(number, _) => Ok(*number),
+        _ => Err("argument must be a number".to_string()),
+    }
+}
+
+fn color(value: &Value) -> std::result::Result<&Color, String> {
+    match value {
+        Value::Color(color) => Ok(color),
+        _ => Err("argument cannot be evaluated to a color".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call, Value};
+
+    #[test]
+    fn test_call() {
+        // Function names are case-insensitive
+        let arguments = [1.0, 2.0, 3.0].map(|n| Value::Number(n, Default::default()));
+        let expected = call("rgb", &arguments).unwrap();
+        assert!(matches!(expected, Some(Value::Color(_))));
+        assert_eq!(call("RGB", &arguments).unwrap(), expected);
+        assert_eq!(call("Rgb", &arguments).unwrap(), expected);
+
+        // Unknown functions are output as-is
+        assert_eq!(call("foo", &arguments).unwrap(), None);
+    }
+}
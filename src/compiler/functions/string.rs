@@ -0,0 +1,191 @@
+//! String functions.
+
+use std::fmt::Write;
+
+use regex::RegexBuilder;
+
+use super::{wrong_arguments, Result};
+use crate::compiler::value::Value;
+
+/// The characters that `escape()` leaves as-is besides ASCII letters and digits, which are those of
+/// `encodeURI()` except `=`, `:`, `#`, `;`, `(` and `)`.
+const ESCAPE_UNRESERVED: &str = "-_.!~*'/?@&+$,";
+
+/// The characters that `encodeURIComponent()` leaves as-is besides ASCII letters and digits.
+const COMPONENT_UNRESERVED: &str = "-_.!~*'()";
+
+/// Percent-encodes the characters of a text other than ASCII letters, digits and `unreserved`.
+fn encode(text: &str, unreserved: &str) -> String {
+    let mut encoded = String::new();
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || unreserved.contains(c) {
+            encoded.push(c);
+        } else {
+            for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                write!(encoded, "%{byte:02X}").unwrap();
+            }
+        }
+    }
+    encoded
+}
+
+/// Returns the text of a value, without quotes if it's a string.
+fn text(value: &Value) -> String {
+    match value {
+        Value::Quoted(text, _) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Returns the contents of a string and its quote, or `None` for an escaped string (e.g. `~"a"`),
+/// so that the result of a function is the same kind of string.
+fn string(value: &Value) -> std::result::Result<(&str, Option<char>), String> {
+    match value {
+        Value::Quoted(text, quote) => Ok((text, Some(*quote))),
+        Value::Keyword(text) => Ok((text, None)),
+        _ => Err("the first argument must be a string".to_string()),
+    }
+}
+
+fn with_quote(text: String, quote: Option<char>) -> Value {
+    match quote {
+        Some(quote) => Value::Quoted(text, quote),
+        None => Value::Keyword(text),
+    }
+}
+
+/// URL-encodes a string (e.g. `escape("a=1")` is `a%3D1`).
+pub(super) fn escape(arguments: &[Value]) -> Result {
+    let [value] = arguments else {
+        return Err(wrong_arguments());
+    };
+    Ok(Some(Value::Keyword(encode(
+        &text(value),
+        ESCAPE_UNRESERVED,
+    ))))
+}
+
+/// Returns the contents of a string without quotes.
+pub(super) fn e(arguments: &[Value]) -> Result {
+    let [value] = arguments else {
+        return Err(wrong_arguments());
+    };
+    Ok(Some(Value::Keyword(text(value))))
+}
+
+/// Formats a string with `%()`, replacing `%s`, `%d` and `%a` with the arguments in order, where
+/// strings replace `%s` without quotes. The uppercase placeholders (e.g. `%S`) URL-encode the
+/// argument, and `%%` is a literal `%`.
+pub(super) fn format(arguments: &[Value]) -> Result {
+    let [string, values @ ..] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let (string, quote) = self::string(string)?;
+    let mut values = values.iter();
+    let mut result = String::new();
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.clone().next() {
+            Some('%') => {
+                chars.next();
+                result.push('%');
+            }
+            Some(placeholder @ ('s' | 'd' | 'a' | 'S' | 'D' | 'A')) if values.len() > 0 => {
+                chars.next();
+                let value = match (placeholder, values.next().unwrap()) {
+                    ('s' | 'S', value) => text(value),
+                    (_, value) => value.to_string(),
+                };
+                match placeholder.is_ascii_uppercase() {
+                    true => result.push_str(&encode(&value, COMPONENT_UNRESERVED)),
+                    false => result.push_str(&value),
+                }
+            }
+            _ => result.push('%'),
+        }
+    }
+    Ok(Some(with_quote(result, quote)))
+}
+
+/// Converts a JavaScript replacement pattern (e.g. `$1` or `$&`) to the syntax of the `regex`
+/// crate.
+fn replacement(text: &str) -> String {
+    let mut replacement = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('$', Some('&')) => {
+                chars.next();
+                replacement.push_str("${0}");
+            }
+            ('$', Some(digit)) if digit.is_ascii_digit() => {
+                let mut group = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    group.push(digit);
+                }
+                write!(replacement, "${{{group}}}").unwrap();
+            }
+            ('$', Some('$')) => {
+                chars.next();
+                replacement.push_str("$$");
+            }
+            ('$', _) => replacement.push_str("$$"),
+            (c, _) => replacement.push(c),
+        }
+    }
+    replacement
+}
+
+/// Replaces the matches of a regular expression in a string, like `String.prototype.replace()`
+/// with the flags `g` (all matches), `i` (case-insensitive), `m` (multi-line) and `s` (`.` matches
+/// newlines).
+pub(super) fn replace(arguments: &[Value]) -> Result {
+    let (string, pattern, replacement, flags) = match arguments {
+        [string, pattern, replacement] => (string, pattern, replacement, String::new()),
+        [string, pattern, replacement, flags] => (string, pattern, replacement, text(flags)),
+        _ => return Err(wrong_arguments()),
+    };
+    let (string, quote) = self::string(string)?;
+    let pattern = text(pattern);
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(flags.contains('i'))
+        .multi_line(flags.contains('m'))
+        .dot_matches_new_line(flags.contains('s'))
+        .build()
+        .map_err(|_| format!("invalid regular expression: {pattern}"))?;
+    let replacement = self::replacement(&text(replacement));
+    let result = match flags.contains('g') {
+        true => regex.replace_all(string, replacement),
+        false => regex.replace(string, replacement),
+    };
+    Ok(Some(with_quote(result.into_owned(), quote)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_string_functions() {
+        let input = r#"
+            @name: "world";
+            .a {
+              a: escape("a=1; b=(2)") e("-ms-filter") ~"b";
+              b: %("%s, %d!", @name, @name) %('%d%% of %A', 50, "a b");
+              c: replace("Hello, Mars", "Mars", "World") replace(~"bar-1", "1", "2");
+              d: replace("abc abc", "(b)", "[$1]", "g") replace("A-b", "a", "x", "i");
+            }
+        "#;
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: a%3D1%3B%20b%3D%282%29 -ms-filter b;\n  \
+             b: \"world, \"world\"!\" '50% of %22a%20b%22';\n  \
+             c: \"Hello, World\" bar-2;\n  d: \"a[b]c a[b]c\" \"x-b\";\n}\n"
+        );
+    }
+}
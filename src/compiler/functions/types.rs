@@ -0,0 +1,160 @@
+//! Type checking and unit functions.
+
+use super::{wrong_arguments, Result};
+use crate::compiler::unit::Unit;
+use crate::compiler::value::Value;
+
+fn boolean(value: bool) -> Value {
+    Value::Keyword(value.to_string())
+}
+
+/// Checks the type of a value, returning `true` or `false`.
+fn is(arguments: &[Value], check: fn(&Value) -> bool) -> Result {
+    let [value] = arguments else {
+        return Err(wrong_arguments());
+    };
+    Ok(Some(boolean(check(value))))
+}
+
+pub(super) fn isnumber(arguments: &[Value]) -> Result {
+    is(arguments, |value| matches!(value, Value::Number(..)))
+}
+
+pub(super) fn isstring(arguments: &[Value]) -> Result {
+    is(arguments, |value| matches!(value, Value::Quoted(..)))
+}
+
+pub(super) fn iscolor(arguments: &[Value]) -> Result {
+    is(arguments, |value| matches!(value, Value::Color(_)))
+}
+
+pub(super) fn iskeyword(arguments: &[Value]) -> Result {
+    is(arguments, |value| matches!(value, Value::Keyword(_)))
+}
+
+pub(super) fn isurl(arguments: &[Value]) -> Result {
+    is(arguments, |value| matches!(value, Value::Url(_)))
+}
+
+/// Checks whether a value is a number with the given unit.
+fn has_unit(arguments: &[Value], unit: &str) -> Result {
+    let [value] = arguments else {
+        return Err(wrong_arguments());
+    };
+    let has_unit = matches!(value, Value::Number(_, number_unit) if number_unit.is(unit));
+    Ok(Some(boolean(has_unit)))
+}
+
+pub(super) fn ispixel(arguments: &[Value]) -> Result {
+    has_unit(arguments, "px")
+}
+
+pub(super) fn isem(arguments: &[Value]) -> Result {
+    has_unit(arguments, "em")
+}
+
+pub(super) fn ispercentage(arguments: &[Value]) -> Result {
+    has_unit(arguments, "%")
+}
+
+/// Returns the name of a unit given as an argument (e.g. `px`, `"px"` or `%`).
+fn unit_name(value: &Value) -> String {
+    match value {
+        Value::Keyword(unit) | Value::Quoted(unit, _) => unit.clone(),
+        value => value.to_string(),
+    }
+}
+
+pub(super) fn isunit(arguments: &[Value]) -> Result {
+    let [value, unit] = arguments else {
+        return Err("missing the required second argument".to_string());
+    };
+    let is_unit =
+        matches!(value, Value::Number(_, number_unit) if number_unit.is(&unit_name(unit)));
+    Ok(Some(boolean(is_unit)))
+}
+
+/// Changes (or removes) the unit of a number without converting it.
+pub(super) fn unit(arguments: &[Value]) -> Result {
+    let (number, unit) = match arguments {
+        [Value::Number(number, _)] => (number, String::new()),
+        [Value::Number(number, _), unit] => (number, unit_name(unit)),
+        [Value::Operation(..)] | [Value::Operation(..), _] => {
+            return Err(
+                "the first argument must be a number; have you forgotten parentheses?".to_string(),
+            )
+        }
+        [_] | [_, _] => return Err("the first argument must be a number".to_string()),
+        _ => return Err(wrong_arguments()),
+    };
+    Ok(Some(Value::Number(*number, Unit::new(&unit))))
+}
+
+pub(super) fn get_unit(arguments: &[Value]) -> Result {
+    let [Value::Number(_, unit)] = arguments else {
+        return Err("argument must be a number".to_string());
+    };
+    Ok(Some(Value::Keyword(unit.to_string())))
+}
+
+/// Converts a number to a compatible unit (e.g. `convert(9s, ms)` is `9000ms`), leaving it as-is if
+/// the units aren't compatible.
+pub(super) fn convert(arguments: &[Value]) -> Result {
+    let [Value::Number(number, number_unit), unit] = arguments else {
+        return Err("arguments must be a number and a unit".to_string());
+    };
+    let (number, unit) = number_unit.convert(*number, &Unit::new(&unit_name(unit)));
+    Ok(Some(Value::Number(number, unit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_type_functions() {
+        let input = "
+            @a: 2px;
+            @dr: { b: c; };
+            .a {
+              a: isnumber(1px) isnumber(a) isstring(\"a\") iscolor(red) iskeyword(auto);
+              b: iskeyword(red) isurl(url(x)) ispixel(1px) isem(1px) ispercentage(1%);
+              c: isunit(10px, px) isunit(10, \"px\") isruleset(@dr) isruleset(@a);
+              d: isdefined(@a) isdefined(@undefined);
+              e: unit(5, px) unit(@a) unit(5, %) get-unit(5em);
+              f: convert(9s, ms) convert(14cm, mm) convert(8, mm);
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: true false true true true;\n  b: false true true false true;\n  \
+             c: true false true false;\n  d: true false;\n  e: 5px 2 5% em;\n  \
+             f: 9000ms 140mm 8;\n}\n"
+        );
+
+        // Type checks in guards
+        let input = "
+            .m(@a) when (isnumber(@a)) and not (ispixel(@a)) { number: @a; }
+            .m(@a) when (ispixel(@a)) { pixels: @a; }
+            .m(@a) when (iscolor(@a)), (iskeyword(@a)) { other: @a; }
+            .m(@a) when (isunit(@a, em)) and (@a > 1) { large: @a; }
+            @mode: dark;
+            .a when (isstring(@mode)) { c: d; }
+            .b when (iskeyword(@mode)) { .m(1px); .m(2em); .m(red); .m(auto); .m(\"x\"); }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".b {\n  pixels: 1px;\n  number: 2em;\n  large: 2em;\n  other: red;\n  \
+             other: auto;\n}\n"
+        );
+
+        let error = compile(".a { b: unit(5/2); }", Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "error evaluating function `unit`: the first argument must be a number; have you \
+             forgotten parentheses? at 8..17"
+        );
+    }
+}
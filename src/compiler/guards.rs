@@ -0,0 +1,158 @@
+//! Parsing of guards and comparison of their values.
+//!
+//! Like in Less.js, a guard is a list of conditions separated by commas or `or`, which passes if
+//! any of them does (e.g. `when (@a > 0), (@b)`). A condition is a list of parenthesized
+//! comparisons separated by `and`, each of which may be negated with `not` (e.g.
+//! `(iscolor(@c)) and not (@c = black)`). A value without a comparison passes if it's `true`.
+
+use std::cmp::Ordering;
+
+use crate::error::EvalError;
+use crate::lexer::{Delim, Spanned, Token, TokenTree};
+
+use super::value::Value;
+use super::{is_symbol, span_of, trim};
+
+type Tokens<'a, 'src> = &'a [Spanned<TokenTree<&'src str>>];
+
+/// A parsed guard, whose values are evaluated when the guard is.
+pub(super) enum Condition<'a, 'src> {
+    Or(Vec<Condition<'a, 'src>>),
+    And(Vec<Condition<'a, 'src>>),
+    Not(Box<Condition<'a, 'src>>),
+    Comparison(Tokens<'a, 'src>, Comparison, Tokens<'a, 'src>),
+    /// A value that passes if it's `true`
+    Value(Tokens<'a, 'src>),
+}
+
+#[derive(Copy, Clone)]
+pub(super) enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    /// Returns whether the result of comparing two values satisfies the comparison. Values that
+    /// can't be compared don't satisfy any comparison.
+    pub fn holds(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (_, None) => false,
+            (Comparison::Less, Some(ordering)) => ordering.is_lt(),
+            (Comparison::LessOrEqual, Some(ordering)) => ordering.is_le(),
+            (Comparison::Equal, Some(ordering)) => ordering.is_eq(),
+            (Comparison::GreaterOrEqual, Some(ordering)) => ordering.is_ge(),
+            (Comparison::Greater, Some(ordering)) => ordering.is_gt(),
+        }
+    }
+}
+
+/// Parses a guard, without the `when` keyword.
+pub(super) fn parse<'a, 'src>(tts: Tokens<'a, 'src>) -> Result<Condition<'a, 'src>, EvalError> {
+    // Parentheses are token trees, so only the separators outside of them are split at
+    let alternatives = tts
+        .split(|(tt, _)| is_symbol(tt, ',') || is_keyword(tt, "or"))
+        .map(|tts| {
+            let conditions = tts
+                .split(|(tt, _)| is_keyword(tt, "and"))
+                .map(negation)
+                .collect::<Result<_, _>>()?;
+            Ok(one_or(conditions, Condition::And))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(one_or(alternatives, Condition::Or))
+}
+
+/// Parses a parenthesized condition, which may be negated (e.g. `not (@a > 0)`).
+fn negation<'a, 'src>(tts: Tokens<'a, 'src>) -> Result<Condition<'a, 'src>, EvalError> {
+    match trim(tts) {
+        [(not, _), rest @ ..] if is_keyword(not, "not") => {
+            Ok(Condition::Not(Box::new(negation(rest)?)))
+        }
+        [(TokenTree::Tree(Delim::Paren, tts), _)] => parenthesized(tts),
+        tts => Err(EvalError::new(span_of(tts), "invalid guard")),
+    }
+}
+
+/// Parses the contents of the parentheses of a condition, which are a comparison, a value or a
+/// nested guard (e.g. `((@a) or (@b))`).
+fn parenthesized<'a, 'src>(tts: Tokens<'a, 'src>) -> Result<Condition<'a, 'src>, EvalError> {
+    let tts = trim(tts);
+    let is_nested = tts
+        .iter()
+        .any(|(tt, _)| is_keyword(tt, "and") || is_keyword(tt, "or"))
+        || matches!(tts, [(not, _), ..] if is_keyword(not, "not"))
+        || matches!(tts, [(TokenTree::Tree(Delim::Paren, _), _)]);
+    if is_nested {
+        return parse(tts);
+    }
+
+    let Some(i) = tts
+        .iter()
+        .position(|(tt, _)| matches!(tt, TokenTree::Token(Token::Symbol('<' | '>' | '='))))
+    else {
+        return Ok(Condition::Value(tts));
+    };
+    // The second symbol of an operator (e.g. `>=`) must be right after the first
+    let next = tts
+        .get(i + 1)
+        .filter(|(_, span)| span.start == tts[i].1.end)
+        .and_then(|(tt, _)| match tt {
+            TokenTree::Token(Token::Symbol(symbol)) => Some(*symbol),
+            _ => None,
+        });
+    let TokenTree::Token(Token::Symbol(first)) = tts[i].0 else {
+        unreachable!()
+    };
+    let (comparison, len) = match (first, next) {
+        ('>', Some('=')) | ('=', Some('>')) => (Comparison::GreaterOrEqual, 2),
+        ('<', Some('=')) | ('=', Some('<')) => (Comparison::LessOrEqual, 2),
+        ('>', _) => (Comparison::Greater, 1),
+        ('<', _) => (Comparison::Less, 1),
+        _ => (Comparison::Equal, 1),
+    };
+    let (left, right) = (trim(&tts[..i]), trim(&tts[i + len..]));
+    if left.is_empty() || right.is_empty() {
+        return Err(EvalError::new(span_of(tts), "invalid guard"));
+    }
+    Ok(Condition::Comparison(left, comparison, right))
+}
+
+/// Compares two values like Less.js: numbers are compared after converting compatible units,
+/// quoted strings by their contents, and other values are only equal if they're output the same.
+pub(super) fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left, left_unit), Value::Number(right, right_unit)) => {
+            if left_unit.is_empty() || right_unit.is_empty() {
+                return left.partial_cmp(right);
+            }
+            let (left, left_unit) = left_unit.unify(*left);
+            let (right, right_unit) = right_unit.unify(*right);
+            match left_unit.describe() == right_unit.describe() {
+                true => left.partial_cmp(&right),
+                false => None,
+            }
+        }
+        (Value::Quoted(left, _), Value::Quoted(right, _)) => Some(left.cmp(right)),
+        (Value::Color(left), Value::Color(right)) => {
+            (left.rgb == right.rgb && left.alpha == right.alpha).then_some(Ordering::Equal)
+        }
+        (left, right) => (left.to_string() == right.to_string()).then_some(Ordering::Equal),
+    }
+}
+
+fn one_or<'a, 'src>(
+    mut conditions: Vec<Condition<'a, 'src>>,
+    combine: fn(Vec<Condition<'a, 'src>>) -> Condition<'a, 'src>,
+) -> Condition<'a, 'src> {
+    match conditions.len() {
+        1 => conditions.pop().unwrap(),
+        _ => combine(conditions),
+    }
+}
+
+fn is_keyword(tt: &TokenTree<&str>, keyword: &str) -> bool {
+    matches!(tt, TokenTree::Token(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword))
+}
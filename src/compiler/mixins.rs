@@ -0,0 +1,322 @@
+//! Lookup of mixins and their parameters.
+//!
+//! Like in Less.js, a mixin is a rule with a class or id selector, which may have parameters (e.g.
+//! `.mixin(@a; @b: 2) { ... }`) or be a rule that is also output (e.g. `.a { ... }`). A call
+//! expands all the definitions that match its arguments and whose guards pass, in the innermost
+//! block that has any.
+//! Namespaced calls (e.g. `#ns > .mixin()`) look for the rest of the selector in the blocks of
+//! the rules that match its start.
+
+use crate::ast::{
+    Combinator, Item, ListOfItems, QualifiedRule, Selector, SelectorComponent, SelectorName,
+};
+use crate::lexer::{Delim, Span, Spanned, Token, TokenTree};
+
+use super::scope::Definition;
+use super::{is_symbol, span_of, trim};
+
+type Tokens<'a, 'src> = &'a [Spanned<TokenTree<&'src str>>];
+
+/// A definition of a mixin.
+pub(super) struct Mixin<'a, 'src> {
+    /// The span of the rule
+    pub span: Span,
+    /// The span of the selector, which is listed when no definition matches a call
+    pub selector: Span,
+    /// The parameters, or `None` for a rule without them (e.g. `.a { ... }`)
+    pub parameters: Option<Vec<Parameter<'a, 'src>>>,
+    /// The guard, without the `when` keyword (e.g. `(@a > 0)`), or the tokens after the
+    /// parameters if they aren't a guard
+    pub guard: Tokens<'a, 'src>,
+    pub block: &'a ListOfItems<&'src str>,
+    /// The blocks of the namespaces the mixin is in, outermost first
+    pub namespaces: Vec<&'a ListOfItems<&'src str>>,
+}
+
+/// A parameter of a mixin.
+pub(super) enum Parameter<'a, 'src> {
+    /// A variable, with its default value if any (e.g. `@color: red`)
+    Variable(&'src str, Option<Tokens<'a, 'src>>),
+    /// A value that the argument must be equal to (e.g. `dark`)
+    Pattern(Tokens<'a, 'src>),
+    /// The rest of the arguments, which may be bound to a variable (e.g. `@rest...` or `...`)
+    Rest(Option<&'src str>),
+}
+
+/// An evaluated argument of a mixin call.
+pub(super) struct Argument<'a, 'src> {
+    pub name: Option<&'src str>,
+    pub value: Definition<'a, 'src>,
+    pub span: Span,
+}
+
+/// Returns the name, parameters and guard of the prelude of a mixin definition (e.g.
+/// `.mixin(@a) when (@a > 0)`), which the parser doesn't distinguish from other rules yet.
+pub(super) fn definition<'a, 'src>(
+    prelude: Tokens<'a, 'src>,
+) -> Option<(String, Tokens<'a, 'src>, Tokens<'a, 'src>)> {
+    let (name, parameters, guard) = match trim(prelude) {
+        [(TokenTree::Token(Token::Symbol('.')), _), (TokenTree::Token(Token::Ident(name)), _), (TokenTree::Tree(Delim::Paren, parameters), _), guard @ ..] => {
+            (format!(".{name}"), parameters, trim(guard))
+        }
+        [(TokenTree::Token(Token::Hash(name)), _), (TokenTree::Tree(Delim::Paren, parameters), _), guard @ ..] => {
+            (format!("#{name}"), parameters, trim(guard))
+        }
+        _ => return None,
+    };
+    let guard = match guard {
+        [(TokenTree::Token(Token::Ident("when")), _), guard @ ..] => trim(guard),
+        guard => guard,
+    };
+    Some((name, parameters, guard))
+}
+
+/// Parses the parameters of a mixin definition, which are separated by semicolons if there are
+/// any, and by commas otherwise.
+fn parameters<'a, 'src>(tts: Tokens<'a, 'src>) -> Vec<Parameter<'a, 'src>> {
+    let separator = match tts.iter().any(|(tt, _)| is_symbol(tt, ';')) {
+        true => ';',
+        false => ',',
+    };
+    tts.split(|(tt, _)| is_symbol(tt, separator))
+        .map(trim)
+        .filter(|tts| !tts.is_empty())
+        .map(|tts| match tts {
+            [(TokenTree::Token(Token::Symbol('@')), _), (TokenTree::Token(Token::Ident(name)), _), rest @ ..] => {
+                match trim(rest) {
+                    [] => Parameter::Variable(name, None),
+                    [(TokenTree::Token(Token::Symbol(':')), _), value @ ..] => {
+                        Parameter::Variable(name, Some(trim(value)))
+                    }
+                    rest if is_ellipsis(rest) => Parameter::Rest(Some(name)),
+                    _ => Parameter::Pattern(tts),
+                }
+            }
+            tts if is_ellipsis(tts) => Parameter::Rest(None),
+            tts => Parameter::Pattern(tts),
+        })
+        .collect()
+}
+
+fn is_ellipsis<S>(tts: &[Spanned<TokenTree<S>>]) -> bool {
+    tts.len() == 3 && tts.iter().all(|(tt, _)| is_symbol(tt, '.'))
+}
+
+/// Returns the names in the selector of a mixin call (e.g. `#ns` and `.mixin` for `#ns > .mixin`).
+pub(super) fn path(selector: &[Spanned<TokenTree<&str>>]) -> Vec<String> {
+    let mut path = Vec::new();
+    for (i, (tt, _)) in selector.iter().enumerate() {
+        match tt {
+            TokenTree::Token(Token::Hash(name)) => path.push(format!("#{name}")),
+            TokenTree::Token(Token::Ident(name)) if i > 0 && is_symbol(&selector[i - 1].0, '.') => {
+                path.push(format!(".{name}"))
+            }
+            _ => {}
+        }
+    }
+    path
+}
+
+/// Returns the names of a selector that only has classes and ids (e.g. `#ns .mixin`).
+fn names(selector: &Selector<&str>) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    for (component, _) in &selector.components {
+        match component {
+            SelectorComponent::Class(SelectorName::Ident(name)) => names.push(format!(".{name}")),
+            SelectorComponent::Id(SelectorName::Ident(name)) => names.push(format!("#{name}")),
+            SelectorComponent::Combinator(Combinator::Descendant | Combinator::Child) => {}
+            _ => return None,
+        }
+    }
+    (!names.is_empty()).then_some(names)
+}
+
+/// Finds the definitions of the mixin with the given path in the innermost of the blocks that has
+/// any.
+pub(super) fn find<'a, 'src>(
+    blocks: impl Iterator<Item = &'a ListOfItems<&'src str>>,
+    path: &[String],
+) -> Vec<Mixin<'a, 'src>> {
+    for block in blocks {
+        let mut mixins = Vec::new();
+        find_in(block, path, &mut Vec::new(), &mut mixins);
+        if !mixins.is_empty() {
+            return mixins;
+        }
+    }
+    Vec::new()
+}
+
+fn find_in<'a, 'src>(
+    block: &'a ListOfItems<&'src str>,
+    path: &[String],
+    namespaces: &mut Vec<&'a ListOfItems<&'src str>>,
+    mixins: &mut Vec<Mixin<'a, 'src>>,
+) {
+    for (item, span) in &block.0 {
+        let Item::QualifiedRule(rule) = item else {
+            continue;
+        };
+        let (len, mixin) = match rule {
+            QualifiedRule::Style(rule) => {
+                let Some((selector, len)) = rule.selectors.0.iter().find_map(|(selector, span)| {
+                    let names = names(selector)?;
+                    path.starts_with(&names).then_some((*span, names.len()))
+                }) else {
+                    continue;
+                };
+                let mixin = Mixin {
+                    span: *span,
+                    selector,
+                    parameters: None,
+                    guard: rule.guard.as_ref().map_or(&[], |guard| &guard.0),
+                    block: &rule.block,
+                    namespaces: namespaces.clone(),
+                };
+                (len, mixin)
+            }
+            QualifiedRule::Generic(rule) => match definition(&rule.prelude.0) {
+                Some((name, parameters, guard)) if name == path[0] => {
+                    let mixin = Mixin {
+                        span: *span,
+                        selector: span_of(trim(&rule.prelude.0)),
+                        parameters: Some(self::parameters(parameters)),
+                        guard,
+                        block: &rule.block,
+                        namespaces: namespaces.clone(),
+                    };
+                    (1, mixin)
+                }
+                _ => continue,
+            },
+            _ => continue,
+        };
+
+        if len == path.len() {
+            mixins.push(mixin);
+        } else {
+            namespaces.push(mixin.block);
+            find_in(mixin.block, &path[len..], namespaces, mixins);
+            namespaces.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+
+    #[test]
+    fn test_mixins() {
+        let input = "
+            .bordered(@width: 2px; @style: solid) {
+              border: @width @style black;
+            }
+            .theme(dark; @color) { color: darken(@color, 10%); }
+            .theme(light; @color) { color: lighten(@color, 10%); }
+            .theme(@_; @color) { display: block; }
+            .box-shadow(@style; @rest...) {
+              box-shadow: @style @rest;
+              args: @arguments;
+            }
+            .plain { float: left; }
+            #ns {
+              @size: 10px;
+              .m() { width: @size; &:hover { width: (@size * 2); } }
+            }
+            .sizes() { @small: 1px; }
+            .a {
+              .bordered(4px);
+              .bordered(@style: dashed);
+              .theme(dark; #888);
+              .box-shadow(inset, 0 1px red, 2px);
+              .plain;
+              #ns > .m() !important;
+              .sizes();
+              small: @small;
+              .wrap({ inner: 1; });
+            }
+            .wrap(@rules) { @rules(); }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".plain {\n  float: left;\n}\n\
+             .a {\n  border: 4px solid black;\n  border: 2px dashed black;\n  color: #6f6f6f;\n  \
+             display: block;\n  box-shadow: inset 0 1px red 2px;\n  \
+             args: inset 0 1px red 2px;\n  float: left;\n  width: 10px !important;\n  \
+             small: 1px;\n  inner: 1;\n}\n\
+             .a:hover {\n  width: 20px !important;\n}\n"
+        );
+
+        let input = "
+            .m(@a) { a: @a; }
+            .m(@a; @b) { b: @b; }
+            .x { .m(1; 2; 3); }
+        ";
+        let error = compile(input, Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "no matching definition was found for `.m(1; 2; 3)`; candidates: `.m(@a)`, \
+             `.m(@a; @b)` at 82..94"
+        );
+
+        // Patterns are matched against the positional arguments, after the named ones are bound
+        let input = "
+            .m(dark; @c) { color: @c; }
+            .m(light; @c) { color: white; }
+            .x { .m(@c: red; dark); }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(css, ".x {\n  color: red;\n}\n");
+
+        // Every definition whose guard passes is expanded
+        let input = "
+            .m(@a) when (@a > 0) { a: 1; }
+            .m(@a) when (@a >= 1cm) and not (@a = 20mm) { b: 2; }
+            .m(@a) when (@a < 0), (@a = 12mm) { c: 3; }
+            .m(@a) when ((@a > 100) or (default())) { d: 4; }
+            .x { .m(12mm); }
+            .y { .m(-1); }
+            .z { .m(0); }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".x {\n  a: 1;\n  b: 2;\n  c: 3;\n}\n.y {\n  c: 3;\n}\n.z {\n  d: 4;\n}\n"
+        );
+
+        let input =
+            ".m() when (default()) { a: 1; }\n.m() when not (default()) { b: 2; }\n.x { .m(); }";
+        let error = compile(input, Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "ambiguous use of `default()` found when matching for `.m()` at 73..78"
+        );
+
+        // Mixins with parameters may call themselves
+        let input = "
+            .loop(@i) when (@i > 0) {
+              .w-@{i} { width: (@i * 10px); }
+              .loop(@i - 1);
+            }
+            .loop(3);
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".w-3 {\n  width: 30px;\n}\n.w-2 {\n  width: 20px;\n}\n.w-1 {\n  width: 10px;\n}\n"
+        );
+
+        let input = ".loop(@i) { .loop(@i + 1); }\n.loop(0);";
+        let error = compile(input, Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "mixins are nested more than 100 levels deep, which is probably infinite recursion \
+             at 12..26"
+        );
+
+        let error = compile(".x { .missing(); }", Options::default()).unwrap_err();
+        assert_eq!(error.to_string(), "mixin `.missing` is undefined at 5..16");
+    }
+}
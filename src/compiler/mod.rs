@@ -0,0 +1,1729 @@
+//! Compilation of LESS to CSS: nested rules are flattened, variables are evaluated, and mixin
+//! definitions and `//` comments are dropped.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ast::{
+    self, AtRule, AttributeValue, Call, Declaration, DeclarationName, ImportOption,
+    InterpolationPart, Item, KeyframeSelector, KeyframesName, ListOfItems, MapStrings, MixinCall,
+    QualifiedRule, Selector, SelectorComponent, SelectorName, Stylesheet, UrlOrString,
+};
+use crate::error::{CompileError, EvalError};
+use crate::lexer::helpers::is_name;
+use crate::lexer::{Delim, Span, Spanned, Token, TokenTree};
+use crate::printer::{at_rule_header, selector_to_string};
+use crate::{lex, parse_tokens};
+
+use self::color::Color;
+use self::css::Node;
+use self::guards::Condition;
+use self::mixins::{Argument, Mixin, Parameter};
+use self::scope::{Definition, Scopes};
+use self::unit::Unit;
+use self::value::{Operator, Separator, Value};
+
+mod color;
+mod css;
+mod functions;
+mod guards;
+mod mixins;
+mod operations;
+mod scope;
+mod selectors;
+mod unit;
+mod value;
+
+/// Options for [`compile`].
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Whether to leave out all optional whitespace and comments
+    pub compress: bool,
+    /// Where arithmetic operations are evaluated
+    pub math: Math,
+    /// Whether to error on operations with incompatible units (e.g. `10px + 5em`) and on numbers
+    /// with multiple units (e.g. `2px * 3px`), instead of keeping the first unit
+    pub strict_units: bool,
+}
+
+/// Where arithmetic operations are evaluated, like the `math` option of Less.js. Operations that
+/// aren't evaluated are output as they're written (e.g. `12px/1.5`).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Math {
+    /// All operations are evaluated
+    Always,
+    /// Division is only evaluated in parentheses or with `./`
+    #[default]
+    ParensDivision,
+    /// All operations are only evaluated in parentheses (`parens` or `strict` in Less.js)
+    Parens,
+}
+
+/// Compiles a LESS stylesheet to CSS.
+///
+/// Some features of Less.js aren't supported yet, and are reported as errors:
+/// - `:extend`, both as an item (e.g. `&:extend(.a);`) and in a selector (e.g. `.b:extend(.a)`)
+/// - importing LESS files, as files aren't loaded: only imports of CSS (a `url()`, a path that
+///   ends in `.css`, or an import with the `css` option) are supported, and are output as-is
+pub fn compile(input: &str, options: Options) -> std::result::Result<String, CompileError> {
+    let tts = lex(input)?;
+    let stylesheet = parse_tokens(input, &tts)?;
+    let mut comments = HashMap::new();
+    block_comments(input, &tts, &stylesheet.items, None, &mut comments);
+    let mut compiler = Compiler {
+        source: input,
+        options: options.clone(),
+        scopes: Scopes::new(),
+        comments,
+        expanding: Vec::new(),
+        parens: 0,
+        in_calc: false,
+        default: None,
+    };
+    let nodes = compiler.stylesheet(&stylesheet)?;
+    Ok(css::write(&nodes, options.compress))
+}
+
+/// Collects the block comments between the items of a block and of the blocks nested in them, by
+/// the span of the item that owns the block (or `None` for the stylesheet). Line comments are left
+/// out, as they aren't output.
+fn block_comments(
+    source: &str,
+    mut tts: &[Spanned<TokenTree<&str>>],
+    items: &ListOfItems<&str>,
+    owner: Option<Span>,
+    comments: &mut HashMap<Option<Span>, Vec<Spanned<String>>>,
+) {
+    let mut push_comments = |tts: &[Spanned<TokenTree<&str>>]| {
+        for (tt, span) in tts {
+            let text = &source[span.into_range()];
+            if matches!(tt, TokenTree::Token(Token::Comment(_))) && text.starts_with("/*") {
+                comments
+                    .entry(owner)
+                    .or_default()
+                    .push((text.to_string(), *span));
+            }
+        }
+    };
+
+    let mut blocks = Vec::new();
+    for (item, span) in &items.0 {
+        let start = tts
+            .iter()
+            .position(|(_, tt_span)| tt_span.start >= span.start)
+            .unwrap_or(tts.len());
+        push_comments(&tts[..start]);
+        let len = tts[start..]
+            .iter()
+            .take_while(|(_, tt_span)| tt_span.end <= span.end)
+            .count();
+        // The block of an item is always its last pair of braces
+        let block = tts[start..start + len]
+            .iter()
+            .rev()
+            .find_map(|(tt, _)| match tt {
+                TokenTree::Tree(Delim::Brace, tts) => Some(tts),
+                _ => None,
+            });
+        if let (Some(items), Some(tts)) = (item.block(), block) {
+            blocks.push((tts, items, *span));
+        }
+        tts = &tts[start + len..];
+    }
+    push_comments(tts);
+
+    for (tts, items, span) in blocks {
+        block_comments(source, tts, items, Some(span), comments);
+    }
+}
+
+/// The maximum number of mixins that are expanded inside each other.
+const MAX_MIXIN_DEPTH: usize = 100;
+
+/// Where the declarations of a block are output.
+#[derive(Copy, Clone)]
+enum Context<'c> {
+    /// The stylesheet, where declarations aren't allowed
+    Root,
+    /// A style rule, whose declarations are output in a rule with its resolved selectors
+    Rule(&'c [Selector<String>]),
+    /// A block whose declarations are output as-is (e.g. `@font-face` or a keyframe)
+    Declarations,
+}
+
+struct Compiler<'a, 'src> {
+    source: &'src str,
+    options: Options,
+    scopes: Scopes<'a, 'src>,
+    comments: HashMap<Option<Span>, Vec<Spanned<String>>>,
+    /// The spans of the mixins that are being expanded, innermost last. Like in Less.js, rules
+    /// without parameters aren't expanded again inside themselves, while mixins with parameters
+    /// may call themselves (e.g. in a guarded loop) up to [`MAX_MIXIN_DEPTH`] levels deep.
+    expanding: Vec<Span>,
+    /// The number of parentheses the value that is being evaluated is in
+    parens: usize,
+    /// Whether the value that is being evaluated is in a `calc()`, where operations are output
+    /// as-is
+    in_calc: bool,
+    /// What `default()` returns while the guard of a mixin definition is being evaluated
+    default: Option<bool>,
+}
+
+type Result<T> = std::result::Result<T, EvalError>;
+
+impl<'a, 'src> Compiler<'a, 'src> {
+    fn stylesheet(&mut self, stylesheet: &'a Stylesheet<&'src str>) -> Result<Vec<Node>> {
+        let mut nodes = Vec::new();
+        self.block(&stylesheet.items, None, Context::Root, &mut nodes)?;
+        Ok(nodes)
+    }
+
+    /// Evaluates a block of items in a new scope, pushing the output nodes to `output`.
+    fn block(
+        &mut self,
+        items: &'a ListOfItems<&'src str>,
+        owner: Option<Span>,
+        context: Context,
+        output: &mut Vec<Node>,
+    ) -> Result<()> {
+        self.scopes.push(items);
+        let result = self.block_items(items, owner, context, output);
+        self.scopes.pop();
+        result
+    }
+
+    /// Evaluates a block of items in the current scope, pushing the output nodes to `output`.
+    fn block_items(
+        &mut self,
+        items: &'a ListOfItems<&'src str>,
+        owner: Option<Span>,
+        context: Context,
+        output: &mut Vec<Node>,
+    ) -> Result<()> {
+        let mut declarations = Vec::new();
+        let mut nested = Vec::new();
+        self.items(items, owner, context, &mut declarations, &mut nested)?;
+
+        match context {
+            Context::Rule(selectors) if !declarations.is_empty() => output.push(Node::Rule {
+                selectors: selectors.iter().map(selector_to_string).collect(),
+                children: declarations,
+            }),
+            _ => output.extend(declarations),
+        }
+        output.extend(nested);
+        Ok(())
+    }
+
+    /// Evaluates the items of a block in the current scope. Declarations (and the comments between
+    /// them) are pushed to `declarations`, and rules and at-rules to `nested`.
+    fn items(
+        &mut self,
+        items: &'a ListOfItems<&'src str>,
+        owner: Option<Span>,
+        context: Context,
+        declarations: &mut Vec<Node>,
+        nested: &mut Vec<Node>,
+    ) -> Result<()> {
+        let comments = self.comments.get(&owner).cloned().unwrap_or_default();
+        let mut comments = comments.into_iter().peekable();
+        let mut push_comments =
+            |before: usize, declarations: &mut Vec<Node>, nested: &mut Vec<Node>| {
+                while let Some((comment, _)) = comments.next_if(|(_, span)| span.start < before) {
+                    match context {
+                        Context::Root => nested.push(Node::Comment(comment)),
+                        _ => declarations.push(Node::Comment(comment)),
+                    }
+                }
+            };
+
+        for (item, span) in &items.0 {
+            push_comments(span.start, declarations, nested);
+            match item {
+                Item::Declaration(declaration) => {
+                    self.declaration(declaration, *span, context, declarations)?
+                }
+                Item::QualifiedRule(rule) => self.qualified_rule(rule, *span, context, nested)?,
+                Item::AtRule(rule) => self.at_rule(rule, *span, context, nested)?,
+                Item::Call(call) => self.call(call, *span, context, declarations, nested)?,
+                Item::Extend(_) => return Err(EvalError::new(*span, "`:extend` is not supported")),
+            }
+        }
+        push_comments(usize::MAX, declarations, nested);
+        Ok(())
+    }
+
+    fn declaration(
+        &mut self,
+        declaration: &'a Declaration<&'src str>,
+        span: Span,
+        context: Context,
+        declarations: &mut Vec<Node>,
+    ) -> Result<()> {
+        let name = match &declaration.name {
+            // Variables are evaluated where they're used
+            DeclarationName::Variable(_) => return Ok(()),
+            DeclarationName::Ident(name) => name.to_string(),
+            DeclarationName::InterpolatedIdent(ident) => self.interpolate(&ident.0)?,
+        };
+        if let Context::Root = context {
+            return Err(EvalError::new(
+                span,
+                "properties must be inside selector blocks",
+            ));
+        }
+
+        let value = match &declaration.value {
+            ast::Value::ComponentValues(values) => self.output_value(&values.0)?,
+            ast::Value::DetachedRuleset(_) => {
+                return Err(EvalError::new(
+                    span,
+                    "detached rulesets can only be assigned to variables",
+                ))
+            }
+        };
+        declarations.push(Node::Declaration {
+            name,
+            value,
+            important: declaration.important,
+        });
+        Ok(())
+    }
+
+    // RULES
+
+    fn qualified_rule(
+        &mut self,
+        rule: &'a QualifiedRule<&'src str>,
+        span: Span,
+        context: Context,
+        output: &mut Vec<Node>,
+    ) -> Result<()> {
+        let parents = match context {
+            Context::Rule(selectors) => selectors,
+            _ => &[],
+        };
+
+        match rule {
+            QualifiedRule::Style(rule) => {
+                // A rule whose guard fails is left out (e.g. `.a when (@mode = dark) { ... }`)
+                if let Some(guard) = &rule.guard {
+                    if !self.guard(&guards::parse(&guard.0)?)? {
+                        return Ok(());
+                    }
+                }
+                let selectors = rule
+                    .selectors
+                    .0
+                    .iter()
+                    .map(|(selector, _)| self.selector(selector))
+                    .collect::<Result<_>>()?;
+                let selectors = selectors::resolve(parents, selectors);
+                self.block(&rule.block, Some(span), Context::Rule(&selectors), output)
+            }
+            // Mixin definitions are only output where they're called
+            QualifiedRule::Generic(rule) if mixins::definition(&rule.prelude.0).is_some() => Ok(()),
+            QualifiedRule::Mixin(_) => Ok(()),
+            QualifiedRule::Generic(rule) => {
+                // Keep selectors that the parser doesn't understand as-is, apart from their
+                // interpolations
+                let mut text = String::new();
+                self.interpolate_tokens(trim(&rule.prelude.0), &mut text)?;
+                let selector = Selector {
+                    components: vec![(SelectorComponent::Type(SelectorName::Ident(text)), span)],
+                    extends: Vec::new(),
+                };
+                let selectors = selectors::resolve(parents, vec![selector]);
+                self.block(&rule.block, Some(span), Context::Rule(&selectors), output)
+            }
+            QualifiedRule::Keyframe(rule) => {
+                let selectors = rule
+                    .selectors
+                    .iter()
+                    .map(|(selector, _)| match selector {
+                        KeyframeSelector::From => "from".to_string(),
+                        KeyframeSelector::To => "to".to_string(),
+                        KeyframeSelector::Percentage(percentage) => format!("{percentage}%"),
+                    })
+                    .collect();
+                let mut children = Vec::new();
+                self.block(
+                    &rule.block,
+                    Some(span),
+                    Context::Declarations,
+                    &mut children,
+                )?;
+                output.push(Node::Rule {
+                    selectors,
+                    children,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Evaluates the interpolations in a selector. The extends of the selector aren't supported.
+    fn selector(&mut self, selector: &Selector<&'src str>) -> Result<Selector<String>> {
+        if let Some((_, span)) = selector.extends.first() {
+            return Err(EvalError::new(*span, "`:extend` is not supported"));
+        }
+
+        let mut selector = selector.clone().map_strings(&mut String::from);
+        for (component, span) in &mut selector.components {
+            match component {
+                SelectorComponent::Type(name)
+                | SelectorComponent::Class(name)
+                | SelectorComponent::Id(name) => self.selector_name(name)?,
+                SelectorComponent::Attribute { name, matcher } => {
+                    self.selector_name(name)?;
+                    if let Some(matcher) = matcher {
+                        match &mut matcher.value {
+                            AttributeValue::Ident(name) => self.selector_name(name)?,
+                            AttributeValue::String(string) => {
+                                let text = self.interpolate(&string.0)?;
+                                string.0 = vec![(InterpolationPart::Literal(text), *span)];
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(selector)
+    }
+
+    fn selector_name(&mut self, name: &mut SelectorName<String>) -> Result<()> {
+        if let SelectorName::InterpolatedIdent(ident) = name {
+            *name = SelectorName::Ident(self.interpolate(&ident.0)?);
+        }
+        Ok(())
+    }
+
+    // AT-RULES
+
+    fn at_rule(
+        &mut self,
+        rule: &'a AtRule<&'src str>,
+        span: Span,
+        context: Context,
+        output: &mut Vec<Node>,
+    ) -> Result<()> {
+        let header = match rule {
+            AtRule::Generic(rule) => {
+                let prelude = self.output_value(&rule.prelude.0)?;
+                match prelude.is_empty() {
+                    true => format!("@{}", rule.name),
+                    false => format!("@{} {}", rule.name, prelude),
+                }
+            }
+            AtRule::Keyframes(rule) => {
+                let name = match &rule.name {
+                    KeyframesName::Ident(name) => name.to_string(),
+                    KeyframesName::String(name) => format!("\"{name}\""),
+                    KeyframesName::Variable(name) => self.variable_text(name, span)?,
+                    KeyframesName::InterpolatedIdent(ident) => self.interpolate(&ident.0)?,
+                };
+                let prefix = rule.vendor_prefix.unwrap_or("");
+                format!("@{prefix}keyframes {name}")
+            }
+            AtRule::Import(import) => {
+                let is_css = match &import.url.0 {
+                    UrlOrString::Url(_) => true,
+                    UrlOrString::String(string) => self.interpolate(&string.0)?.ends_with(".css"),
+                };
+                let is_css = is_css
+                    || import
+                        .options
+                        .iter()
+                        .any(|(option, _)| *option == ImportOption::Css);
+                if !is_css {
+                    return Err(EvalError::new(
+                        span,
+                        "importing LESS files is not supported",
+                    ));
+                }
+                // The import options are only meaningful to LESS
+                let mut import = import.clone();
+                import.options.clear();
+                at_rule_header(&AtRule::Import(import))
+            }
+            _ => at_rule_header(rule),
+        };
+
+        let block = match rule.block() {
+            None => None,
+            // Keyframes only contain keyframe rules
+            Some(block) if matches!(rule, AtRule::Keyframes(_)) => {
+                let mut nodes = Vec::new();
+                self.block(block, Some(span), Context::Root, &mut nodes)?;
+                Some(nodes)
+            }
+            // These only contain declarations
+            Some(block)
+                if matches!(
+                    rule,
+                    AtRule::FontFace(_)
+                        | AtRule::Page(_)
+                        | AtRule::PageMargin(_)
+                        | AtRule::Property(_)
+                ) =>
+            {
+                let mut nodes = Vec::new();
+                self.block(block, Some(span), Context::Declarations, &mut nodes)?;
+                Some(nodes)
+            }
+            // Other at-rules (e.g. `@media`) bubble up out of style rules, keeping their
+            // declarations in a rule with the selectors of the style rule
+            Some(block) => {
+                let mut nodes = Vec::new();
+                self.block(block, Some(span), context, &mut nodes)?;
+                if nodes.is_empty() {
+                    return Ok(());
+                }
+                Some(nodes)
+            }
+        };
+        output.push(Node::AtRule { header, block });
+        Ok(())
+    }
+
+    // CALLS
+
+    fn call(
+        &mut self,
+        call: &'a Call<&'src str>,
+        span: Span,
+        context: Context,
+        declarations: &mut Vec<Node>,
+        nested: &mut Vec<Node>,
+    ) -> Result<()> {
+        match call {
+            Call::Variable(call) => {
+                let variable = self.scopes.lookup(call.name, span)?;
+                let Definition::Declared(ast::Value::DetachedRuleset(items)) = variable.value
+                else {
+                    return Err(EvalError::new(
+                        span,
+                        format!("@{} is not a detached ruleset", call.name),
+                    ));
+                };
+                self.scopes.push(items);
+                let result = self.items(items, Some(variable.span), context, declarations, nested);
+                self.scopes.pop();
+                result
+            }
+            Call::Mixin(call) => self.mixin_call(call, span, context, declarations, nested),
+            Call::Function(call) if call.name.eq_ignore_ascii_case("each") => {
+                self.each(&call.arguments, span, context, declarations, nested)
+            }
+            Call::Function(call) => Err(EvalError::new(
+                span,
+                format!("`{}()` can't be called here", call.name),
+            )),
+        }
+    }
+
+    /// Expands the definitions of a mixin that match the arguments of a call.
+    fn mixin_call(
+        &mut self,
+        call: &'a MixinCall<&'src str>,
+        span: Span,
+        context: Context,
+        declarations: &mut Vec<Node>,
+        nested: &mut Vec<Node>,
+    ) -> Result<()> {
+        let path = mixins::path(&call.selector.0);
+        let mixins = mixins::find(self.scopes.blocks(), &path);
+        if mixins.is_empty() {
+            let selector = &self.source[span_of(&call.selector.0).into_range()];
+            return Err(EvalError::new(
+                span,
+                format!("mixin `{selector}` is undefined"),
+            ));
+        }
+
+        let mut arguments = Vec::new();
+        for (argument, span) in &call.arguments {
+            let value = match &argument.value {
+                ast::Value::ComponentValues(values) => {
+                    Definition::Evaluated(self.value(&values.0)?)
+                }
+                value @ ast::Value::DetachedRuleset(_) => Definition::Declared(value),
+            };
+            arguments.push(Argument {
+                name: argument.name,
+                value,
+                span: *span,
+            });
+        }
+
+        // Like in Less.js, guards are evaluated with `default()` both false and true. Definitions
+        // whose guards pass either way are expanded, and the others are only expanded if their
+        // guard passes with `default()` true if there are no such definitions, and false otherwise
+        let mut is_matched = false;
+        let mut candidates = Vec::new();
+        for mixin in &mixins {
+            let is_recursive = mixin.parameters.is_none() && self.expanding.contains(&mixin.span);
+            if is_recursive || !self.matches(mixin, &arguments)? {
+                continue;
+            }
+            is_matched = true;
+            let (if_false, if_true) = match mixin.guard {
+                [] => (true, true),
+                guard => {
+                    let guard = guards::parse(guard)?;
+                    let if_false = self.mixin_guard(mixin, &guard, &arguments, span, false)?;
+                    let if_true = self.mixin_guard(mixin, &guard, &arguments, span, true)?;
+                    (if_false, if_true)
+                }
+            };
+            if if_false || if_true {
+                candidates.push((mixin, if_false, if_true));
+            }
+        }
+
+        let call_text = self.source[span.into_range()]
+            .trim_end_matches(';')
+            .trim_end();
+        if !is_matched {
+            let candidates: Vec<_> = mixins
+                .iter()
+                .map(|mixin| format!("`{}`", &self.source[mixin.selector.into_range()]))
+                .collect();
+            let message = format!(
+                "no matching definition was found for `{call_text}`; candidates: {}",
+                candidates.join(", ")
+            );
+            return Err(EvalError::new(span, message));
+        }
+        let default = !candidates
+            .iter()
+            .any(|(_, if_false, if_true)| *if_false && *if_true);
+        if default && candidates.len() > 1 {
+            let message =
+                format!("ambiguous use of `default()` found when matching for `{call_text}`");
+            return Err(EvalError::new(span, message));
+        }
+
+        for (mixin, if_false, if_true) in candidates {
+            if !(if default { if_true } else { if_false }) {
+                continue;
+            }
+            let mut mixin_declarations = Vec::new();
+            let mut mixin_nested = Vec::new();
+            self.expand(
+                mixin,
+                &arguments,
+                span,
+                context,
+                &mut mixin_declarations,
+                &mut mixin_nested,
+            )?;
+            if call.important {
+                make_important(&mut mixin_declarations);
+                make_important(&mut mixin_nested);
+            }
+            declarations.extend(mixin_declarations);
+            nested.extend(mixin_nested);
+        }
+        Ok(())
+    }
+
+    /// Evaluates the guard of a mixin definition with the arguments of a call, in a scope that is
+    /// inside the namespaces of the mixin (like the block of the mixin, but without its variables).
+    fn mixin_guard(
+        &mut self,
+        mixin: &Mixin<'a, 'src>,
+        guard: &Condition<'a, 'src>,
+        arguments: &[Argument<'a, 'src>],
+        span: Span,
+        default: bool,
+    ) -> Result<bool> {
+        for namespace in &mixin.namespaces {
+            self.scopes.push(namespace);
+        }
+        self.scopes.push_bindings();
+        let previous = self.default.replace(default);
+        let result = self
+            .bind_arguments(mixin, arguments, span)
+            .and_then(|()| self.guard(guard));
+        self.default = previous;
+        self.scopes.pop();
+        for _ in &mixin.namespaces {
+            self.scopes.pop();
+        }
+        result
+    }
+
+    /// Returns whether a guard passes.
+    fn guard(&mut self, condition: &Condition<'a, 'src>) -> Result<bool> {
+        Ok(match condition {
+            Condition::Or(conditions) => {
+                for condition in conditions {
+                    if self.guard(condition)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Condition::And(conditions) => {
+                for condition in conditions {
+                    if !self.guard(condition)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            Condition::Not(condition) => !self.guard(condition)?,
+            Condition::Comparison(left, comparison, right) => {
+                let (left, right) = (self.value(left)?, self.value(right)?);
+                comparison.holds(guards::compare(&left, &right))
+            }
+            Condition::Value(value) => self.value(value)?.to_string() == "true",
+        })
+    }
+
+    /// Returns whether the arguments of a call match the parameters of a mixin: their number, and
+    /// the values of the parameters that are patterns (e.g. `dark` in `.theme(dark; @color)`).
+    fn matches(&mut self, mixin: &Mixin<'a, 'src>, arguments: &[Argument]) -> Result<bool> {
+        let Some(parameters) = &mixin.parameters else {
+            return Ok(arguments.is_empty());
+        };
+
+        // Like in Less.js, arguments that are named after optional parameters aren't counted
+        let is_optional = |name: &str| {
+            parameters.iter().any(|parameter| {
+                matches!(parameter, Parameter::Variable(parameter, Some(_)) if *parameter == name)
+            })
+        };
+        let required_arguments = arguments
+            .iter()
+            .filter(|argument| !argument.name.is_some_and(is_optional))
+            .count();
+        let required = parameters
+            .iter()
+            .filter(|parameter| !matches!(parameter, Parameter::Variable(_, Some(_))))
+            .count();
+        let is_variadic = parameters
+            .iter()
+            .any(|parameter| matches!(parameter, Parameter::Rest(_)));
+        let is_arity_matched = match is_variadic {
+            true => required_arguments + 1 >= required,
+            false => required_arguments >= required && arguments.len() <= parameters.len(),
+        };
+        if !is_arity_matched {
+            return Ok(false);
+        }
+
+        // Like in `bind_arguments`, the positional arguments are matched in order with the
+        // parameters that aren't bound by name
+        let is_named = |name: &str| arguments.iter().any(|argument| argument.name == Some(name));
+        let mut positional = arguments.iter().filter(|argument| argument.name.is_none());
+        for parameter in parameters {
+            match parameter {
+                Parameter::Variable(name, _) if is_named(name) => {}
+                Parameter::Variable(..) => {
+                    positional.next();
+                }
+                Parameter::Pattern(pattern) => {
+                    let Some(Argument {
+                        value: Definition::Evaluated(value),
+                        ..
+                    }) = positional.next()
+                    else {
+                        return Ok(false);
+                    };
+                    if self.value(pattern)?.to_string() != value.to_string() {
+                        return Ok(false);
+                    }
+                }
+                Parameter::Rest(_) => break,
+            }
+        }
+        Ok(true)
+    }
+
+    /// Evaluates the block of a mixin with the arguments of a call, in a scope that is inside the
+    /// namespaces of the mixin. The variables of the mixin are then defined for the caller too.
+    fn expand(
+        &mut self,
+        mixin: &Mixin<'a, 'src>,
+        arguments: &[Argument<'a, 'src>],
+        span: Span,
+        context: Context,
+        declarations: &mut Vec<Node>,
+        nested: &mut Vec<Node>,
+    ) -> Result<()> {
+        if self.expanding.len() >= MAX_MIXIN_DEPTH {
+            let message = format!(
+                "mixins are nested more than {MAX_MIXIN_DEPTH} levels deep, which is probably \
+                 infinite recursion"
+            );
+            return Err(EvalError::new(span, message));
+        }
+
+        for namespace in &mixin.namespaces {
+            self.scopes.push(namespace);
+        }
+        self.scopes.push_bindings();
+        self.expanding.push(mixin.span);
+        let result = self.bind_arguments(mixin, arguments, span).and_then(|()| {
+            self.scopes.push(mixin.block);
+            let result = self.items(mixin.block, Some(mixin.span), context, declarations, nested);
+            let mut variables = Vec::new();
+            for (item, span) in &mixin.block.0 {
+                if let Item::Declaration(Declaration {
+                    name: DeclarationName::Variable(name),
+                    value: ast::Value::ComponentValues(_),
+                    ..
+                }) = item
+                {
+                    // Variables that can't be evaluated are left out, as the caller may not use
+                    // them
+                    if let Ok(value) = self.variable(name, *span) {
+                        variables.push((*name, value, *span));
+                    }
+                }
+            }
+            self.scopes.pop();
+            result.map(|()| variables)
+        });
+        self.expanding.pop();
+        self.scopes.pop();
+        for _ in &mixin.namespaces {
+            self.scopes.pop();
+        }
+
+        for (name, value, span) in result? {
+            self.scopes.export(name, value, span);
+        }
+        Ok(())
+    }
+
+    /// Binds the arguments of a call to the parameters of a mixin in the innermost scope, along
+    /// with `@arguments`, which is all of them. Named arguments are bound first, and the others
+    /// in order.
+    fn bind_arguments(
+        &mut self,
+        mixin: &Mixin<'a, 'src>,
+        arguments: &[Argument<'a, 'src>],
+        span: Span,
+    ) -> Result<()> {
+        let parameters = mixin.parameters.as_deref().unwrap_or_default();
+        let mut named = vec![None; parameters.len()];
+        let mut positional = Vec::new();
+        for argument in arguments {
+            let Some(name) = argument.name else {
+                positional.push(argument);
+                continue;
+            };
+            let index = parameters.iter().position(|parameter| {
+                matches!(parameter, Parameter::Variable(parameter, _) if *parameter == name)
+            });
+            let index = index.ok_or_else(|| {
+                EvalError::new(argument.span, format!("named argument @{name} not found"))
+            })?;
+            named[index] = Some(argument.value.clone());
+        }
+
+        let evaluated = |argument: &Argument<'a, 'src>| match &argument.value {
+            Definition::Evaluated(value) => Some(value.clone()),
+            Definition::Declared(_) => None,
+        };
+        let mut positional = positional.into_iter();
+        let mut values = Vec::new();
+        for (parameter, named) in parameters.iter().zip(named) {
+            match parameter {
+                Parameter::Variable(name, default) => {
+                    let value =
+                        named.or_else(|| positional.next().map(|argument| argument.value.clone()));
+                    let value = match (value, default) {
+                        (Some(value), _) => value,
+                        (None, Some(default)) => Definition::Evaluated(self.value(default)?),
+                        (None, None) => {
+                            let selector = &self.source[mixin.selector.into_range()];
+                            return Err(EvalError::new(
+                                span,
+                                format!("wrong number of arguments for `{selector}`"),
+                            ));
+                        }
+                    };
+                    if let Definition::Evaluated(value) = &value {
+                        values.push(value.clone());
+                    }
+                    self.scopes.bind(name, value, span);
+                }
+                Parameter::Pattern(_) => values.extend(positional.next().and_then(evaluated)),
+                Parameter::Rest(name) => {
+                    let rest: Vec<_> = positional.by_ref().filter_map(evaluated).collect();
+                    values.extend(rest.iter().cloned());
+                    if let Some(name) = name {
+                        let rest = Value::List(rest, Separator::Space);
+                        self.scopes.bind(name, Definition::Evaluated(rest), span);
+                    }
+                }
+            }
+        }
+        let arguments = Value::List(values, Separator::Space);
+        self.scopes
+            .bind("arguments", Definition::Evaluated(arguments), span);
+        Ok(())
+    }
+
+    /// Evaluates `each(list, ruleset)`, which evaluates a detached ruleset like `& { ... }` for
+    /// each value of a list, or each declaration of a detached ruleset, with `@value`, `@key` and
+    /// `@index` (from 1) bound to it.
+    fn each(
+        &mut self,
+        arguments: &'a [Spanned<ast::Value<&'src str>>],
+        span: Span,
+        context: Context,
+        declarations: &mut Vec<Node>,
+        nested: &mut Vec<Node>,
+    ) -> Result<()> {
+        let [list, ruleset] = arguments else {
+            return Err(EvalError::new(
+                span,
+                "`each()` takes a list and a detached ruleset",
+            ));
+        };
+        let Some((ruleset, owner)) = self.detached_ruleset(ruleset)? else {
+            return Err(EvalError::new(ruleset.1, "expected a detached ruleset"));
+        };
+
+        let entries = match (self.detached_ruleset(list)?, &list.0) {
+            (Some((map, _)), _) => self.map_entries(map)?,
+            (None, ast::Value::DetachedRuleset(_)) => unreachable!(),
+            (None, ast::Value::ComponentValues(list)) => {
+                let values = match self.value(&list.0)? {
+                    Value::List(values, Separator::Comma | Separator::Space) => values,
+                    value => vec![value],
+                };
+                values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, value)| (Value::Number((i + 1) as f64, Unit::default()), value))
+                    .collect()
+            }
+        };
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            self.scopes.push(ruleset);
+            let index = Value::Number((i + 1) as f64, Unit::default());
+            for (name, value) in [("value", value), ("key", key), ("index", index)] {
+                self.scopes.bind(name, Definition::Evaluated(value), span);
+            }
+            let result = self.items(ruleset, Some(owner), context, declarations, nested);
+            self.scopes.pop();
+            result?;
+        }
+        Ok(())
+    }
+
+    /// Returns the detached ruleset that an argument is (a block or a variable), if any, with the
+    /// span that owns its comments.
+    fn detached_ruleset(
+        &mut self,
+        (value, span): &'a Spanned<ast::Value<&'src str>>,
+    ) -> Result<Option<(&'a ListOfItems<&'src str>, Span)>> {
+        let values = match value {
+            ast::Value::DetachedRuleset(items) => return Ok(Some((items, *span))),
+            ast::Value::ComponentValues(values) => &values.0,
+        };
+        Ok(match trim(values) {
+            [(TokenTree::Token(Token::Symbol('@')), start), (TokenTree::Token(Token::Ident(name)), end)] =>
+            {
+                let variable = self.scopes.lookup(name, Span::new(start.start, end.end))?;
+                match variable.value {
+                    Definition::Declared(ast::Value::DetachedRuleset(items)) => {
+                        Some((items, variable.span))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+    }
+
+    /// Evaluates the declarations of a detached ruleset that is used as a map, returning their
+    /// names (with `@` for variables) and values.
+    fn map_entries(&mut self, map: &'a ListOfItems<&'src str>) -> Result<Vec<(Value, Value)>> {
+        self.scopes.push(map);
+        let mut entries = Vec::new();
+        let mut result = Ok(());
+        for (item, _) in &map.0 {
+            let Item::Declaration(Declaration {
+                name,
+                value: ast::Value::ComponentValues(values),
+                ..
+            }) = item
+            else {
+                continue;
+            };
+            let entry = match name {
+                DeclarationName::Variable(name) => Ok(format!("@{name}")),
+                DeclarationName::Ident(name) => Ok(name.to_string()),
+                DeclarationName::InterpolatedIdent(ident) => self.interpolate(&ident.0),
+            }
+            .and_then(|key| Ok((Value::Keyword(key), self.value(&values.0)?)));
+            match entry {
+                Ok(entry) => entries.push(entry),
+                Err(error) => {
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+        self.scopes.pop();
+        result.map(|()| entries)
+    }
+
+    // VARIABLES
+
+    /// Evaluates a variable in the scope it's defined in.
+    fn variable(&mut self, name: &str, span: Span) -> Result<Value> {
+        let variable = self.scopes.lookup(name, span)?;
+        let values = match &variable.value {
+            Definition::Declared(ast::Value::ComponentValues(values)) => values,
+            Definition::Declared(ast::Value::DetachedRuleset(_)) => {
+                return Err(EvalError::new(
+                    span,
+                    format!("@{name} is a detached ruleset, which can only be called"),
+                ))
+            }
+            Definition::Evaluated(value) => return Ok(value.clone()),
+        };
+
+        let inner_scopes = self.scopes.enter(&variable, span)?;
+        let result = self.value(&values.0);
+        self.scopes.exit(inner_scopes);
+        result
+    }
+
+    /// Evaluates lookups in a detached ruleset (e.g. `@config[key]`), where each lookup after the
+    /// first is in the detached ruleset found by the previous one (e.g. `@config[@dark][primary]`).
+    fn lookup(
+        &mut self,
+        name: &str,
+        span: Span,
+        lookups: &[Spanned<TokenTree<&'src str>>],
+    ) -> Result<Value> {
+        // Variable variables are named in the scope of the lookup, before entering the ruleset's
+        let lookups = lookups
+            .iter()
+            .map(|(tt, span)| Ok((self.lookup_key(tt, *span)?, *span)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let variable = self.scopes.lookup(name, span)?;
+        let Definition::Declared(ast::Value::DetachedRuleset(ruleset)) = variable.value else {
+            return Err(EvalError::new(
+                span,
+                format!("cannot look up in @{name}, which is not a detached ruleset"),
+            ));
+        };
+        let inner_scopes = self.scopes.enter(&variable, span)?;
+        let result = self.lookup_in(ruleset, &lookups);
+        self.scopes.exit(inner_scopes);
+        result
+    }
+
+    /// Evaluates the first lookup in a detached ruleset, and the rest in the detached ruleset it
+    /// finds.
+    fn lookup_in(
+        &mut self,
+        ruleset: &'a ListOfItems<&'src str>,
+        lookups: &[Spanned<Lookup<'src>>],
+    ) -> Result<Value> {
+        let [(lookup, span), rest @ ..] = lookups else {
+            unreachable!("expected a lookup")
+        };
+        self.scopes.push(ruleset);
+        let result = match (lookup.find(ruleset), rest) {
+            (None, _) => Err(EvalError::new(*span, format!("{lookup} is undefined"))),
+            (Some(ast::Value::DetachedRuleset(items)), [_, ..]) => self.lookup_in(items, rest),
+            (Some(ast::Value::DetachedRuleset(_)), []) => Err(EvalError::new(
+                *span,
+                format!("{lookup} is a detached ruleset, which can only be called"),
+            )),
+            (Some(ast::Value::ComponentValues(_)), [_, ..]) => Err(EvalError::new(
+                *span,
+                format!("cannot look up in {lookup}, which is not a detached ruleset"),
+            )),
+            (Some(ast::Value::ComponentValues(values)), []) => self.value(&values.0),
+        };
+        self.scopes.pop();
+        result
+    }
+
+    /// Returns what the brackets of a lookup look up: the last declaration (`[]`), a property
+    /// (`[key]` or `[$key]`) or a variable (`[@key]`, or `[@@name]` for the variable named by the
+    /// value of `@name`).
+    fn lookup_key(&mut self, tt: &TokenTree<&'src str>, span: Span) -> Result<Lookup<'src>> {
+        let TokenTree::Tree(Delim::Bracket, tts) = tt else {
+            unreachable!("expected a lookup in brackets")
+        };
+        Ok(match trim(tts) {
+            [] => Lookup::Last,
+            [(TokenTree::Token(Token::Ident(name)), _)]
+            | [(TokenTree::Token(Token::Symbol('$')), _), (TokenTree::Token(Token::Ident(name)), _)] => {
+                Lookup::Property(name)
+            }
+            [(TokenTree::Token(Token::Symbol('@')), _), (TokenTree::Token(Token::Ident(name)), _)] => {
+                Lookup::Variable(name.to_string())
+            }
+            [(TokenTree::Token(Token::Symbol('@')), start), (TokenTree::Token(Token::Symbol('@')), _), (TokenTree::Token(Token::Ident(name)), end)] =>
+            {
+                let span = Span::new(start.start + 1, end.end);
+                Lookup::Variable(self.variable_text(name, span)?)
+            }
+            _ => return Err(EvalError::new(span, "invalid lookup")),
+        })
+    }
+
+    /// Evaluates a variable as text, without quotes (e.g. for interpolation).
+    fn variable_text(&mut self, name: &str, span: Span) -> Result<String> {
+        Ok(match self.variable(name, span)? {
+            Value::Quoted(text, _) => text,
+            value => value.to_string(),
+        })
+    }
+
+    fn interpolate<S: AsRef<str>>(
+        &mut self,
+        parts: &[Spanned<InterpolationPart<S>>],
+    ) -> Result<String> {
+        let mut text = String::new();
+        for (part, span) in parts {
+            match part {
+                InterpolationPart::Literal(literal) => text.push_str(literal.as_ref()),
+                InterpolationPart::Variable(name) => {
+                    text.push_str(&self.variable_text(name.as_ref(), *span)?)
+                }
+            }
+        }
+        Ok(text)
+    }
+
+    /// Replaces the interpolations (e.g. `@{name}`) in the contents of a string.
+    fn interpolate_string(&mut self, string: &str, span: Span) -> Result<String> {
+        let mut text = String::new();
+        let mut rest = string;
+        while let Some(start) = rest.find("@{") {
+            let name_len = rest[start + 2..]
+                .find(|c| !is_name(c))
+                .unwrap_or(rest.len() - start - 2);
+            let name = &rest[start + 2..start + 2 + name_len];
+            if name.is_empty() || !rest[start + 2 + name_len..].starts_with('}') {
+                text.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                continue;
+            }
+            text.push_str(&rest[..start]);
+            text.push_str(&self.variable_text(name, span)?);
+            rest = &rest[start + 3 + name_len..];
+        }
+        text.push_str(rest);
+        Ok(text)
+    }
+
+    /// Pushes the source text of tokens to `text`, with their interpolations (e.g. `@{name}`)
+    /// replaced.
+    fn interpolate_tokens(
+        &mut self,
+        tts: &[Spanned<TokenTree<&'src str>>],
+        text: &mut String,
+    ) -> Result<()> {
+        for (tt, span) in tts {
+            match tt {
+                TokenTree::Token(Token::Interpolation(name)) => {
+                    text.push_str(&self.variable_text(name, *span)?)
+                }
+                TokenTree::Tree(delim, tts) => {
+                    text.push(delim.open());
+                    self.interpolate_tokens(tts, text)?;
+                    text.push(delim.close());
+                }
+                _ => text.push_str(&self.source[span.into_range()]),
+            }
+        }
+        Ok(())
+    }
+
+    // VALUES
+
+    /// Evaluates a value, which may be a comma or space separated list.
+    fn value(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) -> Result<Value> {
+        let mut values = trim(tts)
+            .split(|(tt, _)| is_symbol(tt, ','))
+            .map(|tts| self.space_list(trim(tts)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(match values.len() {
+            1 => values.pop().unwrap(),
+            _ => Value::List(values, Separator::Comma),
+        })
+    }
+
+    /// Evaluates the comma separated arguments of a function call.
+    fn arguments(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) -> Result<Vec<Value>> {
+        match trim(tts) {
+            [] => Ok(Vec::new()),
+            tts => tts
+                .split(|(tt, _)| is_symbol(tt, ','))
+                .map(|tts| self.space_list(trim(tts)))
+                .collect(),
+        }
+    }
+
+    /// Evaluates a value that is output, checking its units if `strict_units` is set.
+    fn output_value(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) -> Result<String> {
+        let mut value = self.value(tts)?;
+        if self.options.strict_units {
+            operations::check_units(&mut value)
+                .map_err(|message| EvalError::new(span_of(trim(tts)), message))?;
+        }
+        Ok(match self.options.compress {
+            true => format!("{value:#}"),
+            false => value.to_string(),
+        })
+    }
+
+    fn space_list(&mut self, mut tts: &[Spanned<TokenTree<&'src str>>]) -> Result<Value> {
+        let mut values = Vec::new();
+        while !tts.is_empty() {
+            let (value, len) = self.expression(tts)?;
+            values.push(value);
+            tts = trim(&tts[len..]);
+        }
+        Ok(match values.len() {
+            1 => values.pop().unwrap(),
+            _ => Value::List(values, Separator::Space),
+        })
+    }
+
+    /// Evaluates the expression at the start of the tokens (e.g. `@a + 2 * @b`), returning it with
+    /// the number of token trees it spans.
+    fn expression(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) -> Result<(Value, usize)> {
+        let (first, mut len) = self.operand(tts)?;
+        let mut operands = vec![(first, span_of(&tts[..len]))];
+        let mut operators = Vec::new();
+        while let Some((operator, operator_len)) = self::operator(&tts[len..]) {
+            let start = len + operator_len;
+            if start == tts.len() {
+                break;
+            }
+            let (operand, operand_len) = self.operand(&tts[start..])?;
+            operators.push(operator);
+            operands.push((operand, span_of(&tts[start..start + operand_len])));
+            len = start + operand_len;
+        }
+
+        // Multiplication and division are evaluated before addition and subtraction
+        let mut operands = operands.into_iter();
+        let mut terms = vec![operands.next().unwrap()];
+        let mut additions = Vec::new();
+        for (operator, right) in operators.into_iter().zip(operands) {
+            match operator.operator {
+                Operator::Multiply | Operator::Divide => {
+                    let left = terms.pop().unwrap();
+                    terms.push(self.operation(operator, left, right)?);
+                }
+                Operator::Add | Operator::Subtract => {
+                    additions.push(operator);
+                    terms.push(right);
+                }
+            }
+        }
+        let mut terms = terms.into_iter();
+        let first = terms.next().unwrap();
+        let (value, _) = additions
+            .into_iter()
+            .zip(terms)
+            .try_fold(first, |left, (operator, right)| {
+                self.operation(operator, left, right)
+            })?;
+        Ok((value, len))
+    }
+
+    /// Evaluates an operation if the math mode allows it, or keeps it to be output as-is.
+    fn operation(
+        &mut self,
+        operator: SpannedOperator,
+        (left, left_span): Spanned<Value>,
+        (right, right_span): Spanned<Value>,
+    ) -> Result<Spanned<Value>> {
+        let span = Span::new(left_span.start, right_span.end);
+        let is_evaluated = !self.in_calc
+            && (operator.forced
+                || match self.options.math {
+                    Math::Always => true,
+                    Math::ParensDivision => {
+                        operator.operator != Operator::Divide || self.parens > 0
+                    }
+                    Math::Parens => self.parens > 0,
+                });
+        if !is_evaluated {
+            let operation = Value::Operation(
+                operator.operator,
+                Box::new(left),
+                Box::new(right),
+                operator.spaced,
+            );
+            return Ok((operation, span));
+        }
+        let value = operations::operate(operator.operator, left, right, self.options.strict_units)
+            .map_err(|message| EvalError::new(span, message))?;
+        Ok((value, span))
+    }
+
+    /// Evaluates an operand of an expression: values that aren't separated by whitespace or
+    /// operators (e.g. `12px` or `a:b`), or a negated value (e.g. `-@a`).
+    fn operand(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) -> Result<(Value, usize)> {
+        if is_symbol(&tts[0].0, '-') && tts.get(1).is_some_and(|tt| !is_junk(tt)) {
+            let (value, len) = self.operand(&tts[1..])?;
+            let value = match value {
+                Value::Number(number, unit) => Value::Number(-number, unit),
+                value => Value::List(vec![Value::Symbol('-'), value], Separator::None),
+            };
+            return Ok((value, len + 1));
+        }
+
+        let mut values = Vec::new();
+        let mut len = 0;
+        while len < tts.len()
+            && !is_junk(&tts[len])
+            && (len == 0 || self::operator(&tts[len..]).is_none())
+        {
+            let (value, value_len) = self.single_value(&tts[len..])?;
+            values.push(value);
+            len += value_len;
+        }
+        let value = match values.len() {
+            1 => values.pop().unwrap(),
+            _ => Value::List(values, Separator::None),
+        };
+        Ok((value, len))
+    }
+
+    /// Evaluates `isdefined()` or `isruleset()`, which check a variable without evaluating it.
+    fn check_variable(
+        &mut self,
+        function: &str,
+        arguments: &[Spanned<TokenTree<&'src str>>],
+    ) -> Value {
+        let variable = match trim(arguments) {
+            [(TokenTree::Token(Token::Symbol('@')), start), (TokenTree::Token(Token::Ident(name)), end)] => {
+                Some(self.scopes.lookup(name, Span::new(start.start, end.end)))
+            }
+            _ => None,
+        };
+        let result = match (function, variable) {
+            ("isdefined", Some(variable)) => variable.is_ok(),
+            // Like in Less.js, other arguments are defined if they can be evaluated
+            ("isdefined", None) => self.value(arguments).is_ok(),
+            (_, Some(Ok(variable))) => matches!(
+                variable.value,
+                Definition::Declared(ast::Value::DetachedRuleset(_))
+            ),
+            (_, _) => false,
+        };
+        Value::Keyword(result.to_string())
+    }
+
+    /// Evaluates the value at the start of the tokens, returning it with the number of token trees
+    /// it spans.
+    fn single_value(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) -> Result<(Value, usize)> {
+        let (tt, span) = &tts[0];
+        let next = tts.get(1).map(|(tt, _)| tt);
+        Ok(match (tt, next) {
+            (
+                TokenTree::Token(Token::Number(number)),
+                Some(TokenTree::Token(Token::Ident(unit))),
+            ) => (Value::Number(to_f64(number), Unit::new(unit)), 2),
+            (
+                TokenTree::Token(Token::Number(number)),
+                Some(TokenTree::Token(Token::Symbol('%'))),
+            ) => (Value::Number(to_f64(number), Unit::new("%")), 2),
+            (TokenTree::Token(Token::Number(number)), _) => {
+                (Value::Number(to_f64(number), Unit::default()), 1)
+            }
+            (
+                TokenTree::Token(Token::Ident(name)),
+                Some(TokenTree::Tree(Delim::Paren, arguments)),
+            ) => {
+                let span = Span::new(span.start, tts[1].1.end);
+                (self.function_call(name, arguments, span)?, 2)
+            }
+            // The `%()` format function
+            (
+                TokenTree::Token(Token::Symbol('%')),
+                Some(TokenTree::Tree(Delim::Paren, arguments)),
+            ) => {
+                let span = Span::new(span.start, tts[1].1.end);
+                (self.function_call("%", arguments, span)?, 2)
+            }
+            // A variable variable (e.g. `@@name`), which is named by the value of another variable
+            (TokenTree::Token(Token::Symbol('@')), Some(TokenTree::Token(Token::Symbol('@'))))
+                if matches!(tts.get(2), Some((TokenTree::Token(Token::Ident(_)), _))) =>
+            {
+                let TokenTree::Token(Token::Ident(name)) = &tts[2].0 else {
+                    unreachable!()
+                };
+                let span = Span::new(span.start, tts[2].1.end);
+                let name = self.variable_text(name, Span::new(span.start + 1, span.end))?;
+                (self.variable(&name, span)?, 3)
+            }
+            (TokenTree::Token(Token::Symbol('@')), Some(TokenTree::Token(Token::Ident(name)))) => {
+                let variable_span = Span::new(span.start, tts[1].1.end);
+                let lookups = tts[2..]
+                    .iter()
+                    .take_while(|(tt, _)| matches!(tt, TokenTree::Tree(Delim::Bracket, _)))
+                    .count();
+                match lookups {
+                    0 => (self.variable(name, variable_span)?, 2),
+                    _ => {
+                        let lookups = &tts[2..2 + lookups];
+                        (
+                            self.lookup(name, variable_span, lookups)?,
+                            2 + lookups.len(),
+                        )
+                    }
+                }
+            }
+            // An escaped string (e.g. `~"calc(100% - 10px)"`) is output without quotes
+            (
+                TokenTree::Token(Token::Symbol('~')),
+                Some(TokenTree::Token(Token::String(string))),
+            ) => {
+                let span = tts[1].1;
+                (Value::Keyword(self.interpolate_string(string, span)?), 2)
+            }
+            (TokenTree::Token(token), _) => {
+                let value = match token {
+                    Token::Ident(ident) => match Color::from_keyword(ident) {
+                        Some(color) => Value::Color(color),
+                        None => Value::Keyword(ident.to_string()),
+                    },
+                    Token::Hash(hash) => match Color::from_hex(hash) {
+                        Some(color) => Value::Color(color),
+                        None => Value::Keyword(format!("#{hash}")),
+                    },
+                    Token::String(string) => {
+                        let quote = self.source[span.start..].chars().next().unwrap_or('"');
+                        Value::Quoted(self.interpolate_string(string, *span)?, quote)
+                    }
+                    Token::Url(url) => Value::Url(self.interpolate_string(url, *span)?),
+                    Token::Interpolation(name) => Value::Keyword(self.variable_text(name, *span)?),
+                    Token::Symbol(symbol) => Value::Symbol(*symbol),
+                    Token::Number(_) | Token::Whitespace | Token::Comment(_) => unreachable!(),
+                };
+                (value, 1)
+            }
+            (TokenTree::Tree(Delim::Brace, _), _) => {
+                return Err(EvalError::new(*span, "unexpected block"))
+            }
+            (TokenTree::Tree(Delim::Paren, tts), _) => {
+                self.parens += 1;
+                let value = self.value(tts);
+                self.parens -= 1;
+                // Parentheses around an operation are dropped once it's evaluated
+                match value? {
+                    value @ (Value::Number(..) | Value::Color(_)) => (value, 1),
+                    value => (Value::Block(Delim::Paren, Box::new(value)), 1),
+                }
+            }
+            (TokenTree::Tree(delim, tts), _) => {
+                (Value::Block(*delim, Box::new(self.value(tts)?)), 1)
+            }
+        })
+    }
+
+    /// Evaluates a call of a built-in function, or keeps it to be output as-is if there's no such
+    /// function.
+    fn function_call(
+        &mut self,
+        name: &str,
+        arguments: &[Spanned<TokenTree<&'src str>>],
+        span: Span,
+    ) -> Result<Value> {
+        let function = name.to_ascii_lowercase();
+        if function == "isdefined" || function == "isruleset" {
+            return Ok(self.check_variable(&function, arguments));
+        }
+        if let ("default", Some(default)) = (function.as_str(), self.default) {
+            return Ok(Value::Keyword(default.to_string()));
+        }
+
+        // Operations in `calc()` are left to the browser
+        let in_calc = self.in_calc;
+        self.in_calc |= function == "calc";
+        let arguments = self.arguments(arguments);
+        self.in_calc = in_calc;
+        let mut arguments = arguments?;
+        let value = functions::call(&function, &arguments)
+            .map_err(|message| EvalError::new(span, message))?;
+        Ok(value.unwrap_or_else(|| {
+            let arguments = match arguments.len() {
+                1 => arguments.pop().unwrap(),
+                _ => Value::List(arguments, Separator::Comma),
+            };
+            Value::Call(name.to_string(), Box::new(arguments))
+        }))
+    }
+}
+
+/// Makes the declarations of nodes `!important`, like those of a mixin called with `!important`.
+fn make_important(nodes: &mut [Node]) {
+    for node in nodes {
+        match node {
+            Node::Declaration { important, .. } => *important = true,
+            Node::Rule { children, .. } => make_important(children),
+            Node::AtRule {
+                block: Some(block), ..
+            } => make_important(block),
+            Node::AtRule { block: None, .. } | Node::Comment(_) => {}
+        }
+    }
+}
+
+/// What a lookup in a detached ruleset looks up (e.g. `key` in `@config[key]`).
+enum Lookup<'src> {
+    /// The value of the last declaration (`[]`)
+    Last,
+    Property(&'src str),
+    Variable(String),
+}
+
+impl Lookup<'_> {
+    /// Returns the value of the last declaration in the ruleset that the lookup matches.
+    fn find<'a, 'src>(
+        &self,
+        ruleset: &'a ListOfItems<&'src str>,
+    ) -> Option<&'a ast::Value<&'src str>> {
+        ruleset.0.iter().rev().find_map(|(item, _)| match item {
+            Item::Declaration(Declaration { name, value, .. }) => match (self, name) {
+                (Lookup::Last, _) => Some(value),
+                (Lookup::Property(key), DeclarationName::Ident(name)) if key == name => Some(value),
+                (Lookup::Variable(key), DeclarationName::Variable(name)) if key == name => {
+                    Some(value)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for Lookup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lookup::Last => f.write_str("the last declaration"),
+            Lookup::Property(name) => write!(f, "property `{name}`"),
+            Lookup::Variable(name) => write!(f, "variable @{name}"),
+        }
+    }
+}
+
+/// An arithmetic operator in an expression.
+#[derive(Copy, Clone)]
+struct SpannedOperator {
+    operator: Operator,
+    /// Whether there's whitespace before the operator, which is kept if it isn't evaluated
+    spaced: bool,
+    /// Whether the operator is evaluated regardless of the math mode (`./`)
+    forced: bool,
+}
+
+/// Returns the operator at the start of the tokens, if any, with the number of token trees it
+/// spans including the whitespace around it.
+///
+/// Like in Less.js, `+` and `-` are only operators if they're followed by whitespace or not preceded
+/// by it, so `1 -@a` is a list of two values.
+fn operator<S>(tts: &[Spanned<TokenTree<S>>]) -> Option<(SpannedOperator, usize)> {
+    let start = tts.iter().position(|tt| !is_junk(tt))?;
+    let (operator, forced, len) = match &tts[start].0 {
+        TokenTree::Token(Token::Symbol('+')) => (Operator::Add, false, 1),
+        TokenTree::Token(Token::Symbol('-')) => (Operator::Subtract, false, 1),
+        TokenTree::Token(Token::Symbol('*')) => (Operator::Multiply, false, 1),
+        TokenTree::Token(Token::Symbol('/')) => (Operator::Divide, false, 1),
+        TokenTree::Token(Token::Symbol('.'))
+            if tts.get(start + 1).is_some_and(|(tt, _)| is_symbol(tt, '/')) =>
+        {
+            (Operator::Divide, true, 2)
+        }
+        _ => return None,
+    };
+    let end = start + len;
+    let spaced = start > 0;
+    let spaced_after = tts[end..].first().is_some_and(is_junk);
+    if matches!(operator, Operator::Add | Operator::Subtract) && spaced && !spaced_after {
+        return None;
+    }
+    let end = tts[end..]
+        .iter()
+        .position(|tt| !is_junk(tt))
+        .map_or(tts.len(), |i| end + i);
+    let operator = SpannedOperator {
+        operator,
+        spaced,
+        forced,
+    };
+    Some((operator, end))
+}
+
+/// Returns the span from the first to the last of the tokens.
+fn span_of<S>(tts: &[Spanned<TokenTree<S>>]) -> Span {
+    match tts {
+        [] => Span::splat(0),
+        [(_, first), .., (_, last)] => Span::new(first.start, last.end),
+        [(_, span)] => *span,
+    }
+}
+
+/// Converts the source text of a number token to the precision values are evaluated with.
+fn to_f64(number: &str) -> f64 {
+    number.parse().unwrap()
+}
+
+fn is_symbol<S>(tt: &TokenTree<S>, symbol: char) -> bool {
+    matches!(tt, TokenTree::Token(Token::Symbol(s)) if *s == symbol)
+}
+
+fn is_junk<S>((tt, _): &Spanned<TokenTree<S>>) -> bool {
+    matches!(tt, TokenTree::Token(Token::Whitespace | Token::Comment(_)))
+}
+
+/// Strips leading and trailing whitespace and comments.
+fn trim<S>(tts: &[Spanned<TokenTree<S>>]) -> &[Spanned<TokenTree<S>>] {
+    let start = tts.iter().position(|tt| !is_junk(tt)).unwrap_or(tts.len());
+    let end = tts
+        .iter()
+        .rposition(|tt| !is_junk(tt))
+        .map_or(start, |i| i + 1);
+    &tts[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile() {
+        let input = r#"
+            // Colors
+            @primary: #337ab7;
+            @font: "Helvetica", sans-serif;
+            @name: banner;
+
+            /* Layout */
+            .@{name} {
+              /* Text */ color: @primary; // primary
+              font: 12px/1.5 @font;
+              .title { margin: 0 auto; }
+              &-large, &.active { padding: .5em; }
+              > a:hover { text-decoration: none !important; }
+              @media (min-width: 768px) { float: left; }
+            }
+            .mixin(@a) { color: @a; }
+            @font-face { font-family: "Icons"; src: url(icons.woff); }
+        "#;
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            r#"/* Layout */
+.banner {
+  /* Text */
+  color: #337ab7;
+  font: 12px/1.5 "Helvetica", sans-serif;
+}
+.banner .title {
+  margin: 0 auto;
+}
+.banner-large,
+.banner.active {
+  padding: 0.5em;
+}
+.banner > a:hover {
+  text-decoration: none !important;
+}
+@media (min-width: 768px) {
+  .banner {
+    float: left;
+  }
+}
+@font-face {
+  font-family: "Icons";
+  src: url(icons.woff);
+}
+"#
+        );
+
+        let options = Options {
+            compress: true,
+            ..Options::default()
+        };
+        let css = compile(".a { b: c; d: e; .f { g: h } }", options).unwrap();
+        assert_eq!(css, ".a{b:c;d:e}.a .f{g:h}");
+
+        let input = r#"
+            @import (css) url("foo.css");
+            @base: "/img";
+            .a { b: url(@{base}/a.png) url("@{base}/b.png"); }
+        "#;
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            "@import url(\"foo.css\");\n.a {\n  b: url(/img/a.png) url(\"/img/b.png\");\n}\n"
+        );
+
+        let error = compile(".a { b: @c; }", Options::default()).unwrap_err();
+        assert_eq!(error.to_string(), "variable @c is undefined at 8..10");
+
+        // Values are looked up in detached rulesets, like maps
+        let input = "
+            @m: { a: 1; };
+            @config: {
+              @dark: { primary: darkblue; };
+              size: 2px;
+              @size: (@base * 2);
+              last: end;
+            };
+            @base: 5px;
+            @key: dark;
+            .x {
+              w: @m[a];
+              b: @config[@dark][primary] @config[@@key][$primary];
+              c: @config[size] @config[@size] @config[];
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".x {\n  w: 1;\n  b: darkblue darkblue;\n  c: 2px 10px end;\n}\n"
+        );
+
+        let input = "@m: { a: 1; };\n.x { b: @m[b]; }";
+        let error = compile(input, Options::default()).unwrap_err();
+        assert_eq!(error.to_string(), "property `b` is undefined at 25..28");
+        let input = "@m: { a: 1; };\n.x { b: @m[a][b]; }";
+        let error = compile(input, Options::default()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot look up in property `a`, which is not a detached ruleset at 25..28"
+        );
+
+        // Selectors that the parser doesn't understand are output as written
+        let input = "@n: 2;\n.a  /deep/ .b { c: d; }\n.col-2 + .col-1 - 1 { e: f; }\n.g /x/ .h-@{n} { i: j; }";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a  /deep/ .b {\n  c: d;\n}\n.col-2 + .col-1 - 1 {\n  e: f;\n}\n.g /x/ .h-2 {\n  i: j;\n}\n"
+        );
+
+        // Keyframe percentages are output as written
+        let input = "@keyframes a { 0%, 33.333333% { b: c; } 100.0% { b: d; } }";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            "@keyframes a {\n  0%,\n  33.333333% {\n    b: c;\n  }\n  100.0% {\n    b: d;\n  }\n}\n"
+        );
+    }
+}
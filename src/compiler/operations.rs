@@ -0,0 +1,168 @@
+//! Arithmetic on numbers and colors, with the unit rules of Less.js.
+
+use super::color::Color;
+use super::value::{Operator, Value};
+
+/// Applies an operator to two values, returning an error message if they can't be operated on.
+///
+/// Like in Less.js, the result has the unit of the left operand (or of the right one if the left
+/// one has none), and the right operand is converted to it if the units are compatible (e.g. `1cm +
+/// 10mm` is `2cm`). With `strict_units`, adding or subtracting incompatible units is an error.
+pub(crate) fn operate(
+    operator: Operator,
+    left: Value,
+    right: Value,
+    strict_units: bool,
+) -> Result<Value, String> {
+    match (left, right) {
+        (Value::Number(left, left_unit), Value::Number(right, right_unit)) => {
+            let (right, unit) = match operator {
+                Operator::Add | Operator::Subtract if left_unit.is_empty() => (right, right_unit),
+                Operator::Add | Operator::Subtract if right_unit.is_empty() => (right, left_unit),
+                Operator::Add | Operator::Subtract => {
+                    let (right, right_unit) = right_unit.convert(right, &left_unit);
+                    if strict_units && right_unit.describe() != left_unit.describe() {
+                        return Err(format!(
+                            "incompatible units: '{}' and '{}'; change the units or use the \
+                             unit function",
+                            left_unit.describe(),
+                            right_unit.describe()
+                        ));
+                    }
+                    (right, left_unit)
+                }
+                Operator::Multiply => (right, left_unit.multiply(&right_unit)),
+                Operator::Divide => (right, left_unit.divide(&right_unit)),
+            };
+            Ok(Value::Number(apply(operator, left, right), unit))
+        }
+        (Value::Color(left), Value::Color(right)) => {
+            Ok(Value::Color(operate_colors(operator, &left, &right)))
+        }
+        (Value::Color(left), Value::Number(right, _)) => Ok(Value::Color(operate_colors(
+            operator,
+            &left,
+            &Color::new([right; 3], 1.0),
+        ))),
+        (Value::Number(left, _), Value::Color(right)) => Ok(Value::Color(operate_colors(
+            operator,
+            &Color::new([left; 3], 1.0),
+            &right,
+        ))),
+        _ => Err("operation on an invalid type".to_string()),
+    }
+}
+
+/// Applies an operator to each channel of two colors.
+fn operate_colors(operator: Operator, left: &Color, right: &Color) -> Color {
+    let rgb = [0, 1, 2].map(|i| apply(operator, left.rgb[i], right.rgb[i]));
+    let alpha = left.alpha * (1.0 - right.alpha) + right.alpha;
+    Color::new(rgb, alpha)
+}
+
+fn apply(operator: Operator, left: f64, right: f64) -> f64 {
+    match operator {
+        Operator::Add => left + right,
+        Operator::Subtract => left - right,
+        Operator::Multiply => left * right,
+        Operator::Divide => left / right,
+    }
+}
+
+/// Checks that the numbers in a value that is output have at most a single unit, as required by
+/// `strictUnits`, and drops the units that cancelled out (e.g. in `(10px / 2px)`).
+pub(crate) fn check_units(value: &mut Value) -> Result<(), String> {
+    match value {
+        Value::Number(_, unit) if !unit.is_singular() => Err(format!(
+            "multiple units in dimension: '{}'; correct the units or use the unit function",
+            unit.describe()
+        )),
+        Value::Number(_, unit) => {
+            unit.drop_backup();
+            Ok(())
+        }
+        Value::Call(_, value) | Value::Block(_, value) => check_units(value),
+        Value::Operation(_, left, right, _) => {
+            check_units(left)?;
+            check_units(right)
+        }
+        Value::List(values, _) => values.iter_mut().try_for_each(check_units),
+        Value::Keyword(_)
+        | Value::Color(_)
+        | Value::Quoted(..)
+        | Value::Url(_)
+        | Value::Symbol(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Math, Options};
+
+    #[test]
+    fn test_operations() {
+        let input = "
+            @gutter: 20px;
+            @w: 100px;
+            .a {
+              a: @gutter / 2;
+              b: (@gutter / 2);
+              c: (@w - 10px);
+              d: @w - 10px;
+              e: 1cm + 10mm;
+              f: 2 * 3px + 1;
+              g: 10px + 5em;
+              h: (10px / 2px);
+              i: -@gutter;
+              j: 1 -1;
+              k: #102030 + #010203;
+              l: #336699 * 2;
+              m: @gutter ./ 4;
+              n: calc(100% - @gutter);
+              o: 12px/1.5;
+              p: 90deg + 1turn;
+              q: 2s + 100ms;
+            }
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".a {\n  a: 20px / 2;\n  b: 10px;\n  c: 90px;\n  d: 90px;\n  e: 2cm;\n  f: 7px;\n  \
+             g: 15px;\n  h: 5px;\n  i: -20px;\n  j: 1 -1;\n  k: #112233;\n  l: #66ccff;\n  \
+             m: 5px;\n  n: calc(100% - 20px);\n  o: 12px/1.5;\n  p: 450deg;\n  q: 2.1s;\n}\n"
+        );
+
+        let input = ".a { b: 10px/2; c: 1 + 1; d: (1 + 1); }";
+        let always = Options {
+            math: Math::Always,
+            ..Options::default()
+        };
+        let css = compile(input, always).unwrap();
+        assert_eq!(css, ".a {\n  b: 5px;\n  c: 2;\n  d: 2;\n}\n");
+        let parens = Options {
+            math: Math::Parens,
+            ..Options::default()
+        };
+        let css = compile(input, parens).unwrap();
+        assert_eq!(css, ".a {\n  b: 10px/2;\n  c: 1 + 1;\n  d: 2;\n}\n");
+
+        let strict = Options {
+            strict_units: true,
+            ..Options::default()
+        };
+        let css = compile(".a { b: (10px / 2px); }", strict.clone()).unwrap();
+        assert_eq!(css, ".a {\n  b: 5;\n}\n");
+        let error = compile(".a { b: 10px + 5em; }", strict.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "incompatible units: 'px' and 'em'; change the units or use the unit function at \
+             8..18"
+        );
+        let error = compile(".a { b: 2px * 3px; }", strict).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "multiple units in dimension: 'px*px'; correct the units or use the unit function at \
+             8..17"
+        );
+    }
+}
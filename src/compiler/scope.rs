@@ -0,0 +1,235 @@
+//! Lexical scopes of variables.
+//!
+//! Like in Less.js, variables are evaluated lazily where they're used, in the scope they're defined
+//! in. The last definition in a scope wins, so a variable can be used before it's defined.
+
+use std::collections::HashMap;
+
+use crate::ast::{self, Declaration, DeclarationName, Item, ListOfItems};
+use crate::error::EvalError;
+use crate::lexer::{Span, Spanned};
+
+use super::value::Value;
+
+/// The variables defined in a block.
+pub(super) struct Scope<'a, 'src> {
+    /// The items of the block, where mixins are looked up
+    items: Option<&'a ListOfItems<&'src str>>,
+    variables: HashMap<&'src str, Spanned<Definition<'a, 'src>>>,
+}
+
+/// The value a variable is defined with.
+#[derive(Clone)]
+pub(super) enum Definition<'a, 'src> {
+    /// A declaration, which is evaluated where the variable is used
+    Declared(&'a ast::Value<&'src str>),
+    /// A value that is already evaluated (e.g. the `@value` of `each()` or a mixin argument)
+    Evaluated(Value),
+}
+
+impl<'a, 'src> Scope<'a, 'src> {
+    fn new(items: &'a ListOfItems<&'src str>) -> Self {
+        let mut variables = HashMap::new();
+        for (item, span) in &items.0 {
+            if let Item::Declaration(Declaration {
+                name: DeclarationName::Variable(name),
+                value,
+                ..
+            }) = item
+            {
+                variables.insert(*name, (Definition::Declared(value), *span));
+            }
+        }
+        Self {
+            items: Some(items),
+            variables,
+        }
+    }
+}
+
+/// A variable found in the scopes.
+pub(super) struct Variable<'a, 'src> {
+    pub name: &'src str,
+    pub value: Definition<'a, 'src>,
+    /// The span of the definition
+    pub span: Span,
+    /// The index of the scope it's defined in
+    depth: usize,
+}
+
+/// A variable that is being evaluated.
+struct Evaluating<'src> {
+    name: &'src str,
+    definition: Span,
+}
+
+/// The scopes of the blocks that are being evaluated, innermost last.
+pub(super) struct Scopes<'a, 'src> {
+    scopes: Vec<Scope<'a, 'src>>,
+    /// The variables that are being evaluated, to detect recursive definitions
+    evaluating: Vec<Evaluating<'src>>,
+}
+
+impl<'a, 'src> Scopes<'a, 'src> {
+    pub fn new() -> Self {
+        Self {
+            scopes: Vec::new(),
+            evaluating: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, items: &'a ListOfItems<&'src str>) {
+        self.scopes.push(Scope::new(items));
+    }
+
+    /// Pushes a scope without a block, for variables that are bound (e.g. the arguments of a
+    /// mixin).
+    pub fn push_bindings(&mut self) {
+        self.scopes.push(Scope {
+            items: None,
+            variables: HashMap::new(),
+        });
+    }
+
+    pub fn pop(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Defines a variable in the innermost scope, replacing any definition of it in the block.
+    pub fn bind(&mut self, name: &'src str, value: Definition<'a, 'src>, span: Span) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("no scope to bind a variable in");
+        scope.variables.insert(name, (value, span));
+    }
+
+    /// Defines a variable with the value it has in a called mixin in the innermost scope, unless
+    /// the block itself declares it.
+    pub fn export(&mut self, name: &'src str, value: Value, span: Span) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("no scope to export a variable to");
+        if !matches!(
+            scope.variables.get(name),
+            Some((Definition::Declared(_), _))
+        ) {
+            scope
+                .variables
+                .insert(name, (Definition::Evaluated(value), span));
+        }
+    }
+
+    /// Returns the blocks of the scopes, innermost first.
+    pub fn blocks(&self) -> impl Iterator<Item = &'a ListOfItems<&'src str>> + '_ {
+        self.scopes.iter().rev().filter_map(|scope| scope.items)
+    }
+
+    /// Finds the variable with the given name in the innermost scope that defines it.
+    pub fn lookup(&self, name: &str, span: Span) -> Result<Variable<'a, 'src>, EvalError> {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, scope)| {
+                let (name, (value, span)) = scope.variables.get_key_value(name)?;
+                Some(Variable {
+                    name,
+                    value: value.clone(),
+                    span: *span,
+                    depth,
+                })
+            })
+            .ok_or_else(|| EvalError::new(span, format!("variable @{name} is undefined")))
+    }
+
+    /// Starts evaluating a variable that is used at `span`, leaving only the scopes it's defined in
+    /// until [`exit`](Self::exit) is called with the returned scopes.
+    ///
+    /// Returns an error with the chain of variables if the variable is defined in terms of itself.
+    pub fn enter(
+        &mut self,
+        variable: &Variable<'a, 'src>,
+        span: Span,
+    ) -> Result<Vec<Scope<'a, 'src>>, EvalError> {
+        if let Some(start) = self
+            .evaluating
+            .iter()
+            .position(|evaluating| evaluating.definition == variable.span)
+        {
+            let chain: Vec<_> = self.evaluating[start..]
+                .iter()
+                .map(|evaluating| evaluating.name)
+                .chain([variable.name])
+                .map(|name| format!("@{name}"))
+                .collect();
+            let message = format!("recursive variable definition: {}", chain.join(" -> "));
+            return Err(self.evaluating[start..]
+                .iter()
+                .fold(EvalError::new(span, message), |error, evaluating| {
+                    error.with_label("in this definition", evaluating.definition)
+                }));
+        }
+
+        self.evaluating.push(Evaluating {
+            name: variable.name,
+            definition: variable.span,
+        });
+        Ok(self.scopes.split_off(variable.depth + 1))
+    }
+
+    /// Finishes evaluating a variable, restoring the scopes returned by [`enter`](Self::enter).
+    pub fn exit(&mut self, inner_scopes: Vec<Scope<'a, 'src>>) {
+        self.evaluating.pop();
+        self.scopes.extend(inner_scopes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compiler::{compile, Options};
+    use crate::CompileError;
+
+    #[test]
+    fn test_scopes() {
+        let input = "
+            @a: 1;
+            .x {
+              last: @a;
+              later: @b;
+              @a: 3;
+              .y { inner: @a; @a: 4; }
+              .z { outer: @c; @c: @a; }
+              @a: 2;
+            }
+            .w { global: @a; name: @@name; }
+            @b: @a;
+            @name: \"a\";
+        ";
+        let css = compile(input, Options::default()).unwrap();
+        assert_eq!(
+            css,
+            ".x {\n  last: 2;\n  later: 1;\n}\n\
+             .x .y {\n  inner: 4;\n}\n\
+             .x .z {\n  outer: 2;\n}\n\
+             .w {\n  global: 1;\n  name: 1;\n}\n"
+        );
+
+        let input = "@a: @b;\n@b: @c;\n@c: @a;\n.x { y: @a; }";
+        let CompileError::Eval(error) = compile(input, Options::default()).unwrap_err() else {
+            panic!("expected an evaluation error");
+        };
+        assert_eq!(
+            error.message(),
+            "recursive variable definition: @a -> @b -> @c -> @a"
+        );
+        assert_eq!(error.span(), 20..22);
+        let labels: Vec<_> = error
+            .labels()
+            .iter()
+            .map(|label| label.span.clone())
+            .collect();
+        assert_eq!(labels, [0..7, 8..15, 16..23]);
+    }
+}
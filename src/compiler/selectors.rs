@@ -0,0 +1,68 @@
+//! Resolution of nested selectors against the selectors of their parent rules.
+
+use crate::ast::{Combinator, Selector, SelectorComponent};
+
+/// Resolves the selectors of a nested rule, replacing each `&` with the parent selectors (in all
+/// combinations), or adding the parent selectors as ancestors if there is no `&`.
+pub(crate) fn resolve(
+    parents: &[Selector<String>],
+    selectors: Vec<Selector<String>>,
+) -> Vec<Selector<String>> {
+    let root = [Selector {
+        components: Vec::new(),
+        extends: Vec::new(),
+    }];
+    let parents = if parents.is_empty() { &root } else { parents };
+
+    let mut resolved = Vec::new();
+    for selector in selectors {
+        let has_parent = selector
+            .components
+            .iter()
+            .any(|(component, _)| *component == SelectorComponent::Parent);
+
+        if !has_parent {
+            for parent in parents {
+                let mut components = parent.components.clone();
+                match selector.components.first() {
+                    // A selector starting with a combinator (e.g. `> .child`) already has one
+                    Some((SelectorComponent::Combinator(_), _)) => {}
+                    Some((_, span)) if !components.is_empty() => components
+                        .push((SelectorComponent::Combinator(Combinator::Descendant), *span)),
+                    _ => {}
+                }
+                components.extend(selector.components.iter().cloned());
+                resolved.push(Selector {
+                    components,
+                    extends: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        let mut combinations = vec![Vec::new()];
+        for (component, span) in selector.components {
+            if component == SelectorComponent::Parent {
+                combinations = combinations
+                    .iter()
+                    .flat_map(|components| {
+                        parents.iter().map(|parent| {
+                            let mut components = components.clone();
+                            components.extend(parent.components.iter().cloned());
+                            components
+                        })
+                    })
+                    .collect();
+            } else {
+                for components in &mut combinations {
+                    components.push((component.clone(), span));
+                }
+            }
+        }
+        resolved.extend(combinations.into_iter().map(|components| Selector {
+            components,
+            extends: Vec::new(),
+        }));
+    }
+    resolved
+}
@@ -0,0 +1,181 @@
+//! Units of numbers, with conversions between compatible units.
+
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Groups of compatible units, with the size of each unit relative to the others in its group.
+const CONVERSIONS: [&[(&str, f64)]; 3] = [
+    &[
+        ("m", 1.0),
+        ("cm", 0.01),
+        ("mm", 0.001),
+        ("in", 0.0254),
+        ("px", 0.0254 / 96.0),
+        ("pt", 0.0254 / 72.0),
+        ("pc", 0.0254 / 72.0 * 12.0),
+    ],
+    &[("s", 1.0), ("ms", 0.001)],
+    &[
+        ("rad", 1.0 / (2.0 * PI)),
+        ("deg", 1.0 / 360.0),
+        ("grad", 1.0 / 400.0),
+        ("turn", 1.0),
+    ],
+];
+
+/// The unit of a number, which can be a product or quotient of units after multiplication and
+/// division (e.g. `px*px`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Unit {
+    numerator: Vec<String>,
+    denominator: Vec<String>,
+    /// The first unit the number had, which is output if the unit isn't a single unit
+    backup: Option<String>,
+}
+
+impl Unit {
+    pub fn new(unit: &str) -> Self {
+        if unit.is_empty() {
+            return Self::default();
+        }
+        Self {
+            numerator: vec![unit.to_string()],
+            denominator: Vec::new(),
+            backup: Some(unit.to_string()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.numerator.is_empty() && self.denominator.is_empty()
+    }
+
+    /// Returns whether the unit is exactly the given unit, case-insensitively.
+    pub fn is(&self, unit: &str) -> bool {
+        self.describe().eq_ignore_ascii_case(unit)
+    }
+
+    /// Returns whether the unit is at most a single unit (e.g. `px`, but not `px*px` or `px/s`).
+    pub fn is_singular(&self) -> bool {
+        self.numerator.len() <= 1 && self.denominator.is_empty()
+    }
+
+    pub fn multiply(&self, other: &Unit) -> Unit {
+        let mut unit = self.clone();
+        unit.numerator.extend(other.numerator.iter().cloned());
+        unit.denominator.extend(other.denominator.iter().cloned());
+        unit.cancel();
+        unit
+    }
+
+    pub fn divide(&self, other: &Unit) -> Unit {
+        let mut unit = self.clone();
+        unit.numerator.extend(other.denominator.iter().cloned());
+        unit.denominator.extend(other.numerator.iter().cloned());
+        unit.cancel();
+        unit
+    }
+
+    /// Removes units that appear in both the numerator and the denominator.
+    fn cancel(&mut self) {
+        let mut i = 0;
+        while i < self.numerator.len() {
+            match self
+                .denominator
+                .iter()
+                .position(|u| *u == self.numerator[i])
+            {
+                Some(j) => {
+                    self.numerator.remove(i);
+                    self.denominator.remove(j);
+                }
+                None => i += 1,
+            }
+        }
+        self.numerator.sort();
+        self.denominator.sort();
+    }
+
+    /// Forgets the backup unit of a number whose units cancelled out, so it's output without a
+    /// unit (as with `strictUnits`).
+    pub fn drop_backup(&mut self) {
+        if self.is_empty() {
+            self.backup = None;
+        }
+    }
+
+    /// Converts a number with this unit to the units of `target` that are compatible with it (e.g.
+    /// `10mm` to `1cm`), returning the converted number and unit.
+    pub fn convert(&self, value: f64, target: &Unit) -> (f64, Unit) {
+        let mut value = value;
+        let mut unit = self.clone();
+        for (units, is_numerator) in [(&mut unit.numerator, true), (&mut unit.denominator, false)] {
+            for from in units.iter_mut() {
+                let Some(group) = group(from) else { continue };
+                let Some(to) = target.units().find(|to| group.iter().any(|(u, _)| u == to)) else {
+                    continue;
+                };
+                let factor = size(group, from) / size(group, to);
+                value = if is_numerator {
+                    value * factor
+                } else {
+                    value / factor
+                };
+                *from = to.to_string();
+            }
+        }
+        unit.cancel();
+        (value, unit)
+    }
+
+    /// Converts a number with this unit to the base units of lengths, durations and angles (`px`,
+    /// `s` and `rad`), for comparisons and trigonometry.
+    pub fn unify(&self, value: f64) -> (f64, Unit) {
+        let base = Unit {
+            numerator: vec!["px".to_string(), "s".to_string(), "rad".to_string()],
+            denominator: Vec::new(),
+            backup: None,
+        };
+        self.convert(value, &base)
+    }
+
+    fn units(&self) -> impl Iterator<Item = &str> {
+        self.numerator
+            .iter()
+            .chain(&self.denominator)
+            .map(String::as_str)
+    }
+
+    /// Describes the unit for error messages (e.g. `px*px/s`).
+    pub fn describe(&self) -> String {
+        let mut description = self.numerator.join("*");
+        for unit in &self.denominator {
+            description.push('/');
+            description.push_str(unit);
+        }
+        description
+    }
+}
+
+fn group(unit: &str) -> Option<&'static [(&'static str, f64)]> {
+    CONVERSIONS
+        .into_iter()
+        .find(|group| group.iter().any(|(u, _)| *u == unit))
+}
+
+fn size(group: &[(&str, f64)], unit: &str) -> f64 {
+    group.iter().find(|(u, _)| *u == unit).unwrap().1
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [unit] = self.numerator.as_slice() {
+            f.write_str(unit)
+        } else if let Some(backup) = &self.backup {
+            f.write_str(backup)
+        } else if let Some(unit) = self.denominator.first() {
+            f.write_str(unit)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! The values that declarations and variables evaluate to.
+
+use std::fmt;
+
+use crate::lexer::Delim;
+
+use super::color::Color;
+use super::unit::Unit;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    /// An identifier or other text that is output as-is (e.g. `solid`)
+    Keyword(String),
+    /// A number with an optional unit (e.g. `10px`, `50%` or `1.5`)
+    Number(f64, Unit),
+    Color(Color),
+    /// The contents of a quoted string, with the quote it was written with
+    Quoted(String, char),
+    /// The contents of an unquoted `url()`
+    Url(String),
+    /// A symbol that isn't part of another value (e.g. the `,` in `a:b,c`)
+    Symbol(char),
+    /// An operation that isn't evaluated because of the math mode (e.g. `12px/1.5`), with whether
+    /// the operator is surrounded by whitespace
+    Operation(Operator, Box<Value>, Box<Value>, bool),
+    /// A call to a function that isn't evaluated (e.g. `calc()`), with its arguments
+    Call(String, Box<Value>),
+    /// A value in parentheses or brackets
+    Block(Delim, Box<Value>),
+    List(Vec<Value>, Separator),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Separator {
+    Comma,
+    Space,
+    /// No separator, for values that are written next to each other (e.g. `12px/1.5`)
+    None,
+}
+
+/// Outputs the value as CSS. The alternate flag (`{:#}`) outputs the compressed form, and is passed
+/// on to the values in it.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Keyword(keyword) => f.write_str(keyword),
+            Value::Number(number, unit) => write!(f, "{}{}", format_number(*number), unit),
+            Value::Quoted(text, quote) => write!(f, "{quote}{text}{quote}"),
+            Value::Url(url) => write!(f, "url({url})"),
+            Value::Color(color) => color.fmt(f),
+            Value::Symbol(symbol) => write!(f, "{symbol}"),
+            Value::Operation(operator, left, right, spaced) => {
+                left.fmt(f)?;
+                match spaced {
+                    true => write!(f, " {operator} ")?,
+                    false => write!(f, "{operator}")?,
+                }
+                right.fmt(f)
+            }
+            Value::Call(name, arguments) => {
+                write!(f, "{name}(")?;
+                arguments.fmt(f)?;
+                f.write_str(")")
+            }
+            Value::Block(delim, value) => {
+                write!(f, "{}", delim.open())?;
+                value.fmt(f)?;
+                write!(f, "{}", delim.close())
+            }
+            Value::List(values, separator) => {
+                let separator = match separator {
+                    Separator::Comma if f.alternate() => ",",
+                    Separator::Comma => ", ",
+                    Separator::Space => " ",
+                    Separator::None => "",
+                };
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(separator)?;
+                    }
+                    value.fmt(f)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Formats a number like Less.js, rounded to 8 decimals and without trailing zeros. Infinite and
+/// undefined results (e.g. of `pow(0, -1)`) are output like JavaScript numbers.
+pub(crate) fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        return "NaN".to_string();
+    }
+    if number.is_infinite() {
+        return match number > 0.0 {
+            true => "Infinity".to_string(),
+            false => "-Infinity".to_string(),
+        };
+    }
+    let formatted = format!("{:.8}", number);
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    match formatted {
+        "-0" => "0".to_string(),
+        _ => formatted.to_string(),
+    }
+}
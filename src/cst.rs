@@ -0,0 +1,594 @@
+//! A lossless concrete syntax tree, in which every byte of the source belongs to exactly one token.
+//!
+//! Like [rowan](https://docs.rs/rowan), the tree has two layers: immutable [`GreenNode`]s, which
+//! only know their kind, length and children (so they can be shared and cheaply rebuilt by
+//! formatters and refactoring tools), and [`SyntaxNode`]s, which are created on demand while walking
+//! the tree and add absolute offsets and parent pointers.
+
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::ast::visit::{walk_item, Visit};
+use crate::ast::{Item, ListOfItems, Stylesheet};
+use crate::lexer::{Delim, Span, Spanned, Token, TokenTree};
+use crate::{lex, parse_tokens_recovering, ParseErrors};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // Nodes
+    Stylesheet,
+    AtRule,
+    QualifiedRule,
+    Declaration,
+    Call,
+    Extend,
+    /// The braces and contents of a block of items (e.g. a rule's block or a detached ruleset)
+    Block,
+    /// Any other parentheses, braces or brackets and their contents
+    Tree,
+
+    // Tokens
+    Whitespace,
+    Comment,
+    Ident,
+    Hash,
+    String,
+    Url,
+    Interpolation,
+    Number,
+    Symbol,
+    /// An opening `(`, `{` or `[`
+    Open,
+    /// A closing `)`, `}` or `]`
+    Close,
+    /// Source text that couldn't be lexed (e.g. because of an unclosed brace)
+    Error,
+}
+
+/// A parsed stylesheet, as both a lossless syntax tree and the typed AST.
+pub struct Cst<'src> {
+    root: SyntaxNode,
+    stylesheet: Stylesheet<&'src str>,
+}
+
+impl<'src> Cst<'src> {
+    /// Parses a LESS stylesheet, recovering from syntax errors like
+    /// [`parse_recovering`](crate::parse_recovering), and returns the tree with the errors.
+    ///
+    /// The tree always covers the whole input: the tokens of items that fail to parse are left
+    /// between the other items, and input that can't be lexed is a single [`SyntaxKind::Error`]
+    /// token (with an empty stylesheet).
+    pub fn parse(input: &'src str) -> (Self, ParseErrors) {
+        let (tts, stylesheet, errors) = match lex(input) {
+            Ok(tts) => {
+                let (stylesheet, errors) = parse_tokens_recovering(input, &tts);
+                (tts, stylesheet, errors)
+            }
+            Err(errors) => (Vec::new(), None, errors),
+        };
+        let stylesheet = stylesheet.unwrap_or(Stylesheet {
+            items: ListOfItems(Vec::new()),
+        });
+
+        let children = match tts.is_empty() && !input.is_empty() {
+            true => vec![GreenElement::Token(Arc::new(GreenToken::new(
+                SyntaxKind::Error,
+                input,
+            )))],
+            false => items(input, &tts, &stylesheet.items.0),
+        };
+        let root = SyntaxNode::new_root(Arc::new(GreenNode::new(SyntaxKind::Stylesheet, children)));
+        (Self { root, stylesheet }, errors)
+    }
+
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    pub fn stylesheet(&self) -> &Stylesheet<&'src str> {
+        &self.stylesheet
+    }
+
+    /// Returns the typed item of an item node (e.g. a [`SyntaxKind::Declaration`]).
+    ///
+    /// This walks the AST, so it's better to walk the AST directly when looking up many nodes.
+    pub fn item(&self, node: &SyntaxNode) -> Option<&Item<&'src str>> {
+        if !matches!(
+            node.kind(),
+            SyntaxKind::AtRule
+                | SyntaxKind::QualifiedRule
+                | SyntaxKind::Declaration
+                | SyntaxKind::Call
+                | SyntaxKind::Extend
+        ) {
+            return None;
+        }
+
+        let mut find = FindItem {
+            span: node.text_range(),
+            found: None,
+        };
+        find.visit_stylesheet(&self.stylesheet);
+        find.found
+    }
+}
+
+struct FindItem<'ast, S> {
+    span: Span,
+    found: Option<&'ast Item<S>>,
+}
+
+impl<'ast, S> Visit<'ast, S> for FindItem<'ast, S> {
+    fn visit_item(&mut self, item: &'ast Item<S>, span: Span) {
+        if span == self.span {
+            self.found.get_or_insert(item);
+        }
+        walk_item(self, item, span);
+    }
+}
+
+// BUILDING
+
+/// Builds the children of a stylesheet or block, grouping the tokens of each item into a node.
+/// Tokens between items (e.g. whitespace and comments) are left as direct children.
+fn items<S>(
+    source: &str,
+    mut tts: &[Spanned<TokenTree<S>>],
+    items: &[Spanned<Item<S>>],
+) -> Vec<GreenElement> {
+    let mut children = Vec::new();
+    let mut items = items.iter().peekable();
+    while let Some(((tt, span), rest)) = tts.split_first() {
+        match items.peek() {
+            Some((item, item_span)) if span.start >= item_span.start => {
+                let len = tts
+                    .iter()
+                    .take_while(|(_, span)| span.end <= item_span.end)
+                    .count()
+                    .max(1);
+                children.push(item_node(source, item, &tts[..len]));
+                tts = &tts[len..];
+                items.next();
+            }
+            _ => {
+                children.push(element(source, tt, *span, None));
+                tts = rest;
+            }
+        }
+    }
+    children
+}
+
+fn item_node<S>(source: &str, item: &Item<S>, tts: &[Spanned<TokenTree<S>>]) -> GreenElement {
+    let kind = match item {
+        Item::AtRule(_) => SyntaxKind::AtRule,
+        Item::QualifiedRule(_) => SyntaxKind::QualifiedRule,
+        Item::Declaration(_) => SyntaxKind::Declaration,
+        Item::Call(_) => SyntaxKind::Call,
+        Item::Extend(_) => SyntaxKind::Extend,
+    };
+
+    // The block of an item is always its last pair of braces
+    let block = item.block();
+    let block_index = block.and_then(|_| {
+        tts.iter()
+            .rposition(|(tt, _)| matches!(tt, TokenTree::Tree(Delim::Brace, _)))
+    });
+
+    let children = tts
+        .iter()
+        .enumerate()
+        .map(|(i, (tt, span))| {
+            let block = block.filter(|_| block_index == Some(i));
+            element(source, tt, *span, block)
+        })
+        .collect();
+    GreenElement::Node(Arc::new(GreenNode::new(kind, children)))
+}
+
+/// Builds the element of a token tree. If the token tree is the given block, its items are
+/// grouped into nodes too.
+fn element<S>(
+    source: &str,
+    tt: &TokenTree<S>,
+    span: Span,
+    block: Option<&ListOfItems<S>>,
+) -> GreenElement {
+    let token = |kind, start, end| {
+        GreenElement::Token(Arc::new(GreenToken::new(kind, &source[start..end])))
+    };
+    match tt {
+        TokenTree::Token(t) => token(token_kind(t), span.start, span.end),
+        TokenTree::Tree(_, tts) => {
+            let mut children = vec![token(SyntaxKind::Open, span.start, span.start + 1)];
+            match block {
+                Some(block) => children.extend(items(source, tts, &block.0)),
+                None => children.extend(
+                    tts.iter()
+                        .map(|(tt, span)| element(source, tt, *span, None)),
+                ),
+            }
+            children.push(token(SyntaxKind::Close, span.end - 1, span.end));
+
+            let kind = match block {
+                Some(_) => SyntaxKind::Block,
+                None => SyntaxKind::Tree,
+            };
+            GreenElement::Node(Arc::new(GreenNode::new(kind, children)))
+        }
+    }
+}
+
+fn token_kind<S>(token: &Token<S>) -> SyntaxKind {
+    match token {
+        Token::Whitespace => SyntaxKind::Whitespace,
+        Token::Comment(_) => SyntaxKind::Comment,
+        Token::Ident(_) => SyntaxKind::Ident,
+        Token::Hash(_) => SyntaxKind::Hash,
+        Token::String(_) => SyntaxKind::String,
+        Token::Url(_) => SyntaxKind::Url,
+        Token::Interpolation(_) => SyntaxKind::Interpolation,
+        Token::Number(_) => SyntaxKind::Number,
+        Token::Symbol(_) => SyntaxKind::Symbol,
+    }
+}
+
+// GREEN TREE
+
+/// An immutable node, without a position or parent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    len: usize,
+    children: Vec<GreenElement>,
+}
+
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> Self {
+        let len = children.iter().map(GreenElement::len).sum();
+        Self {
+            kind,
+            len,
+            children,
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    /// The length of the node's text in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                GreenElement::Node(node) => write!(f, "{node}")?,
+                GreenElement::Token(token) => f.write_str(token.text())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An immutable token, without a position or parent.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    text: Box<str>,
+}
+
+impl GreenToken {
+    pub fn new(kind: SyntaxKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GreenElement {
+    Node(Arc<GreenNode>),
+    Token(Arc<GreenToken>),
+}
+
+impl GreenElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            GreenElement::Node(node) => node.kind,
+            GreenElement::Token(token) => token.kind,
+        }
+    }
+
+    /// The length of the element's text in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.len,
+            GreenElement::Token(token) => token.text.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// RED TREE
+
+/// A node with its position in the source and a pointer to its parent.
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+
+struct NodeData {
+    green: Arc<GreenNode>,
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+
+impl SyntaxNode {
+    pub fn new_root(green: Arc<GreenNode>) -> Self {
+        Self(Rc::new(NodeData {
+            green,
+            offset: 0,
+            parent: None,
+        }))
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+
+    pub fn text_range(&self) -> Span {
+        Span::new(self.0.offset, self.0.offset + self.0.green.len)
+    }
+
+    /// Returns the node's source text.
+    pub fn text(&self) -> String {
+        self.0.green.to_string()
+    }
+
+    pub fn green(&self) -> &Arc<GreenNode> {
+        &self.0.green
+    }
+
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+
+    /// Returns the node and its ancestors, from the node up to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = SyntaxNode> {
+        std::iter::successors(Some(self.clone()), |node| node.parent().cloned())
+    }
+
+    pub fn children_with_tokens(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+        let mut offset = self.0.offset;
+        self.0.green.children.iter().map(move |child| {
+            let start = offset;
+            offset += child.len();
+            match child {
+                GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                    green: green.clone(),
+                    offset: start,
+                    parent: Some(self.clone()),
+                }))),
+                GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                    green: green.clone(),
+                    offset: start,
+                    parent: self.clone(),
+                }),
+            }
+        })
+    }
+
+    pub fn children(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.children_with_tokens().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Returns the node and all nodes below it, in preorder.
+    pub fn descendants(&self) -> impl Iterator<Item = SyntaxNode> {
+        let mut stack = vec![self.clone()];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            let start = stack.len();
+            stack.extend(node.children());
+            stack[start..].reverse();
+            Some(node)
+        })
+    }
+}
+
+impl PartialEq for SyntaxNode {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0.green, &other.0.green) && self.0.offset == other.0.offset
+    }
+}
+
+impl Eq for SyntaxNode {}
+
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}@{}", self.kind(), self.text_range())
+    }
+}
+
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.green)
+    }
+}
+
+/// A token with its position in the source and a pointer to its parent.
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Arc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+}
+
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind
+    }
+
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+
+    pub fn text_range(&self) -> Span {
+        Span::new(self.offset, self.offset + self.green.text.len())
+    }
+
+    pub fn green(&self) -> &Arc<GreenToken> {
+        &self.green
+    }
+
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+}
+
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}@{} {:?}",
+            self.kind(),
+            self.text_range(),
+            self.text()
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn kind(&self) -> SyntaxKind {
+        match self {
+            SyntaxElement::Node(node) => node.kind(),
+            SyntaxElement::Token(token) => token.kind(),
+        }
+    }
+
+    pub fn text_range(&self) -> Span {
+        match self {
+            SyntaxElement::Node(node) => node.text_range(),
+            SyntaxElement::Token(token) => token.text_range(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{Declaration, DeclarationName};
+
+    #[test]
+    fn test_cst() {
+        let input =
+            "// Theme\n@color: red;\n.a {\n  /* note */ color: @color ;\n  .b { margin: 0 }\n}\n";
+        let (cst, errors) = Cst::parse(input);
+        assert!(errors.is_empty());
+        let root = cst.root();
+        assert_eq!(root.text(), input);
+
+        let kinds: Vec<_> = root
+            .children_with_tokens()
+            .map(|child| child.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                SyntaxKind::Comment,
+                SyntaxKind::Whitespace,
+                SyntaxKind::Declaration,
+                SyntaxKind::Whitespace,
+                SyntaxKind::QualifiedRule,
+                SyntaxKind::Whitespace,
+            ]
+        );
+
+        let block = root
+            .descendants()
+            .find(|node| node.kind() == SyntaxKind::Block)
+            .unwrap();
+        let kinds: Vec<_> = block.children().map(|child| child.kind()).collect();
+        assert_eq!(kinds, [SyntaxKind::Declaration, SyntaxKind::QualifiedRule]);
+        assert_eq!(block.parent().unwrap().kind(), SyntaxKind::QualifiedRule);
+
+        let declaration = block.children().next().unwrap();
+        assert_eq!(declaration.text(), "color: @color ;");
+        assert_eq!(
+            &input[declaration.text_range().into_range()],
+            "color: @color ;"
+        );
+        assert_eq!(declaration.ancestors().last().as_ref(), Some(root));
+        assert!(matches!(
+            cst.item(&declaration),
+            Some(Item::Declaration(Declaration {
+                name: DeclarationName::Ident("color"),
+                ..
+            }))
+        ));
+        assert_eq!(cst.item(&block), None);
+    }
+
+    #[test]
+    fn test_cst_invalid() {
+        // The item that fails to parse is kept as tokens between the others
+        let input = ".a { color: red; }\n.b { ) }\n@c: 1;\n";
+        let (cst, errors) = Cst::parse(input);
+        assert!(!errors.is_empty());
+        assert_eq!(cst.root().text(), input);
+        let kinds: Vec<_> = cst.root().children().map(|child| child.kind()).collect();
+        assert_eq!(
+            kinds,
+            [
+                SyntaxKind::QualifiedRule,
+                SyntaxKind::QualifiedRule,
+                SyntaxKind::Declaration
+            ]
+        );
+        let block = cst
+            .root()
+            .descendants()
+            .filter(|node| node.kind() == SyntaxKind::Block);
+        let block = block.last().unwrap();
+        assert_eq!(block.text(), "{ ) }");
+        assert_eq!(block.children().count(), 0);
+
+        let input = ".a { color: red;\n";
+        let (cst, errors) = Cst::parse(input);
+        assert!(!errors.is_empty());
+        assert_eq!(cst.root().text(), input);
+        assert!(cst.stylesheet().items.0.is_empty());
+    }
+}
@@ -0,0 +1,416 @@
+//! Rendering of [`ParseErrors`] and [`CompileError`]s as plain or colored text (in the style of
+//! `rustc`), or as JSON.
+
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::error::{CompileError, EvalError, Expected, Label, ParseError, ParseErrors};
+
+/// The output format of [`render`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    /// Plain text, for logs
+    Plain,
+    /// Text with ANSI colors, for terminals
+    Colored,
+    /// A JSON array with an object per error, for machine consumption
+    Json,
+}
+
+/// Renders the errors produced while parsing `source`, which was read from `file_name`.
+pub fn render(errors: &ParseErrors, file_name: &str, source: &str, format: Format) -> String {
+    let errors: Vec<&dyn Diagnostic> = errors.iter().map(|error| error as _).collect();
+    render_diagnostics(&errors, file_name, source, format)
+}
+
+/// Renders the error produced while compiling `source`, which was read from `file_name`.
+pub fn render_compile_error(
+    error: &CompileError,
+    file_name: &str,
+    source: &str,
+    format: Format,
+) -> String {
+    match error {
+        CompileError::Parse(errors) => render(errors, file_name, source, format),
+        CompileError::Eval(error) => render_diagnostics(&[error], file_name, source, format),
+    }
+}
+
+/// The parts of an error that are rendered.
+trait Diagnostic {
+    fn span(&self) -> Range<usize>;
+    fn message(&self) -> String;
+    fn found(&self) -> Option<&str>;
+    fn expected(&self) -> &[Expected];
+    fn labels(&self) -> &[Label];
+}
+
+impl Diagnostic for ParseError {
+    fn span(&self) -> Range<usize> {
+        self.span()
+    }
+
+    fn message(&self) -> String {
+        self.message()
+    }
+
+    fn found(&self) -> Option<&str> {
+        self.found()
+    }
+
+    fn expected(&self) -> &[Expected] {
+        self.expected()
+    }
+
+    fn labels(&self) -> &[Label] {
+        self.labels()
+    }
+}
+
+impl Diagnostic for EvalError {
+    fn span(&self) -> Range<usize> {
+        self.span()
+    }
+
+    fn message(&self) -> String {
+        self.message().to_string()
+    }
+
+    fn found(&self) -> Option<&str> {
+        None
+    }
+
+    fn expected(&self) -> &[Expected] {
+        &[]
+    }
+
+    fn labels(&self) -> &[Label] {
+        self.labels()
+    }
+}
+
+fn render_diagnostics(
+    errors: &[&dyn Diagnostic],
+    file_name: &str,
+    source: &str,
+    format: Format,
+) -> String {
+    let source = Source::new(source);
+    match format {
+        Format::Plain => render_text(errors, file_name, &source, &Palette::PLAIN),
+        Format::Colored => render_text(errors, file_name, &source, &Palette::COLORED),
+        Format::Json => render_json(errors, file_name, &source),
+    }
+}
+
+/// A position in the source. Lines and columns start at 1, and columns count characters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Location {
+    line: usize,
+    column: usize,
+}
+
+struct Source<'a> {
+    text: &'a str,
+    /// The byte offset at which each line starts
+    line_starts: Vec<usize>,
+}
+
+impl<'a> Source<'a> {
+    fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    fn location(&self, offset: usize) -> Location {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let column = self.text[self.line_starts[line]..offset].chars().count() + 1;
+        Location {
+            line: line + 1,
+            column,
+        }
+    }
+
+    /// Returns the text of a (1-based) line, without its line ending.
+    fn line(&self, line: usize) -> &'a str {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .copied()
+            .unwrap_or(self.text.len());
+        self.text[start..end].trim_end_matches(['\n', '\r'])
+    }
+}
+
+struct Palette {
+    error: &'static str,
+    message: &'static str,
+    gutter: &'static str,
+    label: &'static str,
+    reset: &'static str,
+}
+
+impl Palette {
+    const PLAIN: Palette = Palette {
+        error: "",
+        message: "",
+        gutter: "",
+        label: "",
+        reset: "",
+    };
+
+    const COLORED: Palette = Palette {
+        error: "\x1b[1;31m",
+        message: "\x1b[1m",
+        gutter: "\x1b[1;34m",
+        label: "\x1b[1;34m",
+        reset: "\x1b[0m",
+    };
+}
+
+/// An underlined span in a snippet, with an optional message after the underline.
+struct Annotation<'e> {
+    span: Range<usize>,
+    message: Option<&'e str>,
+    primary: bool,
+}
+
+fn render_text(
+    errors: &[&dyn Diagnostic],
+    file_name: &str,
+    source: &Source,
+    palette: &Palette,
+) -> String {
+    let mut output = String::new();
+    for (i, error) in errors.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        render_error(&mut output, *error, file_name, source, palette);
+    }
+    output
+}
+
+fn render_error(
+    output: &mut String,
+    error: &dyn Diagnostic,
+    file_name: &str,
+    source: &Source,
+    palette: &Palette,
+) {
+    let Palette {
+        error: error_color,
+        message: message_color,
+        gutter: gutter_color,
+        label: label_color,
+        reset,
+    } = palette;
+
+    let mut annotations = vec![Annotation {
+        span: error.span(),
+        message: None,
+        primary: true,
+    }];
+    annotations.extend(error.labels().iter().map(|label| Annotation {
+        span: label.span.clone(),
+        message: Some(label.message),
+        primary: false,
+    }));
+
+    let mut lines: Vec<_> = annotations
+        .iter()
+        .map(|annotation| source.location(annotation.span.start).line)
+        .collect();
+    lines.sort();
+    lines.dedup();
+
+    let gutter_width = lines.last().map_or(1, |line| line.to_string().len());
+    let blank_gutter = " ".repeat(gutter_width);
+
+    let start = source.location(error.span().start);
+    let _ = writeln!(
+        output,
+        "{error_color}error{reset}{message_color}: {}{reset}",
+        error.message()
+    );
+    let _ = writeln!(
+        output,
+        "{blank_gutter}{gutter_color}-->{reset} {}:{}:{}",
+        file_name, start.line, start.column
+    );
+    let _ = writeln!(output, "{blank_gutter} {gutter_color}|{reset}");
+
+    for line in lines {
+        let text = source.line(line);
+        let _ = writeln!(
+            output,
+            "{gutter_color}{:>gutter_width$} |{reset} {}",
+            line, text
+        );
+
+        for annotation in &annotations {
+            let start = source.location(annotation.span.start);
+            if start.line != line {
+                continue;
+            }
+
+            // Underline up to the end of the span, or the end of the line if the span continues on
+            // the next lines. Empty spans (e.g. at the end of input) are underlined as one column.
+            let end = source.location(annotation.span.end);
+            let end_column = if end.line == line {
+                end.column
+            } else {
+                text.chars().count() + 1
+            };
+            let width = end_column.saturating_sub(start.column).max(1);
+
+            // Keep tabs in the indentation, so the underline lines up with the text
+            let indent: String = text
+                .chars()
+                .take(start.column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let (marker, color) = if annotation.primary {
+                ('^', error_color)
+            } else {
+                ('-', label_color)
+            };
+            let underline = marker.to_string().repeat(width);
+
+            let _ = write!(
+                output,
+                "{blank_gutter} {gutter_color}|{reset} {indent}{color}{underline}"
+            );
+            if let Some(message) = annotation.message {
+                let _ = write!(output, " {}", message);
+            }
+            let _ = writeln!(output, "{reset}");
+        }
+    }
+}
+
+fn render_json(errors: &[&dyn Diagnostic], file_name: &str, source: &Source) -> String {
+    let mut output = String::from("[");
+    for (i, error) in errors.iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        output.push_str("{\"severity\":\"error\",\"message\":");
+        write_json_string(&mut output, &error.message());
+        output.push_str(",\"file\":");
+        write_json_string(&mut output, file_name);
+        write_json_span(&mut output, &error.span(), source);
+        output.push_str(",\"found\":");
+        match error.found() {
+            Some(found) => write_json_string(&mut output, found),
+            None => output.push_str("null"),
+        }
+        output.push_str(",\"expected\":[");
+        for (i, expected) in error.expected().iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+            write_json_string(&mut output, &expected.to_string());
+        }
+        output.push_str("],\"labels\":[");
+        for (i, label) in error.labels().iter().enumerate() {
+            if i > 0 {
+                output.push(',');
+            }
+            output.push_str("{\"message\":");
+            write_json_string(&mut output, label.message);
+            write_json_span(&mut output, &label.span, source);
+            output.push('}');
+        }
+        output.push_str("]}");
+    }
+    output.push(']');
+    output
+}
+
+/// Writes the `span`, `start` and `end` fields of a span.
+fn write_json_span(output: &mut String, span: &Range<usize>, source: &Source) {
+    let start = source.location(span.start);
+    let end = source.location(span.end);
+    let _ = write!(
+        output,
+        ",\"span\":{{\"start\":{},\"end\":{}}},\
+         \"start\":{{\"line\":{},\"column\":{}}},\
+         \"end\":{{\"line\":{},\"column\":{}}}",
+        span.start, span.end, start.line, start.column, end.line, end.column
+    );
+}
+
+fn write_json_string(output: &mut String, value: &str) {
+    output.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compile, parse, Options};
+
+    #[test]
+    fn test_render_plain() {
+        let source = "a: b;\n.x {\n  c: d;\n  1 2;\n}\n";
+        let errors = parse(source).unwrap_err();
+        assert_eq!(
+            render(&errors, "style.less", source, Format::Plain),
+            "error: found `;`, expected `{`\n \
+             --> style.less:4:6\n  \
+             |\n\
+             2 | .x {\n  \
+             |     - block\n\
+             4 |   1 2;\n  \
+             |      ^\n"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let source = "a: b;\nc";
+        let errors = parse(source).unwrap_err();
+        assert_eq!(
+            render(&errors, "style.less", source, Format::Json),
+            "[{\"severity\":\"error\",\"message\":\"found end of input, expected `{`\",\
+             \"file\":\"style.less\",\"span\":{\"start\":7,\"end\":7},\
+             \"start\":{\"line\":2,\"column\":2},\"end\":{\"line\":2,\"column\":2},\
+             \"found\":null,\"expected\":[\"`{`\"],\"labels\":[]}]"
+        );
+    }
+
+    #[test]
+    fn test_render_compile_error() {
+        let source = "@a: @b;\n@b: @a;\n.x { y: @a; }\n";
+        let error = compile(source, Options::default()).unwrap_err();
+        assert_eq!(
+            render_compile_error(&error, "style.less", source, Format::Plain),
+            "error: recursive variable definition: @a -> @b -> @a\n \
+             --> style.less:2:5\n  \
+             |\n\
+             1 | @a: @b;\n  \
+             | ------- in this definition\n\
+             2 | @b: @a;\n  \
+             |     ^^\n  \
+             | ------- in this definition\n"
+        );
+    }
+}
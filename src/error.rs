@@ -0,0 +1,380 @@
+use std::fmt;
+use std::ops::Range;
+
+use chumsky::error::{Rich, RichPattern, RichReason};
+
+use crate::lexer::{Span, Token, TokenTree};
+
+/// The errors produced by [`parse`](crate::parse), in source order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseErrors(Vec<ParseError>);
+
+impl ParseErrors {
+    pub(crate) fn from_rich<T, S>(errors: Vec<Rich<'_, T, S>>) -> Self
+    where
+        T: Describe,
+        S: chumsky::span::Span<Offset = usize>,
+    {
+        let mut errors: Vec<_> = errors.into_iter().map(ParseError::from_rich).collect();
+        errors.sort_by_key(|error| (error.span.start, error.span.end));
+        Self(errors)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ParseError> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl IntoIterator for ParseErrors {
+    type Item = ParseError;
+    type IntoIter = std::vec::IntoIter<ParseError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ParseErrors {
+    type Item = &'a ParseError;
+    type IntoIter = std::slice::Iter<'a, ParseError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseErrors {}
+
+/// A single syntax error, with byte offsets into the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    span: Range<usize>,
+    message: Option<String>,
+    found: Option<String>,
+    expected: Vec<Expected>,
+    labels: Vec<Label>,
+}
+
+impl ParseError {
+    fn from_rich<T, S>(error: Rich<'_, T, S>) -> Self
+    where
+        T: Describe,
+        S: chumsky::span::Span<Offset = usize>,
+    {
+        let message = match error.reason() {
+            RichReason::Custom(message) => Some(message.clone()),
+            _ => None,
+        };
+
+        let mut expected = Vec::new();
+        for pattern in error.expected() {
+            let pattern = match pattern {
+                RichPattern::Token(token) => Expected::Token(token.describe()),
+                RichPattern::Label(label) => Expected::Label(label),
+                RichPattern::EndOfInput => Expected::EndOfInput,
+            };
+            if !expected.contains(&pattern) {
+                expected.push(pattern);
+            }
+        }
+
+        let labels = error
+            .contexts()
+            .map(|(label, span)| Label {
+                message: label,
+                span: span.start()..span.end(),
+            })
+            .collect();
+
+        // Spans at the end of input start at the beginning of the input, so only keep their end
+        let span = error.span().start()..error.span().end();
+        let span = match error.found() {
+            Some(_) => span,
+            None => span.end..span.end,
+        };
+
+        Self {
+            span,
+            message,
+            found: error.found().map(Describe::describe),
+            expected,
+            labels,
+        }
+    }
+
+    /// The byte range in the source where the error occurred.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// A description of the input that was found, or `None` at the end of input.
+    pub fn found(&self) -> Option<&str> {
+        self.found.as_deref()
+    }
+
+    /// The inputs that would have been valid instead.
+    pub fn expected(&self) -> &[Expected] {
+        &self.expected
+    }
+
+    /// The constructs that were being parsed when the error occurred, innermost first.
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// Describes the error (e.g. ``found `;`, expected `{` ``), without its location.
+    pub fn message(&self) -> String {
+        if let Some(message) = &self.message {
+            return message.clone();
+        }
+
+        let mut message = format!("found {}", self.found.as_deref().unwrap_or("end of input"));
+        for (i, expected) in self.expected.iter().enumerate() {
+            let separator = match i {
+                0 => ", expected ",
+                i if i == self.expected.len() - 1 => " or ",
+                _ => ", ",
+            };
+            message.push_str(separator);
+            message.push_str(&expected.to_string());
+        }
+        message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message(),
+            self.span.start,
+            self.span.end
+        )?;
+        for label in &self.labels {
+            write!(
+                f,
+                " (in {} at {}..{})",
+                label.message, label.span.start, label.span.end
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The error produced by [`compile`](crate::compile).
+#[derive(Clone, Debug, PartialEq)]
+pub enum CompileError {
+    Parse(ParseErrors),
+    Eval(EvalError),
+}
+
+impl From<ParseErrors> for CompileError {
+    fn from(errors: ParseErrors) -> Self {
+        CompileError::Parse(errors)
+    }
+}
+
+impl From<EvalError> for CompileError {
+    fn from(error: EvalError) -> Self {
+        CompileError::Eval(error)
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Parse(errors) => write!(f, "{}", errors),
+            CompileError::Eval(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// An error while evaluating a well-formed stylesheet (e.g. an undefined variable), with byte
+/// offsets into the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalError {
+    span: Range<usize>,
+    message: String,
+    labels: Vec<Label>,
+}
+
+impl EvalError {
+    pub(crate) fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span: span.into_range(),
+            message: message.into(),
+            labels: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_label(mut self, message: &'static str, span: Span) -> Self {
+        self.labels.push(Label {
+            message,
+            span: span.into_range(),
+        });
+        self
+    }
+
+    /// The byte range in the source where the error occurred.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Other parts of the source that are involved in the error.
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )?;
+        for label in &self.labels {
+            write!(
+                f,
+                " ({} at {}..{})",
+                label.message, label.span.start, label.span.end
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// An input that would have been valid where a [`ParseError`] occurred.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expected {
+    /// A specific token (e.g. `` `;` ``)
+    Token(String),
+    /// A labelled construct (e.g. `declaration`)
+    Label(&'static str),
+    EndOfInput,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Token(token) => write!(f, "{}", token),
+            Expected::Label(label) => write!(f, "{}", label),
+            Expected::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+/// A construct that was being parsed when a [`ParseError`] occurred (e.g. a `block`), or another
+/// part of the source involved in an [`EvalError`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    pub message: &'static str,
+    pub span: Range<usize>,
+}
+
+/// Describes a token for use in error messages.
+pub(crate) trait Describe {
+    fn describe(&self) -> String;
+}
+
+impl Describe for char {
+    fn describe(&self) -> String {
+        format!("`{}`", self)
+    }
+}
+
+impl Describe for TokenTree<&str> {
+    fn describe(&self) -> String {
+        match self {
+            TokenTree::Token(Token::Whitespace) => "whitespace".to_string(),
+            TokenTree::Token(Token::Comment(_)) => "comment".to_string(),
+            TokenTree::Token(Token::Ident(ident)) => format!("`{}`", ident),
+            TokenTree::Token(Token::Hash(hash)) => format!("`#{}`", hash),
+            TokenTree::Token(Token::String(_)) => "string".to_string(),
+            TokenTree::Token(Token::Url(_)) => "url".to_string(),
+            TokenTree::Token(Token::Interpolation(name)) => format!("`@{{{}}}`", name),
+            TokenTree::Token(Token::Number(number)) => format!("`{}`", number),
+            TokenTree::Token(Token::Symbol(symbol)) => format!("`{}`", symbol),
+            TokenTree::Tree(delim, _) => format!("`{}`", delim.open()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_parse_errors() {
+        let input = ".x { a: b; 1 2; c: d; } @ ;";
+        let errors = parse(input).unwrap_err();
+        assert_eq!(
+            errors.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                ParseError {
+                    span: 14..15,
+                    message: None,
+                    found: Some("`;`".to_string()),
+                    expected: vec![Expected::Token("`{`".to_string())],
+                    labels: vec![Label {
+                        message: "block",
+                        span: 4..15,
+                    }],
+                },
+                ParseError {
+                    span: 26..27,
+                    message: None,
+                    found: Some("`;`".to_string()),
+                    expected: vec![Expected::Token("`{`".to_string())],
+                    labels: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            errors.to_string(),
+            "found `;`, expected `{` at 14..15 (in block at 4..15)\n\
+             found `;`, expected `{` at 26..27"
+        );
+
+        // Errors at the end of input point at the end of the last token
+        let input = "a: b; c";
+        let errors = parse(input).unwrap_err();
+        assert_eq!(
+            errors.iter().map(ParseError::span).collect::<Vec<_>>(),
+            vec![7..7]
+        );
+        assert_eq!(errors.iter().next().unwrap().found(), None);
+    }
+}
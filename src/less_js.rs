@@ -0,0 +1,1192 @@
+//! Conversion of the AST into the JSON shape of the Less.js AST (as produced by `parse-less.js`),
+//! to compare the output of both parsers.
+//!
+//! Less.js parses values into expressions, while this crate keeps them as component values. These
+//! are converted into the closest Less.js nodes (e.g. `Dimension`, `Variable`, `Call` and
+//! `Operation`), falling back to an `Anonymous` node with the text of the value.
+
+use serde_json::{json, Value};
+
+use crate::ast::*;
+use crate::lexer::{Delim, Spanned, Token, TokenTree};
+
+pub trait ToLessJsAst {
+    fn to_less_js_ast(&self) -> Value;
+}
+
+impl<S: AsRef<str>> ToLessJsAst for Stylesheet<S> {
+    fn to_less_js_ast(&self) -> Value {
+        json!({
+            "type": "Ruleset",
+            "firstRoot": true,
+            "root": true,
+            "selectors": null,
+            "rules": rules(&self.items),
+        })
+    }
+}
+
+fn rules<S: AsRef<str>>(items: &ListOfItems<S>) -> Vec<Value> {
+    let mut rules = Vec::new();
+    for (item, _) in &items.0 {
+        match item {
+            Item::AtRule(at_rule) => rules.push(at_rule_node(at_rule)),
+            Item::QualifiedRule(rule) => rules.push(qualified_rule_node(rule)),
+            Item::Declaration(declaration) => rules.push(declaration_node(declaration)),
+            Item::Call(call) => rules.push(call_node(call)),
+            // Less.js has an `Extend` node per target
+            Item::Extend(extend) => rules.extend(extend_nodes(extend)),
+        }
+    }
+    rules
+}
+
+fn ruleset<S: AsRef<str>>(selectors: Value, items: &ListOfItems<S>) -> Value {
+    json!({
+        "type": "Ruleset",
+        "selectors": selectors,
+        "rules": rules(items),
+    })
+}
+
+// AT-RULES
+
+fn at_rule_node<S: AsRef<str>>(at_rule: &AtRule<S>) -> Value {
+    match at_rule {
+        AtRule::Generic(rule) if rule.name.as_ref() == "media" => json!({
+            "type": "Media",
+            "features": value_node(&rule.prelude.0),
+            "rules": rule.block.as_ref().map(|block| vec![ruleset(Value::Null, block)]),
+        }),
+        AtRule::Generic(rule) => at_rule_json(
+            rule.name.as_ref(),
+            prelude_node(&tokens_text(&rule.prelude.0)),
+            rule.block.as_ref(),
+        ),
+        AtRule::Keyframes(rule) => {
+            // Less.js parses the name as a single entity
+            let name = match &rule.name {
+                KeyframesName::Ident(name) => keyword(name.as_ref()),
+                KeyframesName::String(name) => quoted(name.as_ref(), false),
+                KeyframesName::Variable(name) => variable(name.as_ref()),
+                KeyframesName::InterpolatedIdent(ident) => anonymous(&interpolated_text(&ident.0)),
+            };
+            let at_keyword = match &rule.vendor_prefix {
+                Some(prefix) => format!("{}keyframes", prefix.as_ref()),
+                None => "keyframes".to_string(),
+            };
+            at_rule_json(&at_keyword, name, Some(&rule.block))
+        }
+        AtRule::Import(rule) => {
+            let options: serde_json::Map<_, _> = rule
+                .options
+                .iter()
+                .map(|(option, _)| {
+                    let option = match option {
+                        ImportOption::Reference => "reference",
+                        ImportOption::Inline => "inline",
+                        ImportOption::Less => "less",
+                        ImportOption::Css => "css",
+                        ImportOption::Once => "once",
+                        ImportOption::Multiple => "multiple",
+                        ImportOption::Optional => "optional",
+                    };
+                    (option.to_string(), Value::Bool(true))
+                })
+                .collect();
+            let features = if rule.media_queries.0.is_empty() {
+                Value::Null
+            } else {
+                value_node(&rule.media_queries.0)
+            };
+            json!({
+                "type": "Import",
+                "path": url_or_string_node(&rule.url.0),
+                "features": features,
+                "options": options,
+            })
+        }
+        AtRule::Supports(rule) => {
+            let mut prelude = String::new();
+            write_condition(&mut prelude, &rule.condition.0, &mut |out, feature| {
+                write_supports_feature(out, feature)
+            });
+            at_rule_json("supports", prelude_node(&prelude), Some(&rule.block))
+        }
+        AtRule::Container(rule) => {
+            let mut prelude = String::new();
+            if let Some(name) = &rule.name {
+                prelude.push_str(name.as_ref());
+            }
+            if let Some((condition, _)) = &rule.condition {
+                if !prelude.is_empty() {
+                    prelude.push(' ');
+                }
+                write_condition(&mut prelude, condition, &mut |out, feature| match feature {
+                    ContainerFeature::Size(values) => {
+                        out.push('(');
+                        out.push_str(&tokens_text(&values.0));
+                        out.push(')');
+                    }
+                    ContainerFeature::Function(function) => write_function(out, function),
+                });
+            }
+            at_rule_json("container", prelude_node(&prelude), Some(&rule.block))
+        }
+        AtRule::Layer(rule) => {
+            let names: Vec<_> = rule
+                .names
+                .iter()
+                .map(|(name, _)| {
+                    name.0
+                        .iter()
+                        .map(|(part, _)| part.as_ref())
+                        .collect::<Vec<_>>()
+                        .join(".")
+                })
+                .collect();
+            at_rule_json(
+                "layer",
+                prelude_node(&names.join(", ")),
+                rule.block.as_ref(),
+            )
+        }
+        AtRule::FontFace(rule) => at_rule_json("font-face", Value::Null, Some(&rule.block)),
+        AtRule::Page(rule) => {
+            let selectors: Vec<_> = rule
+                .selectors
+                .iter()
+                .map(|(selector, _)| {
+                    let mut text = selector
+                        .name
+                        .as_ref()
+                        .map_or(String::new(), |name| name.as_ref().to_string());
+                    for (pseudo_class, _) in &selector.pseudo_classes {
+                        text.push(':');
+                        text.push_str(pseudo_class.as_ref());
+                    }
+                    text
+                })
+                .collect();
+            at_rule_json(
+                "page",
+                prelude_node(&selectors.join(", ")),
+                Some(&rule.block),
+            )
+        }
+        AtRule::PageMargin(rule) => {
+            at_rule_json(rule.name.as_ref(), Value::Null, Some(&rule.block))
+        }
+        // Less.js parses the prelude as an expression
+        AtRule::Namespace(rule) => {
+            let mut value = Vec::new();
+            if let Some(prefix) = &rule.prefix {
+                value.push(keyword(prefix.as_ref()));
+            }
+            value.push(url_or_string_node(&rule.url.0));
+            let value = json!({ "type": "Expression", "value": value });
+            at_rule_json::<S>("namespace", value, None)
+        }
+        AtRule::Property(rule) => at_rule_json(
+            "property",
+            prelude_node(rule.name.as_ref()),
+            Some(&rule.block),
+        ),
+    }
+}
+
+fn at_rule_json<S: AsRef<str>>(name: &str, value: Value, block: Option<&ListOfItems<S>>) -> Value {
+    json!({
+        "type": "AtRule",
+        "name": format!("@{}", name),
+        "value": value,
+        "rules": block.map(|block| vec![ruleset(Value::Null, block)]),
+    })
+}
+
+/// Converts a prelude that Less.js keeps as text.
+fn prelude_node(text: &str) -> Value {
+    if text.is_empty() {
+        Value::Null
+    } else {
+        anonymous(text)
+    }
+}
+
+fn url_or_string_node<S: AsRef<str>>(url: &UrlOrString<S>) -> Value {
+    match url {
+        UrlOrString::String(string) => quoted(&interpolated_text(&string.0), false),
+        UrlOrString::Url(url) => json!({
+            "type": "Url",
+            "value": quoted(&interpolated_text(&url.0), false),
+        }),
+    }
+}
+
+// QUALIFIED RULES
+
+fn qualified_rule_node<S: AsRef<str>>(rule: &QualifiedRule<S>) -> Value {
+    match rule {
+        QualifiedRule::Generic(rule) => ruleset(
+            json!([selector_json(vec![element(
+                json!(tokens_text(&rule.prelude.0)),
+                "",
+                false
+            )])]),
+            &rule.block,
+        ),
+        QualifiedRule::Style(rule) => ruleset(selector_list_node(&rule.selectors), &rule.block),
+        QualifiedRule::Mixin(rule) => {
+            let params = mixin_params(&rule.arguments.0);
+            let required = params
+                .iter()
+                .filter(|param| param.get("name").is_some() && param.get("value").is_none())
+                .count();
+            let variadic = params
+                .iter()
+                .any(|param| param.get("variadic") == Some(&Value::Bool(true)));
+            json!({
+                "type": "MixinDefinition",
+                "name": rule.name.as_ref(),
+                "selectors": [selector_json(vec![element(json!(rule.name.as_ref()), "", false)])],
+                "params": params,
+                "variadic": variadic,
+                "arity": params.len(),
+                "required": required,
+                "rules": rules(&rule.block),
+            })
+        }
+        QualifiedRule::Keyframe(rule) => {
+            let selectors: Vec<_> = rule
+                .selectors
+                .iter()
+                .map(|(selector, _)| {
+                    let value = match selector {
+                        KeyframeSelector::From => "from".to_string(),
+                        KeyframeSelector::To => "to".to_string(),
+                        KeyframeSelector::Percentage(percentage) => {
+                            format!("{}%", percentage.as_ref())
+                        }
+                    };
+                    selector_json(vec![element(json!(value), "", false)])
+                })
+                .collect();
+            ruleset(json!(selectors), &rule.block)
+        }
+    }
+}
+
+/// Converts the parameters of a mixin definition (e.g. `@a; @b: 2; ...`).
+fn mixin_params<S: AsRef<str>>(tts: &[Spanned<TokenTree<S>>]) -> Vec<Value> {
+    let separator = if tts.iter().any(|(tt, _)| is_symbol(tt, ';')) {
+        ';'
+    } else {
+        ','
+    };
+    split(tts, separator)
+        .into_iter()
+        .map(trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            if param.len() == 3 && param.iter().all(|(tt, _)| is_symbol(tt, '.')) {
+                return json!({ "variadic": true });
+            }
+            let name = match param {
+                [(at, _), (TokenTree::Token(Token::Ident(name)), _), ..] if is_symbol(at, '@') => {
+                    format!("@{}", name.as_ref())
+                }
+                _ => return json!({ "value": expression_node(param) }),
+            };
+            match trim(&param[2..]) {
+                [] => json!({ "name": name }),
+                [(colon, _), value @ ..] if is_symbol(colon, ':') => {
+                    json!({ "name": name, "value": expression_node(trim(value)) })
+                }
+                // A rest parameter (e.g. `@rest...`)
+                _ => json!({ "name": name, "variadic": true }),
+            }
+        })
+        .collect()
+}
+
+// SELECTORS
+
+fn selector_list_node<S: AsRef<str>>(selectors: &SelectorList<S>) -> Value {
+    json!(selectors
+        .0
+        .iter()
+        .map(|(selector, _)| selector_node(selector))
+        .collect::<Vec<_>>())
+}
+
+fn selector_node<S: AsRef<str>>(selector: &Selector<S>) -> Value {
+    let mut elements = Vec::new();
+    let mut combinator = "";
+    for (component, _) in &selector.components {
+        let (value, is_variable) = match component {
+            SelectorComponent::Combinator(next) => {
+                combinator = match next {
+                    Combinator::Descendant => " ",
+                    Combinator::Child => ">",
+                    Combinator::NextSibling => "+",
+                    Combinator::SubsequentSibling => "~",
+                };
+                continue;
+            }
+            SelectorComponent::Parent => (json!("&"), false),
+            SelectorComponent::Universal => (json!("*"), false),
+            SelectorComponent::Type(name) => {
+                (json!(selector_name_text(name)), is_interpolated(name))
+            }
+            SelectorComponent::Class(name) => (
+                json!(format!(".{}", selector_name_text(name))),
+                is_interpolated(name),
+            ),
+            SelectorComponent::Id(name) => (
+                json!(format!("#{}", selector_name_text(name))),
+                is_interpolated(name),
+            ),
+            SelectorComponent::Attribute { name, matcher } => {
+                let (op, value, cif) = match matcher {
+                    Some(matcher) => {
+                        let value = match &matcher.value {
+                            AttributeValue::Ident(name) => json!(selector_name_text(name)),
+                            AttributeValue::String(string) => {
+                                quoted(&interpolated_text(&string.0), false)
+                            }
+                        };
+                        let cif = matcher.modifier.as_ref().map(|modifier| modifier.as_ref());
+                        (
+                            json!(attribute_operator(matcher.operator)),
+                            value,
+                            json!(cif),
+                        )
+                    }
+                    None => (Value::Null, Value::Null, Value::Null),
+                };
+                let attribute = json!({
+                    "type": "Attribute",
+                    "key": selector_name_text(name),
+                    "op": op,
+                    "value": value,
+                    "cif": cif,
+                });
+                (attribute, is_interpolated(name))
+            }
+            SelectorComponent::PseudoClass { name, arguments }
+            | SelectorComponent::PseudoElement { name, arguments } => {
+                let colons = match component {
+                    SelectorComponent::PseudoElement { .. } => "::",
+                    _ => ":",
+                };
+                let mut value = format!("{}{}", colons, name.as_ref());
+                if let Some(arguments) = arguments {
+                    value.push('(');
+                    value.push_str(&tokens_text(&arguments.0));
+                    value.push(')');
+                }
+                (json!(value), false)
+            }
+        };
+        elements.push(element(value, combinator, is_variable));
+        combinator = "";
+    }
+
+    let mut node = selector_json(elements);
+    if !selector.extends.is_empty() {
+        let extend_list: Vec<_> = selector
+            .extends
+            .iter()
+            .flat_map(|(extend, _)| extend_nodes(extend))
+            .collect();
+        node["extendList"] = json!(extend_list);
+    }
+    node
+}
+
+fn selector_json(elements: Vec<Value>) -> Value {
+    json!({
+        "type": "Selector",
+        "elements": elements,
+        "evaldCondition": true,
+    })
+}
+
+fn element(value: Value, combinator: &str, is_variable: bool) -> Value {
+    json!({
+        "type": "Element",
+        "value": value,
+        "combinator": {
+            "type": "Combinator",
+            "value": combinator,
+            "emptyOrWhitespace": combinator.is_empty() || combinator == " ",
+        },
+        "isVariable": is_variable,
+    })
+}
+
+fn selector_name_text<S: AsRef<str>>(name: &SelectorName<S>) -> String {
+    match name {
+        SelectorName::Ident(name) => name.as_ref().to_string(),
+        SelectorName::InterpolatedIdent(ident) => interpolated_text(&ident.0),
+    }
+}
+
+fn is_interpolated<S>(name: &SelectorName<S>) -> bool {
+    matches!(name, SelectorName::InterpolatedIdent(_))
+}
+
+fn attribute_operator(operator: AttributeOperator) -> &'static str {
+    match operator {
+        AttributeOperator::Equals => "=",
+        AttributeOperator::Includes => "~=",
+        AttributeOperator::DashMatch => "|=",
+        AttributeOperator::Prefix => "^=",
+        AttributeOperator::Suffix => "$=",
+        AttributeOperator::Substring => "*=",
+    }
+}
+
+// EXTENDS
+
+fn extend_nodes<S: AsRef<str>>(extend: &Extend<S>) -> Vec<Value> {
+    extend
+        .targets
+        .iter()
+        .map(|(target, _)| {
+            json!({
+                "type": "Extend",
+                "selector": selector_node(&target.selector),
+                "option": if target.all { Some("all") } else { None },
+            })
+        })
+        .collect()
+}
+
+// DECLARATIONS
+
+fn declaration_node<S: AsRef<str>>(declaration: &Declaration<S>) -> Value {
+    let value = match &declaration.value {
+        crate::ast::Value::ComponentValues(values) => value_node(&values.0),
+        crate::ast::Value::DetachedRuleset(items) => detached_ruleset(items),
+    };
+    let important = if declaration.important {
+        "!important"
+    } else {
+        ""
+    };
+
+    let mut node = json!({
+        "type": "Declaration",
+        "value": value,
+        "important": important,
+        "inline": false,
+        "merge": false,
+    });
+    node["name"] = match &declaration.name {
+        DeclarationName::Ident(name) => json!([keyword(name.as_ref())]),
+        DeclarationName::InterpolatedIdent(ident) => json!(ident
+            .0
+            .iter()
+            .map(|(part, _)| match part {
+                InterpolationPart::Literal(literal) => keyword(literal.as_ref()),
+                InterpolationPart::Variable(name) => variable(name.as_ref()),
+            })
+            .collect::<Vec<_>>()),
+        DeclarationName::Variable(name) => {
+            node["variable"] = Value::Bool(true);
+            json!(format!("@{}", name.as_ref()))
+        }
+    };
+    node
+}
+
+fn detached_ruleset<S: AsRef<str>>(items: &ListOfItems<S>) -> Value {
+    json!({
+        "type": "DetachedRuleset",
+        "ruleset": ruleset(Value::Null, items),
+    })
+}
+
+// CALLS
+
+fn call_node<S: AsRef<str>>(call: &Call<S>) -> Value {
+    match call {
+        Call::Mixin(call) => {
+            // The selector is a sequence of classes and ids (e.g. `#namespace > .mixin`), in
+            // which Less.js drops the combinators
+            let elements: Vec<_> = call
+                .selector
+                .0
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (tt, _))| match tt {
+                    TokenTree::Token(Token::Hash(name)) => Some(format!("#{}", name.as_ref())),
+                    TokenTree::Token(Token::Ident(name))
+                        if i > 0 && is_symbol(&call.selector.0[i - 1].0, '.') =>
+                    {
+                        Some(format!(".{}", name.as_ref()))
+                    }
+                    _ => None,
+                })
+                .map(|value| element(json!(value), "", false))
+                .collect();
+            let arguments: Vec<_> = call
+                .arguments
+                .iter()
+                .map(|(argument, _)| {
+                    let value = match &argument.value {
+                        crate::ast::Value::ComponentValues(values) => {
+                            expression_node(trim(&values.0))
+                        }
+                        crate::ast::Value::DetachedRuleset(items) => detached_ruleset(items),
+                    };
+                    match &argument.name {
+                        Some(name) => {
+                            json!({ "name": format!("@{}", name.as_ref()), "value": value })
+                        }
+                        None => json!({ "value": value }),
+                    }
+                })
+                .collect();
+            json!({
+                "type": "MixinCall",
+                "selector": selector_json(elements),
+                "arguments": arguments,
+                "important": call.important,
+            })
+        }
+        Call::Variable(call) => json!({
+            "type": "VariableCall",
+            "variable": format!("@{}", call.name.as_ref()),
+        }),
+        Call::Function(call) => {
+            let args: Vec<_> = call
+                .arguments
+                .iter()
+                .map(|(argument, _)| match argument {
+                    crate::ast::Value::ComponentValues(values) => expression_node(trim(&values.0)),
+                    crate::ast::Value::DetachedRuleset(items) => detached_ruleset(items),
+                })
+                .collect();
+            json!({
+                "type": "Call",
+                "name": call.name.as_ref(),
+                "args": args,
+                "calc": call.name.as_ref() == "calc",
+            })
+        }
+    }
+}
+
+fn call_json<S: AsRef<str>>(name: &str, arguments: &[Spanned<TokenTree<S>>]) -> Value {
+    let args: Vec<_> = split(arguments, ',')
+        .into_iter()
+        .map(trim)
+        .filter(|argument| !argument.is_empty())
+        .map(expression_node)
+        .collect();
+    json!({
+        "type": "Call",
+        "name": name,
+        "args": args,
+        "calc": name == "calc",
+    })
+}
+
+// VALUES
+
+/// Converts a comma-separated list of expressions into a `Value` node.
+fn value_node<S: AsRef<str>>(tts: &[Spanned<TokenTree<S>>]) -> Value {
+    let expressions: Vec<_> = split(trim(tts), ',')
+        .into_iter()
+        .map(|expression| expression_node(trim(expression)))
+        .collect();
+    json!({ "type": "Value", "value": expressions })
+}
+
+/// Converts a space-separated list of operands into an `Expression` node.
+fn expression_node<S: AsRef<str>>(tts: &[Spanned<TokenTree<S>>]) -> Value {
+    let mut operands = Vec::new();
+    let mut operators = Vec::new();
+    let mut tts = tts;
+    while let Some((node, rest)) = operand(tts) {
+        operands.push(node);
+        tts = rest;
+
+        // Keep binary operations between consecutive operands (e.g. `@a + 2`)
+        let rest = skip_junk(tts);
+        if let Some(((TokenTree::Token(Token::Symbol(op @ ('+' | '-' | '*' | '/'))), _), rest)) =
+            rest.split_first()
+        {
+            if operand(skip_junk(rest)).is_some() {
+                operators.push((operands.len(), *op));
+                tts = rest;
+            }
+        }
+    }
+
+    // Fold the operations, with `*` and `/` binding tighter than `+` and `-`
+    for ops in [['*', '/'], ['+', '-']] {
+        let mut i = 0;
+        while i < operators.len() {
+            let (index, op) = operators[i];
+            if !ops.contains(&op) {
+                i += 1;
+                continue;
+            }
+            let rhs = operands.remove(index);
+            let lhs = operands.remove(index - 1);
+            operands.insert(
+                index - 1,
+                json!({ "type": "Operation", "op": op.to_string(), "operands": [lhs, rhs] }),
+            );
+            operators.remove(i);
+            for (later, _) in &mut operators[i..] {
+                *later -= 1;
+            }
+        }
+    }
+
+    json!({ "type": "Expression", "value": operands })
+}
+
+/// Converts the operand at the start of the tokens, returning the remaining tokens.
+fn operand<S: AsRef<str>>(
+    tts: &[Spanned<TokenTree<S>>],
+) -> Option<(Value, &[Spanned<TokenTree<S>>])> {
+    let tts = skip_junk(tts);
+    let ((first, _), rest) = tts.split_first()?;
+    let next = rest.first().map(|(tt, _)| tt);
+
+    let result = match (first, next) {
+        (TokenTree::Token(Token::Number(number)), Some(TokenTree::Token(Token::Ident(unit)))) => {
+            (dimension(number.as_ref(), Some(unit.as_ref())), &rest[1..])
+        }
+        (TokenTree::Token(Token::Number(number)), Some(tt)) if is_symbol(tt, '%') => {
+            (dimension(number.as_ref(), Some("%")), &rest[1..])
+        }
+        (TokenTree::Token(Token::Number(number)), _) => (dimension(number.as_ref(), None), rest),
+        (TokenTree::Token(Token::Symbol('@')), Some(TokenTree::Token(Token::Ident(name)))) => {
+            (variable(name.as_ref()), &rest[1..])
+        }
+        (TokenTree::Token(Token::Symbol('@')), Some(tt)) if is_symbol(tt, '@') => {
+            match &rest[1..] {
+                [(TokenTree::Token(Token::Ident(name)), _), rest @ ..] => {
+                    (variable(&format!("@{}", name.as_ref())), rest)
+                }
+                _ => (anonymous("@@"), &rest[1..]),
+            }
+        }
+        (TokenTree::Token(Token::Symbol('~')), Some(TokenTree::Token(Token::String(string)))) => {
+            (quoted(string.as_ref(), true), &rest[1..])
+        }
+        (TokenTree::Token(Token::Ident(name)), Some(TokenTree::Tree(Delim::Paren, arguments))) => {
+            (call_json(name.as_ref(), arguments), &rest[1..])
+        }
+        (TokenTree::Token(Token::Ident(name)), _) => (keyword(name.as_ref()), rest),
+        (TokenTree::Token(Token::Hash(hash)), _) => (color(hash.as_ref()), rest),
+        (TokenTree::Token(Token::String(string)), _) => (quoted(string.as_ref(), false), rest),
+        (TokenTree::Token(Token::Url(url)), _) => (
+            json!({ "type": "Url", "value": anonymous(url.as_ref().trim()) }),
+            rest,
+        ),
+        // Less.js drops the parentheses around sub-expressions
+        (TokenTree::Tree(Delim::Paren, tts), _) => (expression_node(trim(tts)), rest),
+        _ => (anonymous(&tokens_text(&tts[..1])), rest),
+    };
+    Some(result)
+}
+
+fn keyword(value: &str) -> Value {
+    json!({ "type": "Keyword", "value": value })
+}
+
+fn variable(name: &str) -> Value {
+    json!({ "type": "Variable", "name": format!("@{}", name) })
+}
+
+fn anonymous(value: &str) -> Value {
+    json!({ "type": "Anonymous", "value": value })
+}
+
+fn quoted(value: &str, escaped: bool) -> Value {
+    json!({ "type": "Quoted", "escaped": escaped, "value": value })
+}
+
+fn dimension(value: &str, unit: Option<&str>) -> Value {
+    let value: f64 = value.parse().unwrap_or_default();
+    let unit = match unit {
+        Some(unit) => json!({
+            "type": "Unit",
+            "numerator": [unit],
+            "denominator": [],
+            "backupUnit": unit,
+        }),
+        None => json!({ "type": "Unit", "numerator": [], "denominator": [] }),
+    };
+    json!({ "type": "Dimension", "value": value, "unit": unit })
+}
+
+/// Converts a hex color (e.g. `0ff` or `00ffff80`), or a hash that isn't a color.
+fn color(hash: &str) -> Value {
+    let digits: Option<Vec<u8>> = hash
+        .chars()
+        .map(|c| c.to_digit(16).map(|digit| digit as u8))
+        .collect();
+    let channels: Vec<u8> = match (digits, hash.len()) {
+        (Some(digits), 3 | 4) => digits.iter().map(|digit| digit * 17).collect(),
+        (Some(digits), 6 | 8) => digits
+            .chunks(2)
+            .map(|pair| pair[0] * 16 + pair[1])
+            .collect(),
+        _ => return anonymous(&format!("#{}", hash)),
+    };
+    let (rgb, alpha) = match channels[..] {
+        [r, g, b] => ([r, g, b], 1.0),
+        [r, g, b, a] => ([r, g, b], a as f64 / 255.0),
+        _ => unreachable!(),
+    };
+    json!({
+        "type": "Color",
+        "rgb": rgb,
+        "alpha": alpha,
+        "value": format!("#{}", hash),
+    })
+}
+
+// TEXT
+
+/// Writes token trees as text, collapsing whitespace and dropping comments.
+fn tokens_text<S: AsRef<str>>(tts: &[Spanned<TokenTree<S>>]) -> String {
+    let mut text = String::new();
+    write_tokens(&mut text, trim(tts));
+    text
+}
+
+fn write_tokens<S: AsRef<str>>(out: &mut String, tts: &[Spanned<TokenTree<S>>]) {
+    for (tt, _) in tts {
+        match tt {
+            TokenTree::Token(Token::Whitespace) => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            TokenTree::Token(Token::Comment(_)) => {}
+            TokenTree::Token(Token::Ident(ident)) => out.push_str(ident.as_ref()),
+            TokenTree::Token(Token::Hash(hash)) => {
+                out.push('#');
+                out.push_str(hash.as_ref());
+            }
+            TokenTree::Token(Token::String(string)) => {
+                out.push('"');
+                out.push_str(string.as_ref());
+                out.push('"');
+            }
+            TokenTree::Token(Token::Url(url)) => {
+                out.push_str("url(");
+                out.push_str(url.as_ref());
+                out.push(')');
+            }
+            TokenTree::Token(Token::Interpolation(name)) => {
+                out.push_str("@{");
+                out.push_str(name.as_ref());
+                out.push('}');
+            }
+            TokenTree::Token(Token::Number(number)) => out.push_str(number.as_ref()),
+            TokenTree::Token(Token::Symbol(symbol)) => out.push(*symbol),
+            TokenTree::Tree(delim, tts) => {
+                out.push(delim.open());
+                write_tokens(out, trim(tts));
+                out.push(delim.close());
+            }
+        }
+    }
+}
+
+fn interpolated_text<S: AsRef<str>>(parts: &[Spanned<InterpolationPart<S>>]) -> String {
+    let mut text = String::new();
+    for (part, _) in parts {
+        match part {
+            InterpolationPart::Literal(literal) => text.push_str(literal.as_ref()),
+            InterpolationPart::Variable(name) => {
+                text.push_str("@{");
+                text.push_str(name.as_ref());
+                text.push('}');
+            }
+        }
+    }
+    text
+}
+
+fn write_condition<T>(
+    out: &mut String,
+    condition: &Condition<T>,
+    write_feature: &mut impl FnMut(&mut String, &T),
+) {
+    match condition {
+        Condition::Not(condition) => {
+            out.push_str("not ");
+            write_nested_condition(out, &condition.0, write_feature);
+        }
+        Condition::And(conditions) | Condition::Or(conditions) => {
+            let separator = match condition {
+                Condition::And(_) => " and ",
+                _ => " or ",
+            };
+            for (i, (condition, _)) in conditions.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(separator);
+                }
+                write_nested_condition(out, condition, write_feature);
+            }
+        }
+        Condition::Feature(feature) => write_feature(out, feature),
+    }
+}
+
+/// Writes a sub-condition, adding the parentheses implied by the structure.
+fn write_nested_condition<T>(
+    out: &mut String,
+    condition: &Condition<T>,
+    write_feature: &mut impl FnMut(&mut String, &T),
+) {
+    if let Condition::Feature(_) = condition {
+        write_condition(out, condition, write_feature);
+    } else {
+        out.push('(');
+        write_condition(out, condition, write_feature);
+        out.push(')');
+    }
+}
+
+fn write_supports_feature<S: AsRef<str>>(out: &mut String, feature: &SupportsFeature<S>) {
+    match feature {
+        SupportsFeature::Declaration(declaration) => {
+            out.push('(');
+            match &declaration.name {
+                DeclarationName::Ident(name) => out.push_str(name.as_ref()),
+                DeclarationName::InterpolatedIdent(ident) => {
+                    out.push_str(&interpolated_text(&ident.0))
+                }
+                DeclarationName::Variable(name) => {
+                    out.push('@');
+                    out.push_str(name.as_ref());
+                }
+            }
+            out.push_str(": ");
+            if let crate::ast::Value::ComponentValues(values) = &declaration.value {
+                out.push_str(&tokens_text(&values.0));
+            }
+            if declaration.important {
+                out.push_str(" !important");
+            }
+            out.push(')');
+        }
+        SupportsFeature::Function(function) => write_function(out, function),
+        SupportsFeature::GeneralEnclosed(values) => {
+            out.push('(');
+            out.push_str(&tokens_text(&values.0));
+            out.push(')');
+        }
+    }
+}
+
+fn write_function<S: AsRef<str>>(out: &mut String, function: &FunctionCall<S>) {
+    out.push_str(function.name.as_ref());
+    out.push('(');
+    out.push_str(&tokens_text(&function.arguments.0));
+    out.push(')');
+}
+
+// TOKENS
+
+fn is_symbol<S>(tt: &TokenTree<S>, symbol: char) -> bool {
+    matches!(tt, TokenTree::Token(Token::Symbol(s)) if *s == symbol)
+}
+
+fn is_junk<S>(tt: &TokenTree<S>) -> bool {
+    matches!(tt, TokenTree::Token(Token::Whitespace | Token::Comment(_)))
+}
+
+fn skip_junk<S>(tts: &[Spanned<TokenTree<S>>]) -> &[Spanned<TokenTree<S>>] {
+    let start = tts
+        .iter()
+        .position(|(tt, _)| !is_junk(tt))
+        .unwrap_or(tts.len());
+    &tts[start..]
+}
+
+fn trim<S>(tts: &[Spanned<TokenTree<S>>]) -> &[Spanned<TokenTree<S>>] {
+    let tts = skip_junk(tts);
+    let end = tts
+        .iter()
+        .rposition(|(tt, _)| !is_junk(tt))
+        .map_or(0, |i| i + 1);
+    &tts[..end]
+}
+
+/// Splits token trees on a top-level symbol.
+fn split<S>(tts: &[Spanned<TokenTree<S>>], separator: char) -> Vec<&[Spanned<TokenTree<S>>]> {
+    tts.split(|(tt, _)| is_symbol(tt, separator)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_to_less_js_ast() {
+        let input = "@w: 10px; .a > .b:hover { width: @w * 2 + 1; color: #0f0 !important; }";
+        let stylesheet = parse(input).unwrap();
+        assert_eq!(
+            stylesheet.to_less_js_ast(),
+            json!({
+                "type": "Ruleset",
+                "firstRoot": true,
+                "root": true,
+                "selectors": null,
+                "rules": [
+                    {
+                        "type": "Declaration",
+                        "name": "@w",
+                        "variable": true,
+                        "value": {
+                            "type": "Value",
+                            "value": [{
+                                "type": "Expression",
+                                "value": [dimension("10", Some("px"))],
+                            }],
+                        },
+                        "important": "",
+                        "inline": false,
+                        "merge": false,
+                    },
+                    {
+                        "type": "Ruleset",
+                        "selectors": [selector_json(vec![
+                            element(json!(".a"), "", false),
+                            element(json!(".b"), ">", false),
+                            element(json!(":hover"), "", false),
+                        ])],
+                        "rules": [
+                            {
+                                "type": "Declaration",
+                                "name": [keyword("width")],
+                                "value": {
+                                    "type": "Value",
+                                    "value": [{
+                                        "type": "Expression",
+                                        "value": [{
+                                            "type": "Operation",
+                                            "op": "+",
+                                            "operands": [
+                                                {
+                                                    "type": "Operation",
+                                                    "op": "*",
+                                                    "operands": [
+                                                        variable("w"),
+                                                        dimension("2", None),
+                                                    ],
+                                                },
+                                                dimension("1", None),
+                                            ],
+                                        }],
+                                    }],
+                                },
+                                "important": "",
+                                "inline": false,
+                                "merge": false,
+                            },
+                            {
+                                "type": "Declaration",
+                                "name": [keyword("color")],
+                                "value": {
+                                    "type": "Value",
+                                    "value": [{
+                                        "type": "Expression",
+                                        "value": [{
+                                            "type": "Color",
+                                            "rgb": [0, 255, 0],
+                                            "alpha": 1.0,
+                                            "value": "#0f0",
+                                        }],
+                                    }],
+                                },
+                                "important": "!important",
+                                "inline": false,
+                                "merge": false,
+                            },
+                        ],
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_at_rules_to_less_js_ast() {
+        let input = r#"
+            @import (reference, css) "foo.css" screen;
+            @supports (display: grid) and (not (display: inline-grid)) { a: b; }
+            @keyframes spin { from { a: b; } 33.333% { a: c; } }
+            @page :first { a: b; }
+            @namespace svg "http://www.w3.org/2000/svg";
+        "#;
+        let stylesheet = parse(input).unwrap();
+        assert_eq!(
+            stylesheet.to_less_js_ast()["rules"],
+            json!([
+                {
+                    "type": "Import",
+                    "path": quoted("foo.css", false),
+                    "features": {
+                        "type": "Value",
+                        "value": [{ "type": "Expression", "value": [keyword("screen")] }],
+                    },
+                    "options": { "reference": true, "css": true },
+                },
+                {
+                    "type": "AtRule",
+                    "name": "@supports",
+                    "value": anonymous("(display: grid) and (not (display: inline-grid))"),
+                    "rules": [ruleset_json(Value::Null, vec![declaration("a", keyword("b"))])],
+                },
+                {
+                    "type": "AtRule",
+                    "name": "@keyframes",
+                    "value": keyword("spin"),
+                    "rules": [ruleset_json(
+                        Value::Null,
+                        vec![
+                            ruleset_json(
+                                json!([selector_json(vec![element(json!("from"), "", false)])]),
+                                vec![declaration("a", keyword("b"))],
+                            ),
+                            ruleset_json(
+                                json!([selector_json(vec![element(json!("33.333%"), "", false)])]),
+                                vec![declaration("a", keyword("c"))],
+                            ),
+                        ],
+                    )],
+                },
+                {
+                    "type": "AtRule",
+                    "name": "@page",
+                    "value": anonymous(":first"),
+                    "rules": [ruleset_json(Value::Null, vec![declaration("a", keyword("b"))])],
+                },
+                {
+                    "type": "AtRule",
+                    "name": "@namespace",
+                    "value": {
+                        "type": "Expression",
+                        "value": [keyword("svg"), quoted("http://www.w3.org/2000/svg", false)],
+                    },
+                    "rules": null,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_extends_to_less_js_ast() {
+        let input = ".a:extend(.b all) { c: d; }\n.e { &:extend(.f, .g all); }";
+        let stylesheet = parse(input).unwrap();
+        let mut selector = selector_json(vec![element(json!(".a"), "", false)]);
+        selector["extendList"] = json!([extend(".b", Some("all"))]);
+        assert_eq!(
+            stylesheet.to_less_js_ast()["rules"],
+            json!([
+                ruleset_json(json!([selector]), vec![declaration("c", keyword("d"))]),
+                // Less.js has an `Extend` node per target of `&:extend()`
+                ruleset_json(
+                    json!([selector_json(vec![element(json!(".e"), "", false)])]),
+                    vec![extend(".f", None), extend(".g", Some("all"))],
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_calls_to_less_js_ast() {
+        let input = "
+            #ns > .m() !important;
+            @dr: { a: b; };
+            @dr();
+            each(@list, { c: @value; });
+        ";
+        let stylesheet = parse(input).unwrap();
+        assert_eq!(
+            stylesheet.to_less_js_ast()["rules"],
+            json!([
+                {
+                    "type": "MixinCall",
+                    // The combinators of mixin calls are dropped by `parse-less.js`
+                    "selector": selector_json(vec![
+                        element(json!("#ns"), "", false),
+                        element(json!(".m"), "", false),
+                    ]),
+                    "arguments": [],
+                    "important": true,
+                },
+                {
+                    "type": "Declaration",
+                    "name": "@dr",
+                    "variable": true,
+                    "value": {
+                        "type": "DetachedRuleset",
+                        "ruleset": ruleset_json(Value::Null, vec![declaration("a", keyword("b"))]),
+                    },
+                    "important": "",
+                    "inline": false,
+                    "merge": false,
+                },
+                { "type": "VariableCall", "variable": "@dr" },
+                {
+                    "type": "Call",
+                    "name": "each",
+                    "args": [
+                        { "type": "Expression", "value": [variable("list")] },
+                        {
+                            "type": "DetachedRuleset",
+                            "ruleset": ruleset_json(
+                                Value::Null,
+                                vec![declaration("c", variable("value"))],
+                            ),
+                        },
+                    ],
+                    "calc": false,
+                },
+            ])
+        );
+    }
+
+    fn ruleset_json(selectors: Value, rules: Vec<Value>) -> Value {
+        json!({ "type": "Ruleset", "selectors": selectors, "rules": rules })
+    }
+
+    /// A declaration whose value is a single operand.
+    fn declaration(name: &str, value: Value) -> Value {
+        json!({
+            "type": "Declaration",
+            "name": [keyword(name)],
+            "value": {
+                "type": "Value",
+                "value": [{ "type": "Expression", "value": [value] }],
+            },
+            "important": "",
+            "inline": false,
+            "merge": false,
+        })
+    }
+
+    fn extend(class: &str, option: Option<&str>) -> Value {
+        json!({
+            "type": "Extend",
+            "selector": selector_json(vec![element(json!(class), "", false)]),
+            "option": option,
+        })
+    }
+}
@@ -1,14 +1,84 @@
+use std::fmt;
+use std::ops::Range;
+
 use chumsky::prelude::*;
 
 use crate::lexer::helpers::{is_name, would_start_identifier};
 
-mod helpers;
+pub(crate) mod helpers;
+
+/// A range of byte offsets into the source.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub const fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns an empty span at the given offset.
+    pub const fn splat(offset: usize) -> Self {
+        Self::new(offset, offset)
+    }
+
+    pub fn into_range(self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.into_range()
+    }
+}
+
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+// Lets the parser use spans of the token trees as its input spans
+impl chumsky::span::Span for Span {
+    type Context = ();
+    type Offset = usize;
+
+    fn new((): (), range: Range<usize>) -> Self {
+        range.into()
+    }
+
+    fn context(&self) {}
+
+    fn start(&self) -> usize {
+        self.start
+    }
+
+    fn end(&self) -> usize {
+        self.end
+    }
+}
 
-pub type Span = SimpleSpan<usize>;
 pub type Spanned<T> = (T, Span);
-pub type Err<'src> = extra::Err<Rich<'src, char, Span>>;
+pub(crate) type Err<'src> = extra::Err<Rich<'src, char>>;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Delim {
     Paren,
     Brace,
@@ -34,27 +104,35 @@ impl Delim {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum Token<'src> {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Token<S> {
     Whitespace,
-    Comment(&'src str),
-    Ident(&'src str),
-    Hash(&'src str),
-    String(&'src str),
-    Number(f32),
+    Comment(S),
+    Ident(S),
+    Hash(S),
+    String(S),
+    /// The contents of an unquoted `url()`, including any surrounding whitespace.
+    Url(S),
+    /// The variable name of a LESS interpolation (e.g. `@{name}`).
+    Interpolation(S),
+    /// The source text of a number (e.g. `.5` or `1e3`).
+    Number(S),
     Symbol(char),
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum TokenTree<'src> {
-    Token(Token<'src>),
-    Tree(Delim, Vec<Spanned<TokenTree<'src>>>),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TokenTree<S> {
+    Token(Token<S>),
+    Tree(Delim, Vec<Spanned<TokenTree<S>>>),
 }
 
-pub fn lexer<'src>() -> impl Parser<'src, &'src str, Vec<Spanned<TokenTree<'src>>>, Err<'src>> {
+pub(crate) fn lexer<'src>(
+) -> impl Parser<'src, &'src str, Vec<Spanned<TokenTree<&'src str>>>, Err<'src>> {
     token_tree().repeated().collect()
 }
 
-fn token_tree<'src>() -> impl Parser<'src, &'src str, Spanned<TokenTree<'src>>, Err<'src>> {
+fn token_tree<'src>() -> impl Parser<'src, &'src str, Spanned<TokenTree<&'src str>>, Err<'src>> {
     recursive(|token_tree| {
         choice((
             tree(Delim::Paren, token_tree.clone()),
@@ -62,14 +140,17 @@ fn token_tree<'src>() -> impl Parser<'src, &'src str, Spanned<TokenTree<'src>>,
             tree(Delim::Bracket, token_tree.clone()),
             token().map(TokenTree::Token),
         ))
-        .map_with(|tt, e| (tt, e.span()))
+        .map_with(|tt, e| {
+            let span: SimpleSpan = e.span();
+            (tt, span.into_range().into())
+        })
     })
 }
 
 fn tree<'src>(
     delim: Delim,
-    token_tree: impl Parser<'src, &'src str, Spanned<TokenTree<'src>>, Err<'src>> + Clone,
-) -> impl Parser<'src, &'src str, TokenTree<'src>, Err<'src>> + Clone {
+    token_tree: impl Parser<'src, &'src str, Spanned<TokenTree<&'src str>>, Err<'src>> + Clone,
+) -> impl Parser<'src, &'src str, TokenTree<&'src str>, Err<'src>> + Clone {
     just(delim.open())
         .ignore_then(
             // TODO: Clean this up? Test for close delimiter before trying to parse token_tree?
@@ -84,36 +165,52 @@ fn tree<'src>(
         )
 }
 
-fn token<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+fn token<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     choice((
         text::whitespace().at_least(1).to(Token::Whitespace),
         line_comment(),
         block_comment(),
+        url(),
         ident(),
         hash(),
+        interpolation(),
         string(),
         number(),
         any().map(Token::Symbol),
     ))
 }
 
-fn line_comment<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+fn line_comment<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     just("//")
         .ignore_then(any().and_is(just('\n').not()).repeated().to_slice())
-        .map(|value: &str| Token::Comment(value.into()))
+        .map(|value: &str| Token::Comment(value))
 }
 
-fn block_comment<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+fn block_comment<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     just("/*")
         .ignore_then(any().and_is(just("*/").not()).repeated().to_slice())
         .then_ignore(choice((just("*/").ignored(), end())))
-        .map(|value: &str| Token::Comment(value.into()))
+        .map(|value: &str| Token::Comment(value))
 }
 
-fn ident<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+/// Parses an unquoted `url()`. Quoted URLs (e.g. `url("foo.png")`) are left to be parsed as
+/// function calls.
+///
+/// See https://www.w3.org/TR/css-syntax-3/#consume-url-token
+fn url<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
+    just("url(")
+        .then(text::whitespace())
+        .then(one_of("\"'").not())
+        .ignore_then(none_of(")").repeated())
+        .to_slice()
+        .then_ignore(just(')'))
+        .map(|value: &str| Token::Url(&value[4..]))
+}
+
+fn ident<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     peek_ident_start()
         .ignore_then(ident_sequence())
-        .map(|value| Token::Ident(value.into()))
+        .map(Token::Ident)
 }
 
 fn peek_ident_start<'src>() -> impl Parser<'src, &'src str, (), Err<'src>> + Clone {
@@ -133,70 +230,51 @@ fn ident_sequence<'src>() -> impl Parser<'src, &'src str, &'src str, Err<'src>>
     any().filter(|c: &char| is_name(*c)).repeated().to_slice()
 }
 
-fn hash<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+fn hash<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     just('#')
         .ignore_then(ident_sequence())
-        .map(|value: &str| Token::Hash(value.into()))
+        .map(|value: &str| Token::Hash(value))
+}
+
+fn interpolation<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
+    just("@{")
+        .ignore_then(ident_sequence().filter(|name: &&str| !name.is_empty()))
+        .then_ignore(just('}'))
+        .map(Token::Interpolation)
 }
 
-fn string<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+fn string<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     choice((string_with_quote('"'), string_with_quote('\'')))
 }
 
 fn string_with_quote<'src>(
     quote: char,
-) -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+) -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
     // TODO: Deal with escapes and interpolation
     just(quote)
         .ignore_then(any().and_is(just(quote).not()).repeated().to_slice())
         .then_ignore(just(quote))
-        .map(|value: &str| Token::String(value.into()))
+        .map(|value: &str| Token::String(value))
 }
 
-fn number<'src>() -> impl Parser<'src, &'src str, Token<'src>, Err<'src>> + Clone {
+fn number<'src>() -> impl Parser<'src, &'src str, Token<&'src str>, Err<'src>> + Clone {
+    let sign = one_of("+-").or_not();
+    let digits = text::digits(10);
     group((
         // Optional sign
-        opt_sign(),
+        sign,
         // Integer and fractional parts
         choice((
             // Integer part + optional fractional part
-            group((dec_digits(), just('.').ignore_then(dec_digits()).or_not())).map(|o| match o {
-                ((i, _), Some((f, d))) => (i, f, d),
-                ((i, _), None) => (i, 0, 0),
-            }),
+            digits.then(just('.').then(digits).or_not()).ignored(),
             // No integer part + required fractional part
-            just('.').ignore_then(dec_digits()).map(|(f, d)| (0, f, d)),
+            just('.').then(digits).ignored(),
         )),
         // Exponent sign and exponent
-        one_of("eE")
-            .ignore_then(opt_sign().then(dec_digits()))
-            .or_not()
-            .map(|o| match o {
-                Some((t, (e, _))) => (t, e),
-                None => (1, 0),
-            }),
+        one_of("eE").then(sign).then(digits).or_not(),
     ))
-    .map(|(s, (i, f, d), (t, e))| {
-        // See https://www.w3.org/TR/css-syntax-3/#convert-string-to-number
-        let number =
-            s as f32 * (i as f32 + f as f32 * 10f32.powi(-(d as i32))) * 10f32.powi(t * e as i32);
-
-        Token::Number(number)
-    })
-}
-
-/// Parse an optional sign.
-/// Returns -1 for '-', +1 for '+', and +1 otherwise.
-fn opt_sign<'src>() -> impl Parser<'src, &'src str, i32, Err<'src>> + Clone {
-    choice((just('+').to(1), just('-').to(-1), empty().to(1)))
-}
-
-/// Parses a string of decimal digits.
-/// Returns the digits as an unsigned integer and the number of digits.
-fn dec_digits<'src>() -> impl Parser<'src, &'src str, (u32, u32), Err<'src>> + Clone {
-    text::digits(10)
-        .to_slice()
-        .map(|digits: &str| (digits.parse().unwrap(), digits.len() as u32))
+    .to_slice()
+    .map(Token::Number)
 }
 
 #[cfg(test)]
@@ -259,6 +337,20 @@ mod tests {
         assert_eq!(ident().lazy().parse(input).into_result(), expected);
     }
 
+    #[test]
+    fn test_url() {
+        let input = "url(foo.png)";
+        let expected = Ok(Token::Url("foo.png"));
+        assert_eq!(url().parse(input).into_result(), expected);
+
+        let input = "url( http://example.com/foo.png )";
+        let expected = Ok(Token::Url(" http://example.com/foo.png "));
+        assert_eq!(url().parse(input).into_result(), expected);
+
+        let input = r#"url("foo.png")"#;
+        assert!(url().parse(input).has_errors());
+    }
+
     #[test]
     fn test_hash() {
         let input = "#hash";
@@ -270,6 +362,16 @@ mod tests {
         assert_eq!(hash().parse(input).into_result(), expected);
     }
 
+    #[test]
+    fn test_interpolation() {
+        let input = "@{name}";
+        let expected = Ok(Token::Interpolation("name"));
+        assert_eq!(interpolation().parse(input).into_result(), expected);
+
+        let input = "@{}";
+        assert!(interpolation().parse(input).has_errors());
+    }
+
     #[test]
     fn test_string() {
         let input = r#""This is a string""#;
@@ -287,15 +389,26 @@ mod tests {
     #[test]
     fn test_number() {
         let input = "123.45";
-        let expected = Ok(Token::Number(123.45));
+        let expected = Ok(Token::Number("123.45"));
         assert_eq!(number().parse(input).into_result(), expected);
 
         let input = "15px";
-        let expected = Ok(Token::Number(15.0));
+        let expected = Ok(Token::Number("15"));
         assert_eq!(number().lazy().parse(input).into_result(), expected);
 
         let input = "20%";
-        let expected = Ok(Token::Number(20.0));
+        let expected = Ok(Token::Number("20"));
+        assert_eq!(number().lazy().parse(input).into_result(), expected);
+
+        for input in [".5", "1e3", "-1.50", "+2E-1"] {
+            assert_eq!(
+                number().parse(input).into_result(),
+                Ok(Token::Number(input))
+            );
+        }
+
+        let input = "1em";
+        let expected = Ok(Token::Number("1"));
         assert_eq!(number().lazy().parse(input).into_result(), expected);
     }
 
@@ -338,12 +451,12 @@ mod tests {
                 (token!(Whitespace), Span::new(112, 125)),
                 (token!(String("This is a string")), Span::new(125, 143),),
                 (token!(Whitespace), Span::new(143, 156)),
-                (token!(Number(123.45)), Span::new(156, 162)),
+                (token!(Number("123.45")), Span::new(156, 162)),
                 (token!(Whitespace), Span::new(162, 163)),
-                (token!(Number(15.0)), Span::new(163, 165)),
+                (token!(Number("15")), Span::new(163, 165)),
                 (token!(Ident("px")), Span::new(165, 167)),
                 (token!(Whitespace), Span::new(167, 168)),
-                (token!(Number(20.0)), Span::new(168, 170)),
+                (token!(Number("20")), Span::new(168, 170)),
                 (token!(Symbol('%')), Span::new(170, 171)),
                 (token!(Whitespace), Span::new(171, 184)),
                 (
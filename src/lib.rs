@@ -1,6 +1,87 @@
-pub use lexer::lexer;
-pub use parser::parser;
+use chumsky::input::Input;
+use chumsky::Parser;
+
+pub use compiler::{compile, Math, Options};
+pub use error::{CompileError, EvalError, Expected, Label, ParseError, ParseErrors};
+
+use crate::ast::arena::Arena;
+use crate::ast::Stylesheet;
+use crate::lexer::{lexer, Span, Spanned, TokenTree};
+use crate::parser::parser;
 
 pub mod ast;
+mod compiler;
+pub mod cst;
+pub mod diagnostics;
+mod error;
+#[cfg(feature = "less-js")]
+pub mod less_js;
 mod lexer;
 mod parser;
+mod printer;
+
+/// Parses a LESS stylesheet, returning all syntax errors if it's not well-formed.
+pub fn parse(input: &str) -> Result<Stylesheet<&str>, ParseErrors> {
+    match parse_recovering(input) {
+        (Some(stylesheet), errors) if errors.is_empty() => Ok(stylesheet),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Parses a LESS stylesheet, recovering from syntax errors: an item that fails to parse is
+/// reported and skipped, and the stylesheet of the other items is returned with the errors. The
+/// stylesheet is `None` if the input can't be lexed (e.g. an unterminated string).
+pub fn parse_recovering(input: &str) -> (Option<Stylesheet<&str>>, ParseErrors) {
+    match lex(input) {
+        Ok(tts) => parse_tokens_recovering(input, &tts),
+        Err(errors) => (None, errors),
+    }
+}
+
+/// Parses a LESS stylesheet into an [`Arena`], in which every node has a stable id, returning all
+/// syntax errors if it's not well-formed.
+pub fn parse_arena(input: &str) -> Result<Arena<&str>, ParseErrors> {
+    let tts = lex(input)?;
+    match parse_tokens_into_arena(input, &tts) {
+        (Some(arena), errors) if errors.is_empty() => Ok(arena),
+        (_, errors) => Err(errors),
+    }
+}
+
+/// Splits a LESS stylesheet into tokens, with parentheses, braces and brackets grouped into trees.
+pub fn lex(input: &str) -> Result<Vec<Spanned<TokenTree<&str>>>, ParseErrors> {
+    lexer()
+        .parse(input)
+        .into_result()
+        .map_err(ParseErrors::from_rich)
+}
+
+pub(crate) fn parse_tokens<'src>(
+    input: &'src str,
+    tts: &[Spanned<TokenTree<&'src str>>],
+) -> Result<Stylesheet<&'src str>, ParseErrors> {
+    match parse_tokens_recovering(input, tts) {
+        (Some(stylesheet), errors) if errors.is_empty() => Ok(stylesheet),
+        (_, errors) => Err(errors),
+    }
+}
+
+pub(crate) fn parse_tokens_recovering<'src>(
+    input: &'src str,
+    tts: &[Spanned<TokenTree<&'src str>>],
+) -> (Option<Stylesheet<&'src str>>, ParseErrors) {
+    let (arena, errors) = parse_tokens_into_arena(input, tts);
+    (arena.map(|arena| arena.to_stylesheet()), errors)
+}
+
+fn parse_tokens_into_arena<'src>(
+    input: &'src str,
+    tts: &[Spanned<TokenTree<&'src str>>],
+) -> (Option<Arena<&'src str>>, ParseErrors) {
+    let mut arena = Arena::new();
+    let (root, errors) = parser()
+        .parse_with_state(tts.spanned(Span::splat(input.len())), &mut arena)
+        .into_output_errors();
+    let arena = root.map(|root| arena.finish(root));
+    (arena, ParseErrors::from_rich(errors))
+}
@@ -1,14 +1,19 @@
-use chumsky::input::Input;
-use chumsky::prelude::SimpleSpan;
-use chumsky::Parser;
+use std::io::IsTerminal;
 
-use less::{lexer, parser};
+use less::diagnostics::{render, Format};
 
 fn main() {
-    let file =
-        std::fs::read_to_string("node_modules/@less/test-data/less/_main/variables.less").unwrap();
-    let tts = lexer().parse(file.as_str()).unwrap();
-    let parser_input = tts.as_slice().spanned(SimpleSpan::splat(tts.len()));
-    let result = parser().parse(parser_input).into_result();
-    println!("{:#?}", result);
+    let file_name = "node_modules/@less/test-data/less/_main/variables.less";
+    let file = std::fs::read_to_string(file_name).unwrap();
+    match less::parse(&file) {
+        Ok(stylesheet) => println!("{:#?}", stylesheet),
+        Err(errors) => {
+            let format = if std::io::stderr().is_terminal() {
+                Format::Colored
+            } else {
+                Format::Plain
+            };
+            eprint!("{}", render(&errors, file_name, &file, format));
+        }
+    }
 }
@@ -1,20 +1,80 @@
-use std::marker::PhantomData;
+use std::iter::once;
 
 use chumsky::input::SpannedInput;
 use chumsky::prelude::*;
 
 use util::*;
 
+use crate::ast::arena::{Arena, NodeId, NodeKind};
 use crate::ast::*;
+use crate::lexer::helpers::is_name;
 use crate::lexer::{Delim, Span, Spanned, Token, TokenTree};
 
 type ParserInput<'tokens, 'src> =
-    SpannedInput<TokenTree<'src>, Span, &'tokens [Spanned<TokenTree<'src>>]>;
-type ParserExtra<'tokens, 'src> = extra::Err<Rich<'tokens, TokenTree<'src>, Span>>;
+    SpannedInput<TokenTree<&'src str>, Span, &'tokens [Spanned<TokenTree<&'src str>>]>;
+/// The parser pushes the nodes it parses into an [`Arena`], and outputs their ids.
+type ParserExtra<'tokens, 'src> =
+    extra::Full<Rich<'tokens, TokenTree<&'src str>, Span>, Arena<&'src str>, ()>;
+type MapExtra<'tokens, 'src, 'b> =
+    chumsky::input::MapExtra<'tokens, 'b, ParserInput<'tokens, 'src>, ParserExtra<'tokens, 'src>>;
+
+/// Pushes a node spanning the parsed input into the arena.
+fn push<'tokens, 'src: 'tokens>(
+    e: &mut MapExtra<'tokens, 'src, '_>,
+    kind: NodeKind<&'src str>,
+    children: impl IntoIterator<Item = NodeId>,
+) -> NodeId {
+    let span = e.span();
+    e.state().push(kind, span, children)
+}
+
+/// Pushes an [`InterpolatedIdent`] or [`InterpolatedString`] spanning the parsed input into the
+/// arena.
+fn push_interpolated<'tokens, 'src: 'tokens>(
+    e: &mut MapExtra<'tokens, 'src, '_>,
+    kind: NodeKind<&'src str>,
+    parts: Vec<Spanned<InterpolationPart<&'src str>>>,
+) -> NodeId {
+    let span = e.span();
+    e.state().push_interpolated(kind, span, parts)
+}
+
+/// Pushes a [`SelectorName`] into the arena.
+fn push_selector_name<'src>(
+    arena: &mut Arena<&'src str>,
+    (name, span): Spanned<SelectorName<&'src str>>,
+) -> NodeId {
+    match name {
+        SelectorName::Ident(name) => arena.push(NodeKind::Ident(name), span, []),
+        SelectorName::InterpolatedIdent(InterpolatedIdent(parts)) => {
+            arena.push_interpolated(NodeKind::InterpolatedIdent, span, parts)
+        }
+    }
+}
+
+/// Sets the span of a node to that of the parsed input (e.g. to include the parentheses around a
+/// condition).
+fn respan<'tokens, 'src: 'tokens>(id: NodeId, e: &mut MapExtra<'tokens, 'src, '_>) -> NodeId {
+    let span = e.span();
+    e.state().set_span(id, span);
+    id
+}
+
+/// Pushes a copy of a slice of token trees into the arena, as a [`ListOfComponentValues`]
+fn component_values<'tokens, 'src: 'tokens>(
+    tts: &'tokens [Spanned<TokenTree<&'src str>>],
+    e: &mut MapExtra<'tokens, 'src, '_>,
+) -> NodeId {
+    let span = match (tts.first(), tts.last()) {
+        (Some((_, first)), Some((_, last))) => Span::new(first.start, last.end),
+        _ => e.span(),
+    };
+    e.state().push_component_values(span, tts)
+}
 
 fn strip_trailing_junk<'tokens, 'src>(
-    mut value: &'tokens [Spanned<TokenTree<'src>>],
-) -> &'tokens [Spanned<TokenTree<'src>>] {
+    mut value: &'tokens [Spanned<TokenTree<&'src str>>],
+) -> &'tokens [Spanned<TokenTree<&'src str>>] {
     while let Some(((TokenTree::Token(Token::Whitespace | Token::Comment(_)), _), rest_value)) =
         value.split_last()
     {
@@ -23,12 +83,96 @@ fn strip_trailing_junk<'tokens, 'src>(
     value
 }
 
+/// Splits a vendor prefix off of a name (e.g. `-webkit-keyframes` into `-webkit-` and `keyframes`).
+fn split_vendor_prefix(name: &str) -> (Option<&str>, &str) {
+    name.strip_prefix('-')
+        .and_then(|rest| rest.find('-'))
+        .map(|index| name.split_at(index + 2))
+        .map_or((None, name), |(prefix, rest)| (Some(prefix), rest))
+}
+
+/// Splits the contents of a string into literal parts and LESS interpolations (e.g. `@{name}`).
+/// The `offset` is the position of the contents in the source, used to compute the parts' spans.
+fn interpolated_string(value: &str, offset: usize) -> InterpolatedString<&str> {
+    let mut parts = vec![];
+    let mut literal_start = 0;
+    let mut search_start = 0;
+
+    while let Some(index) = value[search_start..].find("@{") {
+        let start = search_start + index;
+        search_start = start + 2;
+
+        let Some(name_len) = value[start + 2..].find('}') else {
+            break;
+        };
+        let name = &value[start + 2..start + 2 + name_len];
+        if name.is_empty() || !name.chars().all(is_name) {
+            continue;
+        }
+        let end = start + 2 + name_len + 1;
+
+        if literal_start < start {
+            parts.push((
+                InterpolationPart::Literal(&value[literal_start..start]),
+                Span::new(offset + literal_start, offset + start),
+            ));
+        }
+        parts.push((
+            InterpolationPart::Variable(name),
+            Span::new(offset + start, offset + end),
+        ));
+        literal_start = end;
+        search_start = end;
+    }
+
+    if literal_start < value.len() {
+        parts.push((
+            InterpolationPart::Literal(&value[literal_start..]),
+            Span::new(offset + literal_start, offset + value.len()),
+        ));
+    }
+
+    InterpolatedString(parts)
+}
+
 mod util {
+    use chumsky::error::Error;
+    use chumsky::label::LabelError;
     use chumsky::prelude::*;
+    use chumsky::util::MaybeRef;
 
-    use crate::lexer::{Token, TokenTree};
+    use crate::ast::{InterpolatedIdent, InterpolationPart};
+    use crate::lexer::{Delim, Span, Token, TokenTree};
     use crate::parser::{ParserExtra, ParserInput};
 
+    type ParserError<'tokens, 'src> = Rich<'tokens, TokenTree<&'src str>, Span>;
+
+    // Token parsers set their expected inputs explicitly, since `select_ref!` doesn't record any
+    // and `Parser::labelled` doesn't apply to errors past the first token.
+
+    /// Replaces the expected inputs of an error with the given token.
+    fn expected_token<'tokens, 'src: 'tokens>(
+        error: ParserError<'tokens, 'src>,
+        token: TokenTree<&'src str>,
+    ) -> ParserError<'tokens, 'src> {
+        let found = error.found().cloned().map(MaybeRef::Val);
+        let span = *error.span();
+        <ParserError as Error<ParserInput>>::expected_found(
+            [Some(MaybeRef::Val(token))],
+            found,
+            span,
+        )
+    }
+
+    /// Replaces the expected inputs of an error with the given label.
+    fn expected_label<'tokens, 'src: 'tokens>(
+        mut error: ParserError<'tokens, 'src>,
+        label: &'static str,
+    ) -> ParserError<'tokens, 'src> {
+        LabelError::<ParserInput, _>::label_with(&mut error, label);
+        error
+    }
+
     pub(crate) fn junk<'tokens, 'src: 'tokens>(
     ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, (), ParserExtra<'tokens, 'src>> + Clone
     {
@@ -39,75 +183,212 @@ mod util {
 
     pub(crate) fn symbol<'tokens, 'src: 'tokens>(
         symbol: char,
-    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, (), ParserExtra<'tokens, 'src>> + Clone + Copy
+    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, (), ParserExtra<'tokens, 'src>> + Copy
     {
         select_ref!(TokenTree::Token(Token::Symbol(s)) if s == &symbol => ())
+            .map_err(move |error| expected_token(error, TokenTree::Token(Token::Symbol(symbol))))
     }
 
     pub(crate) fn ident<'tokens, 'src: 'tokens>(
-    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>>
-           + Clone
-           + Copy {
+    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>> + Copy
+    {
         select_ref!(TokenTree::Token(Token::Ident(ident)) => *ident)
+            .map_err(|error| expected_label(error, "identifier"))
     }
 
     pub(crate) fn at_ident<'tokens, 'src: 'tokens>(
-    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>>
-           + Clone
-           + Copy {
-        symbol('@').ignore_then(ident())
+    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>> + Copy
+    {
+        symbol('@')
+            .ignore_then(ident())
+            .map_err(|error| expected_label(error, "at-keyword"))
+    }
+
+    /// Parses the given identifier, ignoring ASCII case.
+    pub(crate) fn keyword<'tokens, 'src: 'tokens>(
+        keyword: &'static str,
+    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>> + Copy
+    {
+        ident()
+            .filter(move |ident| ident.eq_ignore_ascii_case(keyword))
+            .map_err(move |error| expected_label(error, keyword))
+    }
+
+    /// Parses the given at-rule name (e.g. `@media`), ignoring ASCII case.
+    pub(crate) fn at_keyword<'tokens, 'src: 'tokens>(
+        keyword: &'static str,
+    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>> + Copy
+    {
+        at_ident().filter(move |ident| ident.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Selects the contents of a [`TokenTree::Tree`] with the given delimiter, to be parsed further
+    /// using [`Parser::nested_in`].
+    pub(crate) fn tree<'tokens, 'src: 'tokens>(
+        delim: Delim,
+    ) -> impl Parser<
+        'tokens,
+        ParserInput<'tokens, 'src>,
+        ParserInput<'tokens, 'src>,
+        ParserExtra<'tokens, 'src>,
+    > + Clone {
+        // The end of the nested input is at the closing delimiter
+        select_ref!(
+            TokenTree::Tree(d, tts) = e if d == &delim => {
+                let span: Span = e.span();
+                tts.as_slice().spanned(Span::splat(span.end - 1))
+            }
+        )
+        .map_err(move |error| expected_token(error, TokenTree::Tree(delim, vec![])))
+    }
+
+    /// Parses a LESS variable interpolation (e.g. `@{name}`), returning the variable name.
+    pub(crate) fn interpolation<'tokens, 'src: 'tokens>(
+    ) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, &'src str, ParserExtra<'tokens, 'src>> + Clone
+    {
+        select_ref!(TokenTree::Token(Token::Interpolation(name)) => *name)
+    }
+
+    /// Parses an identifier containing at least one LESS interpolation (e.g. `fade-@{name}`).
+    pub(crate) fn interpolated_ident<'tokens, 'src: 'tokens>() -> impl Parser<
+        'tokens,
+        ParserInput<'tokens, 'src>,
+        InterpolatedIdent<&'src str>,
+        ParserExtra<'tokens, 'src>,
+    > + Clone {
+        let part = choice((
+            interpolation().map(InterpolationPart::Variable),
+            ident().map(InterpolationPart::Literal),
+            symbol('-').to(InterpolationPart::Literal("-")),
+        ))
+        .map_with(|part, e| (part, e.span()));
+
+        part.repeated()
+            .at_least(1)
+            .collect::<Vec<_>>()
+            .filter(|parts| {
+                parts
+                    .iter()
+                    .any(|(part, _)| matches!(part, InterpolationPart::Variable(_)))
+            })
+            .map(InterpolatedIdent)
     }
 }
 
-pub fn parser<'tokens, 'src: 'tokens>() -> impl Parser<
-    'tokens,
-    ParserInput<'tokens, 'src>,
-    Spanned<Stylesheet<'tokens, 'src>>,
-    ParserExtra<'tokens, 'src>,
-> + Clone {
+pub(crate) fn parser<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
     // Item parsers
     let list_of_items = recursive(|list_of_items| {
-        // Parse a rule's block
-        let rule_block = list_of_items.nested_in(select_ref!(
-            TokenTree::Tree(Delim::Brace, tts)
-                => tts.as_slice().spanned(Span::splat(tts.len()))
-        ));
+        // Parse a rule's block. Errors recovered from inside a block are labelled with it. Block
+        // parsers are boxed to keep the parser types (and compile times) manageable.
+        let rule_block = list_of_items
+            .labelled("block")
+            .as_context()
+            .nested_in(tree(Delim::Brace))
+            .map_with(|items, e| push(e, NodeKind::Block, items))
+            .boxed();
+
+        let declaration = declaration(rule_block.clone()).boxed();
+        let call = call(rule_block.clone()).boxed();
+
+        // Parse a keyframes at-rule's block, which contains keyframe rules instead of the usual
+        // qualified rules
+        let keyframes_block = items(choice((
+            declaration.clone(),
+            call.clone(),
+            keyframe_rule(rule_block.clone()),
+        )))
+        .labelled("block")
+        .as_context()
+        .nested_in(tree(Delim::Brace))
+        .map_with(|items, e| push(e, NodeKind::Block, items))
+        .boxed();
+
+        // Parse a page at-rule's block, which may contain margin at-rules
+        let page_block = items(choice((
+            declaration.clone(),
+            call.clone(),
+            page_margin_at_rule(rule_block.clone()),
+        )))
+        .labelled("block")
+        .as_context()
+        .nested_in(tree(Delim::Brace))
+        .map_with(|items, e| push(e, NodeKind::Block, items))
+        .boxed();
 
         // Parse an Item
-        let item = choice((
-            declaration().map(Item::Declaration),
-            call().map(Item::Call),
-            at_rule(rule_block.clone()).map(Item::AtRule),
-            qualified_rule(rule_block.clone()).map(Item::QualifiedRule),
-        ))
-        .map_with(|item, e| (item, e.span()));
-
-        // Parse a list of items separated by junk (whitespace or comments)
-        item.separated_by(junk())
-            .allow_leading()
-            .allow_trailing()
-            .collect()
-            .map(ListOfItems)
+        items(choice((
+            declaration,
+            call,
+            extend_statement(),
+            at_rule(rule_block.clone(), keyframes_block, page_block).boxed(),
+            qualified_rule(rule_block.clone()).boxed(),
+        )))
     });
 
     // A stylesheet is just a list of items
-    list_of_items.map_with(|items, e| (Stylesheet { items }, e.span()))
+    list_of_items.map_with(|items, e| push(e, NodeKind::Stylesheet, items))
+}
+
+/// Parses a list of items separated by junk (whitespace or comments). An item that fails to parse
+/// is reported as an error and skipped up to and including the next semicolon or block, so the
+/// remaining items can still be parsed.
+fn items<'tokens, 'src: 'tokens>(
+    item: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, Vec<NodeId>, ParserExtra<'tokens, 'src>> + Clone
+{
+    let recovery_end = select_ref!(
+        TokenTree::Token(Token::Symbol(';')) => (),
+        TokenTree::Tree(Delim::Brace, _) => (),
+    );
+    let recovery = choice((
+        any()
+            .and_is(recovery_end.not())
+            .repeated()
+            .then(recovery_end)
+            .ignored(),
+        any().repeated().at_least(1).then(end()).ignored(),
+    ))
+    .to(None);
+
+    item.map_with(|item, e| Some(respan(item, e)))
+        .recover_with(via_parser(recovery))
+        .separated_by(junk())
+        .allow_leading()
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .map(|items| items.into_iter().flatten().collect())
 }
 
 /// Parses an [`AtRule`]
 fn at_rule<'tokens, 'src: 'tokens>(
-    rule_block: impl Parser<
-            'tokens,
-            ParserInput<'tokens, 'src>,
-            ListOfItems<'tokens, 'src>,
-            ParserExtra<'tokens, 'src>,
-        > + Clone,
-) -> impl Parser<
-    'tokens,
-    ParserInput<'tokens, 'src>,
-    AtRule<'tokens, 'src>,
-    ParserExtra<'tokens, 'src>,
-> + Clone {
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone
+        + 'tokens,
+    keyframes_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+    page_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    choice((
+        keyframes_at_rule(keyframes_block),
+        import_at_rule(),
+        supports_at_rule(rule_block.clone()),
+        container_at_rule(rule_block.clone()),
+        layer_at_rule(rule_block.clone()),
+        font_face_at_rule(rule_block.clone()),
+        page_at_rule(page_block),
+        namespace_at_rule(),
+        property_at_rule(rule_block.clone()),
+        generic_at_rule(rule_block),
+    ))
+}
+
+/// Parses a [`GenericAtRule`]
+fn generic_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
     // Parse the prelude up to eof, semicolon, or block.
     let at_rule_prelude = any()
         .and_is(
@@ -119,36 +400,421 @@ fn at_rule<'tokens, 'src: 'tokens>(
         )
         .repeated()
         .to_slice()
-        .map(ListOfComponentValues);
+        .map_with(component_values);
 
     // Parse the end of the at-rule.
     let at_rule_end = choice((end().to(None), symbol(';').to(None), rule_block.map(Some)));
 
-    group((at_ident(), at_rule_prelude, at_rule_end)).map(|(name, prelude, block)| {
-        AtRule::Generic(GenericAtRule {
-            name,
-            prelude,
-            block,
+    group((at_ident(), at_rule_prelude, at_rule_end)).map_with(|(name, prelude, block), e| {
+        push(
+            e,
+            NodeKind::GenericAtRule { name },
+            once(prelude).chain(block),
+        )
+    })
+}
+
+/// Parses a [`KeyframesAtRule`]
+fn keyframes_at_rule<'tokens, 'src: 'tokens>(
+    keyframes_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    // Parse the at-rule name, which may have a vendor prefix (e.g. `@-webkit-keyframes`)
+    let keyframes_keyword = at_ident()
+        .map(split_vendor_prefix)
+        .filter(|(_, name)| name.eq_ignore_ascii_case("keyframes"))
+        .map(|(vendor_prefix, _)| vendor_prefix);
+
+    let keyframes_name = choice((
+        interpolated_ident()
+            .map_with(|ident, e| push_interpolated(e, NodeKind::InterpolatedIdent, ident.0)),
+        ident().map_with(|name, e| push(e, NodeKind::Ident(name), [])),
+        select_ref!(TokenTree::Token(Token::String(string)) => *string)
+            .map_with(|name, e| push(e, NodeKind::String(name), [])),
+        at_ident().map_with(|name, e| push(e, NodeKind::Variable(name), [])),
+    ));
+
+    group((
+        keyframes_keyword.then_ignore(junk()),
+        keyframes_name.then_ignore(junk()),
+        keyframes_block,
+    ))
+    .map_with(|(vendor_prefix, name, block), e| {
+        push(
+            e,
+            NodeKind::KeyframesAtRule { vendor_prefix },
+            [name, block],
+        )
+    })
+}
+
+/// Parses an [`ImportAtRule`]
+fn import_at_rule<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let import_option = ident()
+        .try_map(|option, span| match option {
+            "reference" => Ok(ImportOption::Reference),
+            "inline" => Ok(ImportOption::Inline),
+            "less" => Ok(ImportOption::Less),
+            "css" => Ok(ImportOption::Css),
+            "once" => Ok(ImportOption::Once),
+            "multiple" => Ok(ImportOption::Multiple),
+            "optional" => Ok(ImportOption::Optional),
+            _ => Err(Rich::custom(
+                span,
+                format!("unknown import option `{option}`"),
+            )),
         })
+        .map_with(|option, e| push(e, NodeKind::ImportOption(option), []));
+
+    // Parse the import options (e.g. `(reference, optional)`)
+    let import_options = import_option
+        .separated_by(symbol(',').padded_by(junk()))
+        .collect::<Vec<_>>()
+        .padded_by(junk())
+        .nested_in(tree(Delim::Paren));
+
+    // Parse the media queries up to a semicolon or eof
+    let media_queries = any()
+        .and_is(symbol(';').not())
+        .repeated()
+        .to_slice()
+        .map_with(|media_queries, e| component_values(strip_trailing_junk(media_queries), e));
+
+    group((
+        at_keyword("import").then_ignore(junk()),
+        import_options
+            .then_ignore(junk())
+            .or_not()
+            .map(Option::unwrap_or_default),
+        url_or_string().then_ignore(junk()),
+        media_queries.then_ignore(choice((symbol(';'), end()))),
+    ))
+    .map_with(|(_, options, url, media_queries), e| {
+        push(
+            e,
+            NodeKind::ImportAtRule,
+            options.into_iter().chain([url, media_queries]),
+        )
     })
 }
 
-/// Parses a [`QualifiedRule`]
-fn qualified_rule<'tokens, 'src: 'tokens>(
-    rule_block: impl Parser<
-            'tokens,
-            ParserInput<'tokens, 'src>,
-            ListOfItems<'tokens, 'src>,
-            ParserExtra<'tokens, 'src>,
-        > + Clone,
-) -> impl Parser<
+/// Parses a string token into an [`InterpolatedString`]
+fn string<'tokens, 'src: 'tokens>() -> impl Parser<
     'tokens,
     ParserInput<'tokens, 'src>,
-    QualifiedRule<'tokens, 'src>,
+    InterpolatedString<&'src str>,
     ParserExtra<'tokens, 'src>,
 > + Clone {
-    // Parse the prelude up to eof, semicolon, or block. Eof and semicolon are parse errors,
-    // which we'll deal with when parsing the block.
+    select_ref!(
+        TokenTree::Token(Token::String(string)) = e => {
+            let (string, span): (&&str, Span) = (string, e.span());
+            interpolated_string(string, span.start + 1)
+        }
+    )
+}
+
+/// Parses a [`UrlOrString`]
+fn url_or_string<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let string =
+        string().map_with(|string, e| push_interpolated(e, NodeKind::InterpolatedString, string.0));
+
+    choice((
+        string.clone(),
+        // Parse an unquoted URL (e.g. `url(theme.less)`)
+        select_ref!(
+            TokenTree::Token(Token::Url(url)) = e => {
+                let span: Span = e.span();
+                let url: &&str = url;
+                let trimmed = url.trim_start();
+                let offset = span.start + "url(".len() + url.len() - trimmed.len();
+                interpolated_string(trimmed.trim_end(), offset)
+            }
+        )
+        .map_with(|url, e| {
+            let url = push_interpolated(e, NodeKind::InterpolatedString, url.0);
+            push(e, NodeKind::Url, [url])
+        }),
+        // Parse a quoted URL (e.g. `url("theme.less")`)
+        keyword("url")
+            .ignore_then(string.padded_by(junk()).nested_in(tree(Delim::Paren)))
+            .map_with(|url, e| push(e, NodeKind::Url, [url])),
+    ))
+}
+
+/// Parses a [`Condition`] over features parsed by the given parser
+fn condition<'tokens, 'src: 'tokens>(
+    feature: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone
+        + 'tokens,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    recursive(|condition| {
+        // Parse a condition in parentheses or a feature
+        let in_parens = choice((
+            condition.padded_by(junk()).nested_in(tree(Delim::Paren)),
+            feature,
+        ))
+        .map_with(respan);
+
+        // Parse a list of conditions joined by the given operator (e.g. `(a) and (b) and (c)`)
+        let joined_by = |operator| {
+            in_parens
+                .clone()
+                .then(
+                    keyword(operator)
+                        .padded_by(junk())
+                        .ignore_then(in_parens.clone())
+                        .repeated()
+                        .at_least(1)
+                        .collect::<Vec<_>>(),
+                )
+                .map(|(first, mut rest)| {
+                    rest.insert(0, first);
+                    rest
+                })
+        };
+
+        choice((
+            keyword("not")
+                .then_ignore(junk())
+                .ignore_then(in_parens.clone())
+                .map_with(|condition, e| push(e, NodeKind::Not, [condition])),
+            joined_by("and").map_with(|conditions, e| push(e, NodeKind::And, conditions)),
+            joined_by("or").map_with(|conditions, e| push(e, NodeKind::Or, conditions)),
+            in_parens,
+        ))
+    })
+}
+
+/// Parses a [`SupportsAtRule`]
+fn supports_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone
+        + 'tokens,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let supports_feature = choice((
+        declaration(rule_block.clone())
+            .padded_by(junk())
+            .nested_in(tree(Delim::Paren)),
+        function(),
+        select_ref!(TokenTree::Tree(Delim::Paren, tts) => tts.as_slice())
+            .map_with(component_values),
+    ));
+
+    group((
+        at_keyword("supports").then_ignore(junk()),
+        condition(supports_feature)
+            .map_with(respan)
+            .then_ignore(junk()),
+        rule_block,
+    ))
+    .map_with(|(_, condition, block), e| push(e, NodeKind::SupportsAtRule, [condition, block]))
+}
+
+/// Parses a [`ContainerAtRule`]
+fn container_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let container_feature = choice((
+        function(),
+        select_ref!(TokenTree::Tree(Delim::Paren, tts) => tts.as_slice())
+            .map_with(component_values),
+    ));
+
+    // The container name can't be a keyword used in conditions, or the name of a function
+    let container_name = ident()
+        .filter(|name| {
+            !["none", "not", "and", "or"]
+                .iter()
+                .any(|keyword| name.eq_ignore_ascii_case(keyword))
+        })
+        .then_ignore(tree(Delim::Paren).not());
+
+    group((
+        at_keyword("container").then_ignore(junk()),
+        container_name.then_ignore(junk()).or_not(),
+        condition(container_feature)
+            .map_with(respan)
+            .then_ignore(junk())
+            .or_not(),
+        rule_block,
+    ))
+    .filter(|(_, name, condition, _)| name.is_some() || condition.is_some())
+    .map_with(|(_, name, condition, block), e| {
+        push(
+            e,
+            NodeKind::ContainerAtRule { name },
+            condition.into_iter().chain([block]),
+        )
+    })
+}
+
+/// Parses a [`LayerAtRule`]
+fn layer_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let layer_name = ident()
+        .map_with(|ident, e| push(e, NodeKind::Ident(ident), []))
+        .separated_by(symbol('.'))
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .map_with(|parts, e| push(e, NodeKind::LayerName, parts));
+
+    // Parse the statement form (e.g. `@layer reset, base;`)
+    let layer_statement = layer_name
+        .separated_by(symbol(',').padded_by(junk()))
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .then_ignore(junk())
+        .then_ignore(choice((symbol(';'), end())))
+        .map(|names| (names, None));
+
+    // Parse the block form (e.g. `@layer base { ... }`)
+    let layer_block = layer_name
+        .then_ignore(junk())
+        .or_not()
+        .then(rule_block)
+        .map(|(name, block)| (name.into_iter().collect(), Some(block)));
+
+    at_keyword("layer")
+        .then_ignore(junk())
+        .ignore_then(choice((layer_statement, layer_block)))
+        .map_with(|(names, block), e| {
+            push(e, NodeKind::LayerAtRule, names.into_iter().chain(block))
+        })
+}
+
+/// Parses a [`FontFaceAtRule`]
+fn font_face_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    at_keyword("font-face")
+        .then_ignore(junk())
+        .ignore_then(rule_block)
+        .map_with(|block, e| push(e, NodeKind::FontFaceAtRule, [block]))
+}
+
+/// Parses a [`PageAtRule`]
+fn page_at_rule<'tokens, 'src: 'tokens>(
+    page_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let page_selector = group((
+        ident().or_not(),
+        symbol(':')
+            .ignore_then(ident())
+            .map_with(|pseudo_class, e| push(e, NodeKind::Ident(pseudo_class), []))
+            .repeated()
+            .collect::<Vec<_>>(),
+    ))
+    .filter(|(name, pseudo_classes)| name.is_some() || !pseudo_classes.is_empty())
+    .map_with(|(name, pseudo_classes), e| push(e, NodeKind::PageSelector { name }, pseudo_classes));
+
+    group((
+        at_keyword("page").then_ignore(junk()),
+        page_selector
+            .separated_by(symbol(',').padded_by(junk()))
+            .collect::<Vec<_>>()
+            .then_ignore(junk()),
+        page_block,
+    ))
+    .map_with(|(_, selectors, block), e| {
+        push(
+            e,
+            NodeKind::PageAtRule,
+            selectors.into_iter().chain([block]),
+        )
+    })
+}
+
+/// Parses a [`PageMarginAtRule`]
+fn page_margin_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    const PAGE_MARGIN_NAMES: [&str; 16] = [
+        "top-left-corner",
+        "top-left",
+        "top-center",
+        "top-right",
+        "top-right-corner",
+        "bottom-left-corner",
+        "bottom-left",
+        "bottom-center",
+        "bottom-right",
+        "bottom-right-corner",
+        "left-top",
+        "left-middle",
+        "left-bottom",
+        "right-top",
+        "right-middle",
+        "right-bottom",
+    ];
+
+    group((
+        at_ident()
+            .filter(|name| {
+                PAGE_MARGIN_NAMES
+                    .iter()
+                    .any(|margin_name| name.eq_ignore_ascii_case(margin_name))
+            })
+            .then_ignore(junk()),
+        rule_block,
+    ))
+    .map_with(|(name, block), e| push(e, NodeKind::PageMarginAtRule { name }, [block]))
+}
+
+/// Parses a [`NamespaceAtRule`]
+fn namespace_at_rule<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    at_keyword("namespace")
+        .then_ignore(junk())
+        .ignore_then(choice((
+            ident()
+                .then_ignore(junk())
+                .then(url_or_string())
+                .map(|(prefix, url)| (Some(prefix), url)),
+            url_or_string().map(|url| (None, url)),
+        )))
+        .then_ignore(junk())
+        .then_ignore(choice((symbol(';'), end())))
+        .map_with(|(prefix, url), e| push(e, NodeKind::NamespaceAtRule { prefix }, [url]))
+}
+
+/// Parses a [`PropertyAtRule`]
+fn property_at_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    group((
+        at_keyword("property").then_ignore(junk()),
+        ident()
+            .filter(|name| name.starts_with("--"))
+            .then_ignore(junk()),
+        rule_block,
+    ))
+    .map_with(|(_, name, block), e| push(e, NodeKind::PropertyAtRule { name }, [block]))
+}
+
+/// Parses a [`QualifiedRule`]
+fn qualified_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    choice((style_rule(rule_block.clone()), generic_rule(rule_block)))
+}
+
+/// Parses a [`GenericRule`]
+fn generic_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    // Parse the prelude up to eof, semicolon, or block. Eof and semicolon are parse errors, which
+    // are recovered from in `items`.
     let qualified_rule_prelude = any()
         .and_is(
             select_ref!(
@@ -159,50 +825,339 @@ fn qualified_rule<'tokens, 'src: 'tokens>(
         )
         .repeated()
         .to_slice()
-        .map(ListOfComponentValues);
+        .map_with(component_values);
+
+    group((qualified_rule_prelude, rule_block))
+        .map_with(|(prelude, block), e| push(e, NodeKind::GenericRule, [prelude, block]))
+}
+
+/// Parses a [`StyleRule`]
+fn style_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    // Parse the guard up to the block (e.g. `when (@mode = dark)`)
+    let guard = keyword("when")
+        .then(junk())
+        .ignore_then(any().and_is(tree(Delim::Brace).not()).repeated().to_slice())
+        .map_with(|guard, e| component_values(strip_trailing_junk(guard), e));
 
     group((
-        qualified_rule_prelude,
-        // TODO: Deal with eof or semicolon as parse errors
+        selector_list().then_ignore(junk()),
+        guard.or_not(),
         rule_block,
     ))
-    .map(|(prelude, block)| QualifiedRule::Generic(GenericRule { prelude, block }))
+    .map_with(|(selectors, guard, block), e| {
+        push(
+            e,
+            NodeKind::StyleRule,
+            once(selectors).chain(guard).chain([block]),
+        )
+    })
 }
 
-/// Parses a [`Declaration`]
-fn declaration<'tokens, 'src: 'tokens>() -> impl Parser<
+/// Parses a [`SelectorList`]
+fn selector_list<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    selector()
+        .separated_by(symbol(',').padded_by(junk()))
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .map_with(|selectors, e| push(e, NodeKind::SelectorList, selectors))
+}
+
+/// Parses a [`Selector`] with the extends attached to it
+fn selector<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let extend = symbol(':')
+        .then(keyword("extend"))
+        .ignore_then(extend().nested_in(tree(Delim::Paren)))
+        .map_with(respan);
+
+    group((selector_components(), extend.repeated().collect::<Vec<_>>())).map_with(
+        |(components, extends), e| {
+            push(e, NodeKind::Selector, components.into_iter().chain(extends))
+        },
+    )
+}
+
+/// Parses the components of a [`Selector`] (e.g. `.a > .b:hover`)
+fn selector_components<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, Vec<NodeId>, ParserExtra<'tokens, 'src>> + Clone
+{
+    let arguments = select_ref!(TokenTree::Tree(Delim::Paren, tts) => tts.as_slice())
+        .map_with(component_values)
+        .or_not();
+
+    // Parse an id selector, which may continue with interpolations after the hash token (e.g.
+    // `#@{id}` or `#icon-@{name}`)
+    let id = select_ref!(
+        TokenTree::Token(Token::Hash(id)) = e => {
+            let span: Span = e.span();
+            (*id, Span::new(span.start + 1, span.end))
+        }
+    )
+    .then(interpolated_ident().or_not())
+    .map_with(|((id, span), rest), e| {
+        let name = match rest {
+            None => (SelectorName::Ident(id), span),
+            Some(InterpolatedIdent(rest)) => {
+                let first = (!id.is_empty()).then_some((InterpolationPart::Literal(id), span));
+                let name = InterpolatedIdent(first.into_iter().chain(rest).collect());
+                let span = Span::new(span.start, e.span().end);
+                (SelectorName::InterpolatedIdent(name), span)
+            }
+        };
+        let name = push_selector_name(e.state(), name);
+        push(e, NodeKind::Id, [name])
+    });
+
+    let attribute_operator = choice((
+        symbol('=').to(AttributeOperator::Equals),
+        symbol('~')
+            .then(symbol('='))
+            .to(AttributeOperator::Includes),
+        symbol('|')
+            .then(symbol('='))
+            .to(AttributeOperator::DashMatch),
+        symbol('^').then(symbol('=')).to(AttributeOperator::Prefix),
+        symbol('$').then(symbol('=')).to(AttributeOperator::Suffix),
+        symbol('*')
+            .then(symbol('='))
+            .to(AttributeOperator::Substring),
+    ));
+
+    let attribute_value = choice((
+        string().map_with(|string, e| push_interpolated(e, NodeKind::InterpolatedString, string.0)),
+        selector_name().map_with(|name, e| push_selector_name(e.state(), name)),
+    ));
+
+    let attribute_matcher = group((
+        attribute_operator.then_ignore(junk()),
+        attribute_value.then_ignore(junk()),
+        ident().then_ignore(junk()).or_not(),
+    ))
+    .map_with(|(operator, value, modifier), e| {
+        push(
+            e,
+            NodeKind::AttributeMatcher { operator, modifier },
+            [value],
+        )
+    });
+
+    let attribute = group((
+        selector_name()
+            .map_with(|name, e| push_selector_name(e.state(), name))
+            .padded_by(junk()),
+        attribute_matcher.or_not(),
+    ))
+    .nested_in(tree(Delim::Bracket))
+    .map_with(|(name, matcher), e| push(e, NodeKind::Attribute, once(name).chain(matcher)));
+
+    let simple_selector = choice((
+        symbol('&').map_with(|_, e| push(e, NodeKind::Parent, [])),
+        symbol('*').map_with(|_, e| push(e, NodeKind::Universal, [])),
+        // Don't mistake the start of a guard for a type selector
+        selector_name()
+            .filter(|(name, _)| !matches!(name, SelectorName::Ident(name) if name.eq_ignore_ascii_case("when")))
+            .map_with(|name, e| {
+                let name = push_selector_name(e.state(), name);
+                push(e, NodeKind::Type, [name])
+            }),
+        symbol('.')
+            .ignore_then(selector_name())
+            .map_with(|name, e| {
+                let name = push_selector_name(e.state(), name);
+                push(e, NodeKind::Class, [name])
+            }),
+        id,
+        attribute,
+        symbol(':')
+            .then(symbol(':'))
+            .ignore_then(ident())
+            .then(arguments)
+            .map_with(|(name, arguments), e| push(e, NodeKind::PseudoElement { name }, arguments)),
+        // The extend pseudo-class is parsed separately, see `selector`
+        symbol(':')
+            .ignore_then(ident().filter(|name| !name.eq_ignore_ascii_case("extend")))
+            .then(arguments)
+            .map_with(|(name, arguments), e| push(e, NodeKind::PseudoClass { name }, arguments)),
+    ));
+
+    // Parse a compound selector (e.g. `a.b:hover`)
+    let compound_selector = simple_selector.repeated().at_least(1).collect::<Vec<_>>();
+
+    let explicit_combinator = choice((
+        symbol('>').to(Combinator::Child),
+        symbol('+').to(Combinator::NextSibling),
+        symbol('~').to(Combinator::SubsequentSibling),
+    ))
+    .map_with(|combinator, e| push(e, NodeKind::Combinator(combinator), []));
+
+    let descendant_combinator =
+        select_ref!(TokenTree::Token(Token::Whitespace | Token::Comment(_)) => ())
+            .repeated()
+            .at_least(1)
+            .map_with(|_, e| push(e, NodeKind::Combinator(Combinator::Descendant), []));
+
+    let combinator = choice((explicit_combinator.padded_by(junk()), descendant_combinator));
+
+    group((
+        // A selector may start with a combinator when nested (e.g. `> .child`)
+        explicit_combinator.then_ignore(junk()).or_not(),
+        compound_selector.clone(),
+        combinator
+            .then(compound_selector)
+            .repeated()
+            .collect::<Vec<_>>(),
+    ))
+    .map(|(leading_combinator, first, rest)| {
+        let mut components: Vec<_> = leading_combinator.into_iter().chain(first).collect();
+        for (combinator, compound_selector) in rest {
+            components.push(combinator);
+            components.extend(compound_selector);
+        }
+        components
+    })
+}
+
+/// Parses a [`SelectorName`] (e.g. `btn` or `@{prefix}-btn`), to be pushed into the arena with
+/// [`push_selector_name`].
+fn selector_name<'tokens, 'src: 'tokens>() -> impl Parser<
     'tokens,
     ParserInput<'tokens, 'src>,
-    Declaration<'tokens, 'src>,
+    Spanned<SelectorName<&'src str>>,
     ParserExtra<'tokens, 'src>,
 > + Clone {
+    choice((
+        interpolated_ident().map(SelectorName::InterpolatedIdent),
+        ident().map(SelectorName::Ident),
+    ))
+    .map_with(|name, e| (name, e.span()))
+}
+
+/// Parses the arguments of an [`Extend`] (e.g. `.b all, .c`)
+fn extend<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let extend_target = selector_components().map_with(|mut components, e| {
+        let span = e.span();
+        let arena = e.state();
+
+        // The `all` keyword is parsed as a descendant type selector, so split it off here
+        let all = match components.as_slice() {
+            [.., combinator, name] => {
+                arena[*combinator].kind == NodeKind::Combinator(Combinator::Descendant)
+                    && arena[*name].kind == NodeKind::Type
+                    && matches!(
+                        arena[arena.children(*name)[0]].kind,
+                        NodeKind::Ident(keyword) if keyword.eq_ignore_ascii_case("all")
+                    )
+            }
+            _ => false,
+        };
+        let mut selector_span = span;
+        if all {
+            components.truncate(components.len() - 2);
+            selector_span.end = arena[*components.last().unwrap()].span.end;
+        }
+        let selector = arena.push(NodeKind::Selector, selector_span, components);
+        arena.push(NodeKind::ExtendTarget { all }, span, [selector])
+    });
+
+    extend_target
+        .separated_by(symbol(',').padded_by(junk()))
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .padded_by(junk())
+        .map_with(|targets, e| push(e, NodeKind::Extend, targets))
+}
+
+/// Parses a [`KeyframeRule`]
+fn keyframe_rule<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let keyframe_selector = choice((
+        keyword("from").to(KeyframeSelector::From),
+        keyword("to").to(KeyframeSelector::To),
+        select_ref!(TokenTree::Token(Token::Number(number)) => *number)
+            .then_ignore(symbol('%'))
+            .map(KeyframeSelector::Percentage),
+    ))
+    .map_with(|selector, e| push(e, NodeKind::KeyframeSelector(selector), []));
+
+    group((
+        keyframe_selector
+            .separated_by(symbol(',').padded_by(junk()))
+            .at_least(1)
+            .collect::<Vec<_>>()
+            .then_ignore(junk()),
+        rule_block,
+    ))
+    .map_with(|(selectors, block), e| {
+        push(
+            e,
+            NodeKind::KeyframeRule,
+            selectors.into_iter().chain([block]),
+        )
+    })
+}
+
+/// Parses a [`Declaration`]
+fn declaration<'tokens, 'src: 'tokens>(
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    let variable = at_ident().map_with(|name, e| push(e, NodeKind::Variable(name), []));
+
     let declaration_name = choice((
-        ident().map(DeclarationName::Ident),
-        at_ident().map(DeclarationName::Variable),
-        // TODO: Support LESS interpolation in declaration names
+        interpolated_ident()
+            .map_with(|ident, e| push_interpolated(e, NodeKind::InterpolatedIdent, ident.0)),
+        ident().map_with(|name, e| push(e, NodeKind::Ident(name), [])),
+        variable,
     ));
 
-    // Parse component values up to a semicolon or eof
+    // Parse a detached ruleset assigned to a variable (e.g. `@dr: { color: blue; }`), for which the
+    // trailing semicolon is optional
+    let detached_ruleset = group((
+        variable
+            .then_ignore(junk())
+            .then_ignore(symbol(':'))
+            .then_ignore(junk()),
+        rule_block.then_ignore(junk().then(symbol(';')).or_not()),
+    ))
+    .map_with(|(name, block), e| {
+        push(e, NodeKind::Declaration { important: false }, [name, block])
+    });
+
+    // Parse component values up to a semicolon or eof. A block is only allowed as the entire value
+    // (e.g. `--custom: { ... }`), which keeps selectors like `a:hover { ... }` and at-rules like
+    // `@page :first { ... }` from being parsed as declarations.
     let declaration_value = any()
         .and_is(symbol(';').not())
         .repeated()
         .to_slice()
-        .map(ListOfComponentValues);
+        .filter(|value: &&[Spanned<TokenTree<&str>>]| {
+            let value = strip_trailing_junk(value);
+            value.len() == 1
+                || !value
+                    .iter()
+                    .any(|(tt, _)| matches!(tt, TokenTree::Tree(Delim::Brace, _)))
+        });
 
-    group((
+    let declaration = group((
         declaration_name
             .then_ignore(junk())
             .then_ignore(symbol(':'))
             .then_ignore(junk()),
         declaration_value.then_ignore(choice((symbol(';'), end()))),
     ))
-    .map(|(name, mut value)| {
-        value.0 = strip_trailing_junk(value.0);
+    .map_with(|(name, mut value), e| {
+        value = strip_trailing_junk(value);
 
         // Split off the !important flag
         let important = {
             value
-                .0
                 .split_last_chunk::<2>()
                 .filter(|(_, chunk)| {
                     matches!(
@@ -213,44 +1168,112 @@ fn declaration<'tokens, 'src: 'tokens>() -> impl Parser<
                         ]
                     )
                 })
-                .inspect(|(rest_value, _)| value.0 = rest_value)
+                .inspect(|(rest_value, _)| value = rest_value)
                 .is_some()
         };
 
-        value.0 = strip_trailing_junk(value.0);
+        let value = component_values(strip_trailing_junk(value), e);
+        push(e, NodeKind::Declaration { important }, [name, value])
+    });
 
-        Declaration {
-            name,
-            value,
-            important,
-        }
-    })
+    choice((detached_ruleset, declaration))
+}
+
+/// Parses an [`Extend`] item (e.g. `&:extend(.b);`)
+fn extend_statement<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    group((symbol('&'), symbol(':'), keyword("extend")))
+        .ignore_then(extend().nested_in(tree(Delim::Paren)))
+        .then_ignore(junk())
+        .then_ignore(choice((symbol(';'), end())))
 }
 
 /// Parses a [`Call`]
 fn call<'tokens, 'src: 'tokens>(
-) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, Call<'tokens, 'src>, ParserExtra<'tokens, 'src>>
-       + Clone {
+    rule_block: impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>>
+        + Clone,
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
     let call_end = choice((end(), symbol(';')));
 
+    // Parse the value of an argument, which ends at the given separator
+    let argument_value = |separator: char| {
+        choice((
+            rule_block.clone(),
+            select_ref!(TokenTree::Token(Token::Whitespace | Token::Comment(_)) => ())
+                .not()
+                .ignore_then(any())
+                .and_is(symbol(separator).not())
+                .separated_by(junk())
+                .at_least(1)
+                .to_slice()
+                .map_with(component_values),
+        ))
+    };
+
     // Parse a MixinCall
     let mixin_call = {
-        // TODO: Support namespaced selectors (e.g. `.foo.bar` or `#foo > .bar`).
-        let mixin_call_selector = symbol('.')
-            .then(ident())
+        // The selector may be namespaced (e.g. `#ns > .mixin` or `#ns.mixin`)
+        let mixin_name = choice((
+            symbol('.').then(ident()).ignored(),
+            select_ref!(TokenTree::Token(Token::Hash(_)) => ()),
+        ));
+        let mixin_call_selector = mixin_name
+            .separated_by(junk().then(symbol('>').then(junk()).or_not()))
+            .at_least(1)
             .to_slice()
-            .map(ListOfComponentValues);
-        // TODO: Parse mixin arguments
-        let mixin_call_arguments =
-            select_ref!(TokenTree::Tree(Delim::Paren, tts) => tts.as_slice())
-                .map(ListOfComponentValues);
+            .map_with(component_values);
+
+        // Parse a single argument, whose value ends at the given separator
+        let mixin_argument = |separator: char| {
+            let value = argument_value(separator);
+
+            junk().ignore_then(
+                group((
+                    at_ident()
+                        .then_ignore(junk())
+                        .then_ignore(symbol(':'))
+                        .then_ignore(junk())
+                        .or_not(),
+                    value,
+                ))
+                .map_with(|(name, value), e| push(e, NodeKind::MixinArgument { name }, [value])),
+            )
+        };
+
+        // Arguments are separated by semicolons if there are any (e.g. `.m(1, 2; 3)` has two
+        // arguments), and by commas otherwise
+        let mixin_call_arguments = choice((
+            mixin_argument(';')
+                .or_not()
+                .separated_by(symbol(';'))
+                .at_least(2)
+                .collect::<Vec<_>>()
+                .map(|arguments| arguments.into_iter().flatten().collect()),
+            mixin_argument(',')
+                .separated_by(symbol(','))
+                .collect::<Vec<_>>(),
+        ))
+        .then_ignore(junk())
+        .nested_in(tree(Delim::Paren));
+
+        // The parentheses are optional (e.g. `.mixin;`)
+        let important = junk()
+            .then(symbol('!'))
+            .then(keyword("important"))
+            .or_not()
+            .map(|important| important.is_some());
+
         group((
             mixin_call_selector,
-            mixin_call_arguments.then_ignore(call_end),
+            mixin_call_arguments.or_not().map(Option::unwrap_or_default),
+            important.then_ignore(junk()).then_ignore(call_end),
         ))
-        .map(|(selector, arguments)| MixinCall {
-            selector,
-            arguments,
+        .map_with(|(selector, arguments, important), e| {
+            push(
+                e,
+                NodeKind::MixinCall { important },
+                once(selector).chain(arguments),
+            )
         })
     };
 
@@ -258,116 +1281,714 @@ fn call<'tokens, 'src: 'tokens>(
     let variable_call = at_ident()
         .then_ignore(select_ref!(TokenTree::Tree(Delim::Paren, tts) if tts.is_empty() => ()))
         .then_ignore(call_end)
-        .map(|name| VariableCall {
-            name,
-            _lookups: PhantomData,
-        });
+        .map_with(|name, e| push(e, NodeKind::VariableCall { name }, []));
+
+    // Parse a FunctionCallItem, whose arguments may be detached rulesets (e.g. `each(@list, {})`)
+    let function_call_arguments = junk()
+        .ignore_then(argument_value(',').map_with(respan))
+        .then_ignore(junk())
+        .separated_by(symbol(','))
+        .collect::<Vec<_>>()
+        .then_ignore(junk())
+        .nested_in(tree(Delim::Paren));
+    let function_call = group((ident(), function_call_arguments))
+        .map_with(|(name, arguments), e| push(e, NodeKind::FunctionCallItem { name }, arguments))
+        .then_ignore(call_end);
 
-    // Parse a FunctionCall
-    let function_call = group((
+    choice((mixin_call, variable_call, function_call))
+}
+
+/// Parses a function (e.g. `foo(bar)`) as a [`FunctionCall`]
+fn function<'tokens, 'src: 'tokens>(
+) -> impl Parser<'tokens, ParserInput<'tokens, 'src>, NodeId, ParserExtra<'tokens, 'src>> + Clone {
+    group((
         ident(),
         select_ref!(TokenTree::Tree(Delim::Paren, tts) => tts.as_slice())
-            .map(ListOfComponentValues)
-            .then_ignore(call_end),
-    ))
-    .map(|(name, arguments)| FunctionCall { name, arguments });
-
-    choice((
-        mixin_call.map(Call::Mixin),
-        variable_call.map(Call::Variable),
-        function_call.map(Call::Function),
+            .map_with(component_values),
     ))
+    .map_with(|(name, arguments), e| push(e, NodeKind::FunctionCall { name }, [arguments]))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::marker::PhantomData;
+    use chumsky::prelude::*;
+
+    use crate::ast::arena::Arena;
+    use crate::ast::*;
+    use crate::lexer::{lexer, Delim, Span, Token, TokenTree};
+    use crate::parser::parser;
+
+    type ParseErrors<'tokens, 'src> = Vec<Rich<'tokens, TokenTree<&'src str>, Span>>;
+
+    /// Parses token trees into an arena, and converts it into a stylesheet.
+    fn parse_output_errors<'tokens, 'src>(
+        tts: &'tokens [Spanned<TokenTree<&'src str>>],
+    ) -> (
+        Option<Spanned<Stylesheet<&'src str>>>,
+        ParseErrors<'tokens, 'src>,
+    ) {
+        let mut arena = Arena::new();
+        let (root, errors) = parser()
+            .parse_with_state(tts.spanned(Span::splat(tts.len())), &mut arena)
+            .into_output_errors();
+        let output = root.map(|root| {
+            let arena = arena.finish(root);
+            (arena.to_stylesheet(), arena[arena.root()].span)
+        });
+        (output, errors)
+    }
+
+    fn parse_result<'tokens, 'src>(
+        tts: &'tokens [Spanned<TokenTree<&'src str>>],
+    ) -> Result<Spanned<Stylesheet<&'src str>>, ParseErrors<'tokens, 'src>> {
+        match parse_output_errors(tts) {
+            (Some(output), errors) if errors.is_empty() => Ok(output),
+            (_, errors) => Err(errors),
+        }
+    }
+
+    #[test]
+    fn test_item_at_rule() {
+        // Parse an at-rule with no prelude or block
+        let input = "@foo;";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Generic(GenericAtRule {
+                            name: "foo",
+                            prelude: ListOfComponentValues(vec![]),
+                            block: None,
+                        })),
+                        Span::new(0, 5)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse an at-rule with a simple prelude and no block
+        let input = "@foo bar;";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Generic(GenericAtRule {
+                            name: "foo",
+                            prelude: ListOfComponentValues(vec![
+                                (TokenTree::Token(Token::Whitespace), Span::new(4, 5)),
+                                (TokenTree::Token(Token::Ident("bar")), Span::new(5, 8))
+                            ]),
+                            block: None,
+                        })),
+                        Span::new(0, 9)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse an at-rule with a simple prelude and block
+        let input = "@foo bar { @baz; }";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Generic(GenericAtRule {
+                            name: "foo",
+                            prelude: ListOfComponentValues(vec![
+                                (TokenTree::Token(Token::Whitespace), Span::new(4, 5)),
+                                (TokenTree::Token(Token::Ident("bar")), Span::new(5, 8)),
+                                (TokenTree::Token(Token::Whitespace), Span::new(8, 9)),
+                            ]),
+                            block: Some(ListOfItems(vec![(
+                                Item::AtRule(AtRule::Generic(GenericAtRule {
+                                    name: "baz",
+                                    prelude: ListOfComponentValues(vec![]),
+                                    block: None,
+                                })),
+                                Span::new(11, 16)
+                            )])),
+                        })),
+                        Span::new(0, 18)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_keyframes_at_rule() {
+        // Parse a keyframes at-rule with keyframe rules
+        let input = "@keyframes fade { from {} 50%, 100% {} }";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Keyframes(KeyframesAtRule {
+                            vendor_prefix: None,
+                            name: KeyframesName::Ident("fade"),
+                            block: ListOfItems(vec![
+                                (
+                                    Item::QualifiedRule(QualifiedRule::Keyframe(KeyframeRule {
+                                        selectors: vec![(
+                                            KeyframeSelector::From,
+                                            Span::new(18, 22)
+                                        )],
+                                        block: ListOfItems(vec![]),
+                                    })),
+                                    Span::new(18, 25)
+                                ),
+                                (
+                                    Item::QualifiedRule(QualifiedRule::Keyframe(KeyframeRule {
+                                        selectors: vec![
+                                            (KeyframeSelector::Percentage("50"), Span::new(26, 29)),
+                                            (
+                                                KeyframeSelector::Percentage("100"),
+                                                Span::new(31, 35)
+                                            ),
+                                        ],
+                                        block: ListOfItems(vec![]),
+                                    })),
+                                    Span::new(26, 38)
+                                ),
+                            ]),
+                        })),
+                        Span::new(0, 40)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse a vendor-prefixed keyframes at-rule with an interpolated name
+        let input = "@-webkit-keyframes @{name}-in {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Keyframes(KeyframesAtRule {
+                            vendor_prefix: Some("-webkit-"),
+                            name: KeyframesName::InterpolatedIdent(InterpolatedIdent(vec![
+                                (InterpolationPart::Variable("name"), Span::new(19, 26)),
+                                (InterpolationPart::Literal("-in"), Span::new(26, 29)),
+                            ])),
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 32)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_import_at_rule() {
+        // Parse an import at-rule with options and media queries
+        let input = r#"@import (reference, optional) "@{themes}/theme.less" screen;"#;
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Import(ImportAtRule {
+                            options: vec![
+                                (ImportOption::Reference, Span::new(9, 18)),
+                                (ImportOption::Optional, Span::new(20, 28)),
+                            ],
+                            url: (
+                                UrlOrString::String(InterpolatedString(vec![
+                                    (InterpolationPart::Variable("themes"), Span::new(31, 40)),
+                                    (InterpolationPart::Literal("/theme.less"), Span::new(40, 51)),
+                                ])),
+                                Span::new(30, 52)
+                            ),
+                            media_queries: ListOfComponentValues(vec![(
+                                TokenTree::Token(Token::Ident("screen")),
+                                Span::new(53, 59)
+                            )]),
+                        })),
+                        Span::new(0, 60)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse an import at-rule with an unquoted url
+        let input = "@import url(foo.less);";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Import(ImportAtRule {
+                            options: vec![],
+                            url: (
+                                UrlOrString::Url(InterpolatedString(vec![(
+                                    InterpolationPart::Literal("foo.less"),
+                                    Span::new(12, 20)
+                                )])),
+                                Span::new(8, 21)
+                            ),
+                            media_queries: ListOfComponentValues(vec![]),
+                        })),
+                        Span::new(0, 22)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_supports_at_rule() {
+        // Parse a supports at-rule with a negated declaration
+        let input = "@supports not (a: b) {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Supports(SupportsAtRule {
+                            condition: (
+                                Condition::Not(Box::new((
+                                    Condition::Feature(SupportsFeature::Declaration(Declaration {
+                                        name: DeclarationName::Ident("a"),
+                                        value: Value::ComponentValues(ListOfComponentValues(vec![
+                                            (
+                                                TokenTree::Token(Token::Ident("b")),
+                                                Span::new(18, 19)
+                                            )
+                                        ])),
+                                        important: false,
+                                    })),
+                                    Span::new(14, 20)
+                                ))),
+                                Span::new(10, 20)
+                            ),
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 23)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse a supports at-rule with a disjunction
+        let input = "@supports (a: b) or selector(c) {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Supports(SupportsAtRule {
+                            condition: (
+                                Condition::Or(vec![
+                                    (
+                                        Condition::Feature(SupportsFeature::Declaration(
+                                            Declaration {
+                                                name: DeclarationName::Ident("a"),
+                                                value: Value::ComponentValues(
+                                                    ListOfComponentValues(vec![(
+                                                        TokenTree::Token(Token::Ident("b")),
+                                                        Span::new(14, 15)
+                                                    )])
+                                                ),
+                                                important: false,
+                                            }
+                                        )),
+                                        Span::new(10, 16)
+                                    ),
+                                    (
+                                        Condition::Feature(SupportsFeature::Function(
+                                            FunctionCall {
+                                                name: "selector",
+                                                arguments: ListOfComponentValues(vec![(
+                                                    TokenTree::Token(Token::Ident("c")),
+                                                    Span::new(29, 30)
+                                                )]),
+                                            }
+                                        )),
+                                        Span::new(20, 31)
+                                    ),
+                                ]),
+                                Span::new(10, 31)
+                            ),
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 34)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_container_at_rule() {
+        // Parse a named container at-rule with a size feature
+        let input = "@container card (width > 1px) {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Container(ContainerAtRule {
+                            name: Some("card"),
+                            condition: Some((
+                                Condition::Feature(ContainerFeature::Size(ListOfComponentValues(
+                                    vec![
+                                        (
+                                            TokenTree::Token(Token::Ident("width")),
+                                            Span::new(17, 22)
+                                        ),
+                                        (TokenTree::Token(Token::Whitespace), Span::new(22, 23)),
+                                        (TokenTree::Token(Token::Symbol('>')), Span::new(23, 24)),
+                                        (TokenTree::Token(Token::Whitespace), Span::new(24, 25)),
+                                        (TokenTree::Token(Token::Number("1")), Span::new(25, 26)),
+                                        (TokenTree::Token(Token::Ident("px")), Span::new(26, 28)),
+                                    ]
+                                ))),
+                                Span::new(16, 29)
+                            )),
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 32)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_layer_at_rule() {
+        // Parse a layer statement
+        let input = "@layer a, b.c;";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Layer(LayerAtRule {
+                            names: vec![
+                                (LayerName(vec![("a", Span::new(7, 8))]), Span::new(7, 8)),
+                                (
+                                    LayerName(vec![
+                                        ("b", Span::new(10, 11)),
+                                        ("c", Span::new(12, 13))
+                                    ]),
+                                    Span::new(10, 13)
+                                ),
+                            ],
+                            block: None,
+                        })),
+                        Span::new(0, 14)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse a layer block
+        let input = "@layer a {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Layer(LayerAtRule {
+                            names: vec![(LayerName(vec![("a", Span::new(7, 8))]), Span::new(7, 8))],
+                            block: Some(ListOfItems(vec![])),
+                        })),
+                        Span::new(0, 11)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_font_face_at_rule() {
+        // Parse a font-face at-rule
+        let input = "@font-face {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::FontFace(FontFaceAtRule {
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 13)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
 
-    use chumsky::prelude::*;
+    #[test]
+    fn test_item_page_at_rule() {
+        // Parse a page at-rule with a margin at-rule
+        let input = "@page :first { @top-left {} }";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Page(PageAtRule {
+                            selectors: vec![(
+                                PageSelector {
+                                    name: None,
+                                    pseudo_classes: vec![("first", Span::new(6, 12))],
+                                },
+                                Span::new(6, 12)
+                            )],
+                            block: ListOfItems(vec![(
+                                Item::AtRule(AtRule::PageMargin(PageMarginAtRule {
+                                    name: "top-left",
+                                    block: ListOfItems(vec![]),
+                                })),
+                                Span::new(15, 27)
+                            )]),
+                        })),
+                        Span::new(0, 29)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
 
-    use crate::ast::*;
-    use crate::lexer::{lexer, Span, Token, TokenTree};
-    use crate::parser::parser;
+    #[test]
+    fn test_item_namespace_at_rule() {
+        // Parse a namespace at-rule with a prefix
+        let input = "@namespace svg url(x);";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::AtRule(AtRule::Namespace(NamespaceAtRule {
+                            prefix: Some("svg"),
+                            url: (
+                                UrlOrString::Url(InterpolatedString(vec![(
+                                    InterpolationPart::Literal("x"),
+                                    Span::new(19, 20)
+                                )])),
+                                Span::new(15, 21)
+                            ),
+                        })),
+                        Span::new(0, 22)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
 
     #[test]
-    fn test_item_at_rule() {
-        // Parse an at-rule with no prelude or block
-        let input = "@foo;";
+    fn test_item_property_at_rule() {
+        // Parse a property at-rule
+        let input = "@property --a {}";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
-                        Item::AtRule(AtRule::Generic(GenericAtRule {
-                            name: "foo",
-                            prelude: ListOfComponentValues(&[]),
-                            block: None,
+                        Item::AtRule(AtRule::Property(PropertyAtRule {
+                            name: "--a",
+                            block: ListOfItems(vec![]),
                         })),
-                        Span::new(0, 5)
+                        Span::new(0, 16)
                     )])
                 },
                 Span::new(0, input.len())
             ))
         );
+    }
 
-        // Parse an at-rule with a simple prelude and no block
-        let input = "@foo bar;";
+    #[test]
+    fn test_item_interpolation() {
+        // Parse interpolations in class, id and attribute selectors
+        let input = ".@{p}-btn#@{id}[data-@{a}=\"x@{b}\" i] {}";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
-                        Item::AtRule(AtRule::Generic(GenericAtRule {
-                            name: "foo",
-                            prelude: ListOfComponentValues(&[
-                                (TokenTree::Token(Token::Whitespace), Span::new(4, 5)),
-                                (TokenTree::Token(Token::Ident("bar")), Span::new(5, 8))
-                            ]),
-                            block: None,
+                        Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                            selectors: SelectorList(vec![(
+                                Selector {
+                                    components: vec![
+                                        (
+                                            SelectorComponent::Class(
+                                                SelectorName::InterpolatedIdent(InterpolatedIdent(
+                                                    vec![
+                                                        (
+                                                            InterpolationPart::Variable("p"),
+                                                            Span::new(1, 5)
+                                                        ),
+                                                        (
+                                                            InterpolationPart::Literal("-btn"),
+                                                            Span::new(5, 9)
+                                                        ),
+                                                    ]
+                                                ))
+                                            ),
+                                            Span::new(0, 9)
+                                        ),
+                                        (
+                                            SelectorComponent::Id(SelectorName::InterpolatedIdent(
+                                                InterpolatedIdent(vec![(
+                                                    InterpolationPart::Variable("id"),
+                                                    Span::new(10, 15)
+                                                )])
+                                            )),
+                                            Span::new(9, 15)
+                                        ),
+                                        (
+                                            SelectorComponent::Attribute {
+                                                name: SelectorName::InterpolatedIdent(
+                                                    InterpolatedIdent(vec![
+                                                        (
+                                                            InterpolationPart::Literal("data-"),
+                                                            Span::new(16, 21)
+                                                        ),
+                                                        (
+                                                            InterpolationPart::Variable("a"),
+                                                            Span::new(21, 25)
+                                                        ),
+                                                    ])
+                                                ),
+                                                matcher: Some(AttributeMatcher {
+                                                    operator: AttributeOperator::Equals,
+                                                    value: AttributeValue::String(
+                                                        InterpolatedString(vec![
+                                                            (
+                                                                InterpolationPart::Literal("x"),
+                                                                Span::new(27, 28)
+                                                            ),
+                                                            (
+                                                                InterpolationPart::Variable("b"),
+                                                                Span::new(28, 32)
+                                                            ),
+                                                        ])
+                                                    ),
+                                                    modifier: Some("i"),
+                                                }),
+                                            },
+                                            Span::new(15, 36)
+                                        ),
+                                    ],
+                                    extends: vec![],
+                                },
+                                Span::new(0, 36)
+                            )]),
+                            guard: None,
+                            block: ListOfItems(vec![]),
                         })),
-                        Span::new(0, 9)
+                        Span::new(0, 39)
                     )])
                 },
                 Span::new(0, input.len())
             ))
         );
 
-        // Parse an at-rule with a simple prelude and block
-        let input = "@foo bar { @baz; }";
+        // Parse an interpolation in a declaration name
+        let input = "border-@{side}: blue;";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::Declaration(Declaration {
+                            name: DeclarationName::InterpolatedIdent(InterpolatedIdent(vec![
+                                (InterpolationPart::Literal("border-"), Span::new(0, 7)),
+                                (InterpolationPart::Variable("side"), Span::new(7, 14)),
+                            ])),
+                            value: Value::ComponentValues(ListOfComponentValues(vec![(
+                                TokenTree::Token(Token::Ident("blue")),
+                                Span::new(16, 20)
+                            )])),
+                            important: false,
+                        }),
+                        Span::new(0, 21)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Interpolations in an at-rule's prelude are kept as explicit tokens
+        let input = "@media @{q} {}";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
                         Item::AtRule(AtRule::Generic(GenericAtRule {
-                            name: "foo",
-                            prelude: ListOfComponentValues(&[
-                                (TokenTree::Token(Token::Whitespace), Span::new(4, 5)),
-                                (TokenTree::Token(Token::Ident("bar")), Span::new(5, 8)),
-                                (TokenTree::Token(Token::Whitespace), Span::new(8, 9)),
+                            name: "media",
+                            prelude: ListOfComponentValues(vec![
+                                (TokenTree::Token(Token::Whitespace), Span::new(6, 7)),
+                                (
+                                    TokenTree::Token(Token::Interpolation("q")),
+                                    Span::new(7, 11)
+                                ),
+                                (TokenTree::Token(Token::Whitespace), Span::new(11, 12)),
                             ]),
-                            block: Some(ListOfItems(vec![(
-                                Item::AtRule(AtRule::Generic(GenericAtRule {
-                                    name: "baz",
-                                    prelude: ListOfComponentValues(&[]),
-                                    block: None,
-                                })),
-                                Span::new(11, 16)
-                            )])),
+                            block: Some(ListOfItems(vec![])),
                         })),
-                        Span::new(0, 18)
+                        Span::new(0, 14)
                     )])
                 },
                 Span::new(0, input.len())
@@ -375,14 +1996,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_item_recovery() {
+        // Skip an invalid item up to the next semicolon, keeping the items around it
+        let input = ".x { a: b; 1 2; c: d; } e: f;";
+        let tts = lexer().parse(input).unwrap();
+        let (output, errors) = parse_output_errors(&tts);
+        assert_eq!(
+            output,
+            Some((
+                Stylesheet {
+                    items: ListOfItems(vec![
+                        (
+                            Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                                selectors: SelectorList(vec![(
+                                    Selector {
+                                        components: vec![(
+                                            SelectorComponent::Class(SelectorName::Ident("x")),
+                                            Span::new(0, 2)
+                                        )],
+                                        extends: vec![],
+                                    },
+                                    Span::new(0, 2)
+                                )]),
+                                guard: None,
+                                block: ListOfItems(vec![
+                                    (
+                                        Item::Declaration(Declaration {
+                                            name: DeclarationName::Ident("a"),
+                                            value: Value::ComponentValues(ListOfComponentValues(
+                                                vec![(
+                                                    TokenTree::Token(Token::Ident("b")),
+                                                    Span::new(8, 9)
+                                                )]
+                                            )),
+                                            important: false,
+                                        }),
+                                        Span::new(5, 10)
+                                    ),
+                                    (
+                                        Item::Declaration(Declaration {
+                                            name: DeclarationName::Ident("c"),
+                                            value: Value::ComponentValues(ListOfComponentValues(
+                                                vec![(
+                                                    TokenTree::Token(Token::Ident("d")),
+                                                    Span::new(19, 20)
+                                                )]
+                                            )),
+                                            important: false,
+                                        }),
+                                        Span::new(16, 21)
+                                    ),
+                                ]),
+                            })),
+                            Span::new(0, 23)
+                        ),
+                        (
+                            Item::Declaration(Declaration {
+                                name: DeclarationName::Ident("e"),
+                                value: Value::ComponentValues(ListOfComponentValues(vec![(
+                                    TokenTree::Token(Token::Ident("f")),
+                                    Span::new(27, 28)
+                                )])),
+                                important: false,
+                            }),
+                            Span::new(24, 29)
+                        ),
+                    ])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+        assert_eq!(
+            errors.iter().map(|error| *error.span()).collect::<Vec<_>>(),
+            vec![Span::new(14, 15)]
+        );
+    }
+
     #[test]
     fn test_item_variable_declaration() {
         // Parse a variable declaration
         let input = "@foo: bar;";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
@@ -390,10 +2086,10 @@ mod tests {
                     items: ListOfItems(vec![(
                         Item::Declaration(Declaration {
                             name: DeclarationName::Variable("foo"),
-                            value: ListOfComponentValues(&[(
+                            value: Value::ComponentValues(ListOfComponentValues(vec![(
                                 TokenTree::Token(Token::Ident("bar")),
                                 Span::new(6, 9)
-                            )]),
+                            )])),
                             important: false,
                         }),
                         Span::new(0, 10)
@@ -402,6 +2098,37 @@ mod tests {
                 Span::new(0, input.len())
             ))
         );
+
+        // Parse a detached ruleset, for which the semicolon is optional
+        let input = "@dr: { a: b; }";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::Declaration(Declaration {
+                            name: DeclarationName::Variable("dr"),
+                            value: Value::DetachedRuleset(ListOfItems(vec![(
+                                Item::Declaration(Declaration {
+                                    name: DeclarationName::Ident("a"),
+                                    value: Value::ComponentValues(ListOfComponentValues(vec![(
+                                        TokenTree::Token(Token::Ident("b")),
+                                        Span::new(10, 11)
+                                    )])),
+                                    important: false,
+                                }),
+                                Span::new(7, 12)
+                            )])),
+                            important: false,
+                        }),
+                        Span::new(0, 14)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
     }
 
     #[test]
@@ -409,9 +2136,7 @@ mod tests {
         // Parse a declaration
         let input = "foo: bar;";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
@@ -419,10 +2144,10 @@ mod tests {
                     items: ListOfItems(vec![(
                         Item::Declaration(Declaration {
                             name: DeclarationName::Ident("foo"),
-                            value: ListOfComponentValues(&[(
+                            value: Value::ComponentValues(ListOfComponentValues(vec![(
                                 TokenTree::Token(Token::Ident("bar")),
                                 Span::new(5, 8)
-                            )]),
+                            )])),
                             important: false,
                         }),
                         Span::new(0, 9)
@@ -435,9 +2160,7 @@ mod tests {
         // Parse a declaration with important
         let input = "foo: bar !important;";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
@@ -445,10 +2168,10 @@ mod tests {
                     items: ListOfItems(vec![(
                         Item::Declaration(Declaration {
                             name: DeclarationName::Ident("foo"),
-                            value: ListOfComponentValues(&[(
+                            value: Value::ComponentValues(ListOfComponentValues(vec![(
                                 TokenTree::Token(Token::Ident("bar")),
                                 Span::new(5, 8)
-                            )]),
+                            )])),
                             important: true,
                         }),
                         Span::new(0, 20)
@@ -464,26 +2187,31 @@ mod tests {
         // Parse a qualified rule
         let input = "foo { bar: baz; }";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
-                        Item::QualifiedRule(QualifiedRule::Generic(GenericRule {
-                            prelude: ListOfComponentValues(&[
-                                (TokenTree::Token(Token::Ident("foo")), Span::new(0, 3)),
-                                (TokenTree::Token(Token::Whitespace), Span::new(3, 4)),
-                            ]),
+                        Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                            selectors: SelectorList(vec![(
+                                Selector {
+                                    components: vec![(
+                                        SelectorComponent::Type(SelectorName::Ident("foo")),
+                                        Span::new(0, 3)
+                                    )],
+                                    extends: vec![],
+                                },
+                                Span::new(0, 3)
+                            )]),
+                            guard: None,
                             block: ListOfItems(vec![(
                                 Item::Declaration(Declaration {
                                     name: DeclarationName::Ident("bar"),
-                                    value: ListOfComponentValues(&[(
+                                    value: Value::ComponentValues(ListOfComponentValues(vec![(
                                         TokenTree::Token(Token::Ident("baz")),
                                         Span::new(11, 14)
-                                    )]),
+                                    )])),
                                     important: false,
                                 }),
                                 Span::new(6, 15)
@@ -495,6 +2223,196 @@ mod tests {
                 Span::new(0, input.len())
             ))
         );
+
+        // Parse a qualified rule whose prelude looks like a declaration
+        let input = "a:hover {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                            selectors: SelectorList(vec![(
+                                Selector {
+                                    components: vec![
+                                        (
+                                            SelectorComponent::Type(SelectorName::Ident("a")),
+                                            Span::new(0, 1)
+                                        ),
+                                        (
+                                            SelectorComponent::PseudoClass {
+                                                name: "hover",
+                                                arguments: None,
+                                            },
+                                            Span::new(1, 7)
+                                        ),
+                                    ],
+                                    extends: vec![],
+                                },
+                                Span::new(0, 7)
+                            )]),
+                            guard: None,
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 10)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse a qualified rule with combinators and a guard
+        let input = ".a > b when (c) {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                            selectors: SelectorList(vec![(
+                                Selector {
+                                    components: vec![
+                                        (
+                                            SelectorComponent::Class(SelectorName::Ident("a")),
+                                            Span::new(0, 2)
+                                        ),
+                                        (
+                                            SelectorComponent::Combinator(Combinator::Child),
+                                            Span::new(3, 4)
+                                        ),
+                                        (
+                                            SelectorComponent::Type(SelectorName::Ident("b")),
+                                            Span::new(5, 6)
+                                        ),
+                                    ],
+                                    extends: vec![],
+                                },
+                                Span::new(0, 6)
+                            )]),
+                            guard: Some(ListOfComponentValues(vec![(
+                                TokenTree::Tree(
+                                    Delim::Paren,
+                                    vec![(TokenTree::Token(Token::Ident("c")), Span::new(13, 14))]
+                                ),
+                                Span::new(12, 15)
+                            )])),
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 18)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse a qualified rule that isn't a style rule
+        let input = ".m(@a) {}";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::QualifiedRule(QualifiedRule::Generic(GenericRule {
+                            prelude: ListOfComponentValues(vec![
+                                (TokenTree::Token(Token::Symbol('.')), Span::new(0, 1)),
+                                (TokenTree::Token(Token::Ident("m")), Span::new(1, 2)),
+                                (
+                                    TokenTree::Tree(
+                                        Delim::Paren,
+                                        vec![
+                                            (TokenTree::Token(Token::Symbol('@')), Span::new(3, 4)),
+                                            (TokenTree::Token(Token::Ident("a")), Span::new(4, 5)),
+                                        ]
+                                    ),
+                                    Span::new(2, 6)
+                                ),
+                                (TokenTree::Token(Token::Whitespace), Span::new(6, 7)),
+                            ]),
+                            block: ListOfItems(vec![]),
+                        })),
+                        Span::new(0, 9)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_item_extend() {
+        // Parse extends attached to a selector and as an item
+        let input = ".a:extend(.b all) { &:extend(.c); }";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::QualifiedRule(QualifiedRule::Style(StyleRule {
+                            selectors: SelectorList(vec![(
+                                Selector {
+                                    components: vec![(
+                                        SelectorComponent::Class(SelectorName::Ident("a")),
+                                        Span::new(0, 2)
+                                    )],
+                                    extends: vec![(
+                                        Extend {
+                                            targets: vec![(
+                                                ExtendTarget {
+                                                    selector: Selector {
+                                                        components: vec![(
+                                                            SelectorComponent::Class(
+                                                                SelectorName::Ident("b")
+                                                            ),
+                                                            Span::new(10, 12)
+                                                        )],
+                                                        extends: vec![],
+                                                    },
+                                                    all: true,
+                                                },
+                                                Span::new(10, 16)
+                                            )],
+                                        },
+                                        Span::new(2, 17)
+                                    )],
+                                },
+                                Span::new(0, 17)
+                            )]),
+                            guard: None,
+                            block: ListOfItems(vec![(
+                                Item::Extend(Extend {
+                                    targets: vec![(
+                                        ExtendTarget {
+                                            selector: Selector {
+                                                components: vec![(
+                                                    SelectorComponent::Class(SelectorName::Ident(
+                                                        "c"
+                                                    )),
+                                                    Span::new(29, 31)
+                                                )],
+                                                extends: vec![],
+                                            },
+                                            all: false,
+                                        },
+                                        Span::new(29, 31)
+                                    )],
+                                }),
+                                Span::new(20, 33)
+                            )]),
+                        })),
+                        Span::new(0, 35)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
     }
 
     #[test]
@@ -502,26 +2420,28 @@ mod tests {
         // Parse a mixin call
         let input = ".foo(@arg: blue);";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
                         Item::Call(Call::Mixin(MixinCall {
-                            selector: ListOfComponentValues(&[
+                            selector: ListOfComponentValues(vec![
                                 (TokenTree::Token(Token::Symbol('.')), Span::new(0, 1)),
                                 (TokenTree::Token(Token::Ident("foo")), Span::new(1, 4))
                             ]),
-                            arguments: ListOfComponentValues(&[
-                                (TokenTree::Token(Token::Symbol('@')), Span::new(5, 6)),
-                                (TokenTree::Token(Token::Ident("arg")), Span::new(6, 9)),
-                                (TokenTree::Token(Token::Symbol(':')), Span::new(9, 10)),
-                                (TokenTree::Token(Token::Whitespace), Span::new(10, 11)),
-                                (TokenTree::Token(Token::Ident("blue")), Span::new(11, 15)),
-                            ]),
+                            arguments: vec![(
+                                MixinArgument {
+                                    name: Some("arg"),
+                                    value: Value::ComponentValues(ListOfComponentValues(vec![(
+                                        TokenTree::Token(Token::Ident("blue")),
+                                        Span::new(11, 15)
+                                    )])),
+                                },
+                                Span::new(5, 15)
+                            )],
+                            important: false,
                         })),
                         Span::new(0, 17)
                     )])
@@ -530,21 +2450,100 @@ mod tests {
             ))
         );
 
+        // Parse a mixin call with semicolon-separated arguments and a detached ruleset
+        let input = ".m(1, 2; { a: b; });";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::Call(Call::Mixin(MixinCall {
+                            selector: ListOfComponentValues(vec![
+                                (TokenTree::Token(Token::Symbol('.')), Span::new(0, 1)),
+                                (TokenTree::Token(Token::Ident("m")), Span::new(1, 2))
+                            ]),
+                            arguments: vec![
+                                (
+                                    MixinArgument {
+                                        name: None,
+                                        value: Value::ComponentValues(ListOfComponentValues(vec![
+                                            (TokenTree::Token(Token::Number("1")), Span::new(3, 4)),
+                                            (TokenTree::Token(Token::Symbol(',')), Span::new(4, 5)),
+                                            (TokenTree::Token(Token::Whitespace), Span::new(5, 6)),
+                                            (TokenTree::Token(Token::Number("2")), Span::new(6, 7)),
+                                        ])),
+                                    },
+                                    Span::new(3, 7)
+                                ),
+                                (
+                                    MixinArgument {
+                                        name: None,
+                                        value: Value::DetachedRuleset(ListOfItems(vec![(
+                                            Item::Declaration(Declaration {
+                                                name: DeclarationName::Ident("a"),
+                                                value: Value::ComponentValues(
+                                                    ListOfComponentValues(vec![(
+                                                        TokenTree::Token(Token::Ident("b")),
+                                                        Span::new(14, 15)
+                                                    )])
+                                                ),
+                                                important: false,
+                                            }),
+                                            Span::new(11, 16)
+                                        )])),
+                                    },
+                                    Span::new(9, 18)
+                                ),
+                            ],
+                            important: false,
+                        })),
+                        Span::new(0, 20)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
+        // Parse a namespaced mixin call without arguments that is made important
+        let input = "#ns > .m !important;";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::Call(Call::Mixin(MixinCall {
+                            selector: ListOfComponentValues(vec![
+                                (TokenTree::Token(Token::Hash("ns")), Span::new(0, 3)),
+                                (TokenTree::Token(Token::Whitespace), Span::new(3, 4)),
+                                (TokenTree::Token(Token::Symbol('>')), Span::new(4, 5)),
+                                (TokenTree::Token(Token::Whitespace), Span::new(5, 6)),
+                                (TokenTree::Token(Token::Symbol('.')), Span::new(6, 7)),
+                                (TokenTree::Token(Token::Ident("m")), Span::new(7, 8))
+                            ]),
+                            arguments: vec![],
+                            important: true,
+                        })),
+                        Span::new(0, 20)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
+
         // Parse a variable call
         let input = "@foo();";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
-                        Item::Call(Call::Variable(VariableCall {
-                            name: "foo",
-                            _lookups: PhantomData,
-                        })),
+                        Item::Call(Call::Variable(VariableCall { name: "foo" })),
                         Span::new(0, 7)
                     )])
                 },
@@ -555,17 +2554,15 @@ mod tests {
         // Parse a function call
         let input = "foo();";
         let tts = lexer().parse(input).unwrap();
-        let result = parser()
-            .parse((&tts).spanned(Span::splat(tts.len())))
-            .into_result();
+        let result = parse_result(&tts);
         assert_eq!(
             result,
             Ok((
                 Stylesheet {
                     items: ListOfItems(vec![(
-                        Item::Call(Call::Function(FunctionCall {
+                        Item::Call(Call::Function(FunctionCallItem {
                             name: "foo",
-                            arguments: ListOfComponentValues(&[]),
+                            arguments: vec![],
                         })),
                         Span::new(0, 6)
                     )])
@@ -573,5 +2570,55 @@ mod tests {
                 Span::new(0, input.len())
             ))
         );
+
+        // Parse a function call with a detached ruleset
+        let input = "each(@list, { a: @value; });";
+        let tts = lexer().parse(input).unwrap();
+        let result = parse_result(&tts);
+        assert_eq!(
+            result,
+            Ok((
+                Stylesheet {
+                    items: ListOfItems(vec![(
+                        Item::Call(Call::Function(FunctionCallItem {
+                            name: "each",
+                            arguments: vec![
+                                (
+                                    Value::ComponentValues(ListOfComponentValues(vec![
+                                        (TokenTree::Token(Token::Symbol('@')), Span::new(5, 6)),
+                                        (TokenTree::Token(Token::Ident("list")), Span::new(6, 10)),
+                                    ])),
+                                    Span::new(5, 10)
+                                ),
+                                (
+                                    Value::DetachedRuleset(ListOfItems(vec![(
+                                        Item::Declaration(Declaration {
+                                            name: DeclarationName::Ident("a"),
+                                            value: Value::ComponentValues(ListOfComponentValues(
+                                                vec![
+                                                    (
+                                                        TokenTree::Token(Token::Symbol('@')),
+                                                        Span::new(17, 18)
+                                                    ),
+                                                    (
+                                                        TokenTree::Token(Token::Ident("value")),
+                                                        Span::new(18, 23)
+                                                    ),
+                                                ]
+                                            )),
+                                            important: false,
+                                        }),
+                                        Span::new(14, 24)
+                                    )])),
+                                    Span::new(12, 26)
+                                ),
+                            ],
+                        })),
+                        Span::new(0, 28)
+                    )])
+                },
+                Span::new(0, input.len())
+            ))
+        );
     }
 }
@@ -0,0 +1,850 @@
+//! Printing of the AST back to LESS source.
+
+use std::collections::HashMap;
+
+use crate::ast::visit::{walk_item, walk_list_of_items, Visit};
+use crate::ast::*;
+use crate::lexer::{Span, Spanned, Token, TokenTree};
+
+impl<S: AsRef<str> + PartialEq> Stylesheet<S> {
+    /// Prints the stylesheet as LESS source, with one item per line and nested blocks indented.
+    pub fn to_less(&self) -> String {
+        let mut printer = Printer::new(None);
+        printer.stylesheet(self);
+        printer.out
+    }
+
+    /// Prints the stylesheet as LESS source, like [`to_less`](Self::to_less), but copies the source
+    /// text of items that are unchanged from `original`, which was parsed from `source`, and of the
+    /// whitespace and comments between items that were neighbours in `original`, changed or not.
+    /// This keeps the formatting and comments of everything a transform didn't touch, so an
+    /// unchanged stylesheet is printed as `source`.
+    pub fn to_less_with_source(&self, original: &Stylesheet<S>, source: &str) -> String {
+        let mut items = OriginalItems {
+            items: HashMap::new(),
+            next: HashMap::new(),
+        };
+        items.visit_stylesheet(original);
+        // The start and end of the source are the neighbours of the first and last items
+        let spans = std::iter::once((0, 0))
+            .chain(
+                original
+                    .items
+                    .0
+                    .iter()
+                    .map(|(_, span)| (span.start, span.end)),
+            )
+            .chain(std::iter::once((source.len(), source.len())))
+            .collect::<Vec<_>>();
+        for pair in spans.windows(2) {
+            items.next.insert(pair[0], pair[1]);
+        }
+        let mut printer = Printer::new(Some(Original {
+            items: items.items,
+            next: items.next,
+            source,
+        }));
+        printer.stylesheet(self);
+        printer.out
+    }
+}
+
+/// Prints a selector as LESS (and CSS) source.
+pub(crate) fn selector_to_string<S: AsRef<str> + PartialEq>(selector: &Selector<S>) -> String {
+    let mut printer = Printer::new(None);
+    printer.selector(selector);
+    printer.out
+}
+
+/// Prints an at-rule as LESS (and CSS) source, without its block or terminating semicolon.
+pub(crate) fn at_rule_header<S: AsRef<str> + PartialEq>(at_rule: &AtRule<S>) -> String {
+    let mut printer = Printer::new(None);
+    printer.at_rule_header(at_rule);
+    printer.out
+}
+
+/// Collects the items of the original stylesheet by their span, and the span of the item after
+/// each one in the same block.
+struct OriginalItems<'a, S> {
+    items: HashMap<(usize, usize), &'a Item<S>>,
+    next: HashMap<(usize, usize), (usize, usize)>,
+}
+
+impl<'a, S> Visit<'a, S> for OriginalItems<'a, S> {
+    fn visit_list_of_items(&mut self, items: &'a ListOfItems<S>) {
+        for pair in items.0.windows(2) {
+            let (previous, next) = (pair[0].1, pair[1].1);
+            self.next
+                .insert((previous.start, previous.end), (next.start, next.end));
+        }
+        walk_list_of_items(self, items);
+    }
+
+    fn visit_item(&mut self, item: &'a Item<S>, span: Span) {
+        self.items.insert((span.start, span.end), item);
+        walk_item(self, item, span);
+    }
+}
+
+struct Original<'a, S> {
+    items: HashMap<(usize, usize), &'a Item<S>>,
+    next: HashMap<(usize, usize), (usize, usize)>,
+    source: &'a str,
+}
+
+struct Printer<'a, S> {
+    out: String,
+    indent: usize,
+    original: Option<Original<'a, S>>,
+}
+
+impl<'a, S: AsRef<str> + PartialEq> Printer<'a, S> {
+    fn new(original: Option<Original<'a, S>>) -> Self {
+        Self {
+            out: String::new(),
+            indent: 0,
+            original,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    fn stylesheet(&mut self, stylesheet: &Stylesheet<S>) {
+        // The source text before the first item and after the last one is kept even if they changed
+        let key = |(_, span): &Spanned<Item<S>>| (span.start, span.end);
+        let (first, last) = (stylesheet.items.0.first(), stylesheet.items.0.last());
+        let end = self
+            .original
+            .as_ref()
+            .map(|original| (original.source.len(), original.source.len()));
+        if let Some(gap) = self.gap(Some((0, 0)), first.map(key)) {
+            self.push(gap);
+        }
+        self.items(&stylesheet.items, "\n");
+        match self.gap(last.map(key), end) {
+            Some(gap) => self.push(gap),
+            None if last.is_some() => self.push("\n"),
+            None => {}
+        }
+    }
+
+    /// Prints a list of items separated by `separator`, or by the source text between them if they
+    /// are neighbours in the original.
+    fn items(&mut self, items: &ListOfItems<S>, separator: &str) {
+        let mut previous = None;
+        for (i, (item, span)) in items.0.iter().enumerate() {
+            let current = Some((span.start, span.end));
+            match self.gap(previous, current) {
+                Some(gap) => self.push(gap),
+                None if i > 0 => self.push(separator),
+                None => {}
+            }
+            self.item(item, *span);
+            previous = current;
+        }
+    }
+
+    /// Returns the source text between two spans if they are neighbours in the original.
+    fn gap(
+        &self,
+        previous: Option<(usize, usize)>,
+        next: Option<(usize, usize)>,
+    ) -> Option<&'a str> {
+        let original = self.original.as_ref()?;
+        let (previous, next) = (previous?, next?);
+        let source = original.source;
+        (original.next.get(&previous) == Some(&next)).then(|| &source[previous.1..next.0])
+    }
+
+    /// Returns the source text of an item if it's unchanged from the original.
+    fn original_text(&self, item: &Item<S>, span: Span) -> Option<&'a str> {
+        let original = self.original.as_ref()?;
+        let source = original.source;
+        (original.items.get(&(span.start, span.end)) == Some(&item))
+            .then(|| &source[span.into_range()])
+    }
+
+    /// Prints a block (e.g. ` { color: blue; }`), including the space before it.
+    fn block(&mut self, items: &ListOfItems<S>) {
+        self.push(" ");
+        self.braces(items);
+    }
+
+    fn braces(&mut self, items: &ListOfItems<S>) {
+        if items.0.is_empty() {
+            self.push("{}");
+            return;
+        }
+
+        self.indent += 1;
+        let separator = format!("\n{}", "  ".repeat(self.indent));
+        self.push("{");
+        self.push(&separator);
+        self.items(items, &separator);
+        self.indent -= 1;
+        self.push("\n");
+        self.push(&"  ".repeat(self.indent));
+        self.push("}");
+    }
+
+    /// Prints an item, including its terminating semicolon (if any).
+    fn item(&mut self, item: &Item<S>, span: Span) {
+        if let Some(text) = self.original_text(item, span) {
+            self.push(text);
+            return;
+        }
+
+        match item {
+            Item::AtRule(at_rule) => self.at_rule(at_rule),
+            Item::QualifiedRule(rule) => self.qualified_rule(rule),
+            Item::Declaration(declaration) => self.declaration(declaration),
+            Item::Call(call) => {
+                self.call(call);
+                self.push(";");
+            }
+            Item::Extend(extend) => {
+                self.push("&");
+                self.extend(extend);
+                self.push(";");
+            }
+        }
+    }
+
+    // AT-RULES
+
+    fn at_rule(&mut self, at_rule: &AtRule<S>) {
+        self.at_rule_header(at_rule);
+        match at_rule.block() {
+            Some(block) => self.block(block),
+            None => self.push(";"),
+        }
+    }
+
+    /// Prints an at-rule without its block or terminating semicolon.
+    fn at_rule_header(&mut self, at_rule: &AtRule<S>) {
+        match at_rule {
+            AtRule::Generic(rule) => {
+                self.push("@");
+                self.push(rule.name.as_ref());
+                if !trim(&rule.prelude.0).is_empty() {
+                    self.push(" ");
+                    self.component_values(&rule.prelude);
+                }
+            }
+            AtRule::Keyframes(rule) => {
+                self.push("@");
+                if let Some(prefix) = &rule.vendor_prefix {
+                    self.push(prefix.as_ref());
+                }
+                self.push("keyframes ");
+                match &rule.name {
+                    KeyframesName::Ident(name) => self.push(name.as_ref()),
+                    KeyframesName::String(name) => self.string(name.as_ref()),
+                    KeyframesName::Variable(name) => {
+                        self.push("@");
+                        self.push(name.as_ref());
+                    }
+                    KeyframesName::InterpolatedIdent(ident) => self.interpolation_parts(&ident.0),
+                }
+            }
+            AtRule::Import(rule) => {
+                self.push("@import ");
+                if !rule.options.is_empty() {
+                    self.push("(");
+                    for (i, (option, _)) in rule.options.iter().enumerate() {
+                        if i > 0 {
+                            self.push(", ");
+                        }
+                        self.push(match option {
+                            ImportOption::Reference => "reference",
+                            ImportOption::Inline => "inline",
+                            ImportOption::Less => "less",
+                            ImportOption::Css => "css",
+                            ImportOption::Once => "once",
+                            ImportOption::Multiple => "multiple",
+                            ImportOption::Optional => "optional",
+                        });
+                    }
+                    self.push(") ");
+                }
+                self.url_or_string(&rule.url.0);
+                if !trim(&rule.media_queries.0).is_empty() {
+                    self.push(" ");
+                    self.component_values(&rule.media_queries);
+                }
+            }
+            AtRule::Supports(rule) => {
+                self.push("@supports ");
+                self.condition(&rule.condition.0, &mut |printer, feature| {
+                    printer.supports_feature(feature)
+                });
+            }
+            AtRule::Container(rule) => {
+                self.push("@container");
+                if let Some(name) = &rule.name {
+                    self.push(" ");
+                    self.push(name.as_ref());
+                }
+                if let Some((condition, _)) = &rule.condition {
+                    self.push(" ");
+                    self.condition(condition, &mut |printer, feature| match feature {
+                        ContainerFeature::Size(values) => {
+                            printer.push("(");
+                            printer.component_values(values);
+                            printer.push(")");
+                        }
+                        ContainerFeature::Function(function) => printer.function_call(function),
+                    });
+                }
+            }
+            AtRule::Layer(rule) => {
+                self.push("@layer");
+                for (i, (name, _)) in rule.names.iter().enumerate() {
+                    self.push(if i > 0 { ", " } else { " " });
+                    for (j, (part, _)) in name.0.iter().enumerate() {
+                        if j > 0 {
+                            self.push(".");
+                        }
+                        self.push(part.as_ref());
+                    }
+                }
+            }
+            AtRule::FontFace(_) => {
+                self.push("@font-face");
+            }
+            AtRule::Page(rule) => {
+                self.push("@page");
+                for (i, (selector, _)) in rule.selectors.iter().enumerate() {
+                    self.push(if i > 0 { ", " } else { " " });
+                    if let Some(name) = &selector.name {
+                        self.push(name.as_ref());
+                    }
+                    for (pseudo_class, _) in &selector.pseudo_classes {
+                        self.push(":");
+                        self.push(pseudo_class.as_ref());
+                    }
+                }
+            }
+            AtRule::PageMargin(rule) => {
+                self.push("@");
+                self.push(rule.name.as_ref());
+            }
+            AtRule::Namespace(rule) => {
+                self.push("@namespace ");
+                if let Some(prefix) = &rule.prefix {
+                    self.push(prefix.as_ref());
+                    self.push(" ");
+                }
+                self.url_or_string(&rule.url.0);
+            }
+            AtRule::Property(rule) => {
+                self.push("@property ");
+                self.push(rule.name.as_ref());
+            }
+        }
+    }
+
+    fn url_or_string(&mut self, url: &UrlOrString<S>) {
+        match url {
+            UrlOrString::String(string) => self.interpolated_string(string),
+            UrlOrString::Url(url) => {
+                self.push("url(");
+                self.interpolated_string(url);
+                self.push(")");
+            }
+        }
+    }
+
+    fn condition<T>(&mut self, condition: &Condition<T>, feature: &mut impl FnMut(&mut Self, &T)) {
+        match condition {
+            Condition::Not(condition) => {
+                self.push("not ");
+                self.nested_condition(&condition.0, feature);
+            }
+            Condition::And(conditions) | Condition::Or(conditions) => {
+                let operator = match condition {
+                    Condition::And(_) => " and ",
+                    _ => " or ",
+                };
+                for (i, (condition, _)) in conditions.iter().enumerate() {
+                    if i > 0 {
+                        self.push(operator);
+                    }
+                    self.nested_condition(condition, feature);
+                }
+            }
+            Condition::Feature(f) => feature(self, f),
+        }
+    }
+
+    /// Prints a sub-condition, adding the parentheses implied by the structure.
+    fn nested_condition<T>(
+        &mut self,
+        condition: &Condition<T>,
+        feature: &mut impl FnMut(&mut Self, &T),
+    ) {
+        if let Condition::Feature(_) = condition {
+            self.condition(condition, feature);
+        } else {
+            self.push("(");
+            self.condition(condition, feature);
+            self.push(")");
+        }
+    }
+
+    fn supports_feature(&mut self, feature: &SupportsFeature<S>) {
+        match feature {
+            SupportsFeature::Declaration(declaration) => {
+                self.push("(");
+                self.declaration_name(&declaration.name);
+                self.push(": ");
+                self.value(&declaration.value);
+                if declaration.important {
+                    self.push(" !important");
+                }
+                self.push(")");
+            }
+            SupportsFeature::Function(function) => self.function_call(function),
+            SupportsFeature::GeneralEnclosed(values) => {
+                self.push("(");
+                self.component_values(values);
+                self.push(")");
+            }
+        }
+    }
+
+    // QUALIFIED RULES
+
+    fn qualified_rule(&mut self, rule: &QualifiedRule<S>) {
+        match rule {
+            QualifiedRule::Generic(rule) => {
+                self.component_values(&rule.prelude);
+                self.block(&rule.block);
+            }
+            QualifiedRule::Style(rule) => {
+                self.selector_list(&rule.selectors);
+                self.guard(rule.guard.as_ref());
+                self.block(&rule.block);
+            }
+            QualifiedRule::Mixin(rule) => {
+                self.push(rule.name.as_ref());
+                self.push("(");
+                self.component_values(&rule.arguments);
+                self.push(")");
+                self.guard(rule.guard.as_ref());
+                self.block(&rule.block);
+            }
+            QualifiedRule::Keyframe(rule) => {
+                for (i, (selector, _)) in rule.selectors.iter().enumerate() {
+                    if i > 0 {
+                        self.push(", ");
+                    }
+                    match selector {
+                        KeyframeSelector::From => self.push("from"),
+                        KeyframeSelector::To => self.push("to"),
+                        KeyframeSelector::Percentage(percentage) => {
+                            self.push(percentage.as_ref());
+                            self.push("%");
+                        }
+                    }
+                }
+                self.block(&rule.block);
+            }
+        }
+    }
+
+    fn guard(&mut self, guard: Option<&ListOfComponentValues<S>>) {
+        if let Some(guard) = guard {
+            self.push(" when ");
+            self.component_values(guard);
+        }
+    }
+
+    // SELECTORS
+
+    fn selector_list(&mut self, selectors: &SelectorList<S>) {
+        for (i, (selector, _)) in selectors.0.iter().enumerate() {
+            if i > 0 {
+                self.push(", ");
+            }
+            self.selector(selector);
+        }
+    }
+
+    fn selector(&mut self, selector: &Selector<S>) {
+        for (i, (component, _)) in selector.components.iter().enumerate() {
+            match component {
+                SelectorComponent::Combinator(combinator) => {
+                    let combinator = match combinator {
+                        Combinator::Descendant => " ",
+                        Combinator::Child => " > ",
+                        Combinator::NextSibling => " + ",
+                        Combinator::SubsequentSibling => " ~ ",
+                    };
+                    // A leading combinator (e.g. `> .child`) has no space before it
+                    self.push(if i == 0 {
+                        combinator.trim_start()
+                    } else {
+                        combinator
+                    });
+                }
+                SelectorComponent::Parent => self.push("&"),
+                SelectorComponent::Universal => self.push("*"),
+                SelectorComponent::Type(name) => self.selector_name(name),
+                SelectorComponent::Class(name) => {
+                    self.push(".");
+                    self.selector_name(name);
+                }
+                SelectorComponent::Id(name) => {
+                    self.push("#");
+                    self.selector_name(name);
+                }
+                SelectorComponent::Attribute { name, matcher } => {
+                    self.push("[");
+                    self.selector_name(name);
+                    if let Some(matcher) = matcher {
+                        self.push(match matcher.operator {
+                            AttributeOperator::Equals => "=",
+                            AttributeOperator::Includes => "~=",
+                            AttributeOperator::DashMatch => "|=",
+                            AttributeOperator::Prefix => "^=",
+                            AttributeOperator::Suffix => "$=",
+                            AttributeOperator::Substring => "*=",
+                        });
+                        match &matcher.value {
+                            AttributeValue::Ident(name) => self.selector_name(name),
+                            AttributeValue::String(string) => self.interpolated_string(string),
+                        }
+                        if let Some(modifier) = &matcher.modifier {
+                            self.push(" ");
+                            self.push(modifier.as_ref());
+                        }
+                    }
+                    self.push("]");
+                }
+                SelectorComponent::PseudoClass { name, arguments }
+                | SelectorComponent::PseudoElement { name, arguments } => {
+                    self.push(match component {
+                        SelectorComponent::PseudoElement { .. } => "::",
+                        _ => ":",
+                    });
+                    self.push(name.as_ref());
+                    if let Some(arguments) = arguments {
+                        self.push("(");
+                        self.component_values(arguments);
+                        self.push(")");
+                    }
+                }
+            }
+        }
+        for (extend, _) in &selector.extends {
+            self.extend(extend);
+        }
+    }
+
+    fn selector_name(&mut self, name: &SelectorName<S>) {
+        match name {
+            SelectorName::Ident(name) => self.push(name.as_ref()),
+            SelectorName::InterpolatedIdent(ident) => self.interpolation_parts(&ident.0),
+        }
+    }
+
+    // EXTENDS
+
+    /// Prints an extend (e.g. `:extend(.b all)`), without the selector or `&` it's attached to.
+    fn extend(&mut self, extend: &Extend<S>) {
+        self.push(":extend(");
+        for (i, (target, _)) in extend.targets.iter().enumerate() {
+            if i > 0 {
+                self.push(", ");
+            }
+            self.selector(&target.selector);
+            if target.all {
+                self.push(" all");
+            }
+        }
+        self.push(")");
+    }
+
+    // DECLARATIONS
+
+    fn declaration(&mut self, declaration: &Declaration<S>) {
+        self.declaration_name(&declaration.name);
+        self.push(": ");
+        self.value(&declaration.value);
+        if declaration.important {
+            self.push(" !important");
+        }
+        if let Value::ComponentValues(_) = declaration.value {
+            self.push(";");
+        }
+    }
+
+    fn declaration_name(&mut self, name: &DeclarationName<S>) {
+        match name {
+            DeclarationName::Ident(name) => self.push(name.as_ref()),
+            DeclarationName::InterpolatedIdent(ident) => self.interpolation_parts(&ident.0),
+            DeclarationName::Variable(name) => {
+                self.push("@");
+                self.push(name.as_ref());
+            }
+        }
+    }
+
+    fn value(&mut self, value: &Value<S>) {
+        match value {
+            Value::ComponentValues(values) => self.component_values(values),
+            Value::DetachedRuleset(items) => self.braces(items),
+        }
+    }
+
+    // CALLS
+
+    /// Prints a call, without the terminating semicolon.
+    fn call(&mut self, call: &Call<S>) {
+        match call {
+            Call::Mixin(call) => {
+                self.component_values(&call.selector);
+                self.push("(");
+                for (i, (argument, _)) in call.arguments.iter().enumerate() {
+                    if i > 0 {
+                        self.push("; ");
+                    }
+                    if let Some(name) = &argument.name {
+                        self.push("@");
+                        self.push(name.as_ref());
+                        self.push(": ");
+                    }
+                    self.value(&argument.value);
+                }
+                // A single argument containing commas needs a semicolon to not be split up
+                if let [(argument, _)] = call.arguments.as_slice() {
+                    if let Value::ComponentValues(values) = &argument.value {
+                        if values.0.iter().any(|(tt, _)| is_symbol(tt, ',')) {
+                            self.push(";");
+                        }
+                    }
+                }
+                self.push(")");
+                if call.important {
+                    self.push(" !important");
+                }
+            }
+            Call::Variable(call) => {
+                self.push("@");
+                self.push(call.name.as_ref());
+                self.push("()");
+            }
+            Call::Function(call) => {
+                self.push(call.name.as_ref());
+                self.push("(");
+                for (i, (argument, _)) in call.arguments.iter().enumerate() {
+                    if i > 0 {
+                        self.push(", ");
+                    }
+                    self.value(argument);
+                }
+                self.push(")");
+            }
+        }
+    }
+
+    fn function_call(&mut self, function: &FunctionCall<S>) {
+        self.push(function.name.as_ref());
+        self.push("(");
+        self.component_values(&function.arguments);
+        self.push(")");
+    }
+
+    // COMPONENT VALUES
+
+    fn component_values(&mut self, values: &ListOfComponentValues<S>) {
+        self.token_trees(trim(&values.0));
+    }
+
+    /// Prints token trees, collapsing whitespace into single spaces.
+    fn token_trees(&mut self, tts: &[Spanned<TokenTree<S>>]) {
+        for (tt, _) in tts {
+            match tt {
+                TokenTree::Token(token) => self.token(token),
+                TokenTree::Tree(delim, tts) => {
+                    self.out.push(delim.open());
+                    self.token_trees(tts);
+                    self.out.push(delim.close());
+                }
+            }
+        }
+    }
+
+    fn token(&mut self, token: &Token<S>) {
+        match token {
+            Token::Whitespace => self.push(" "),
+            // Line comments can't be told apart from block comments anymore, and a line comment
+            // would swallow the rest of the line
+            Token::Comment(comment) => {
+                self.push("/*");
+                self.push(comment.as_ref());
+                self.push("*/");
+            }
+            Token::Ident(ident) => self.push(ident.as_ref()),
+            Token::Hash(hash) => {
+                self.push("#");
+                self.push(hash.as_ref());
+            }
+            Token::String(string) => self.string(string.as_ref()),
+            Token::Url(url) => {
+                self.push("url(");
+                self.push(url.as_ref());
+                self.push(")");
+            }
+            Token::Interpolation(name) => {
+                self.push("@{");
+                self.push(name.as_ref());
+                self.push("}");
+            }
+            Token::Number(number) => self.push(number.as_ref()),
+            Token::Symbol(symbol) => self.out.push(*symbol),
+        }
+    }
+
+    /// Prints the contents of a string in quotes that don't appear in the contents.
+    fn string(&mut self, contents: &str) {
+        let quote = if contents.contains('"') { "'" } else { "\"" };
+        self.push(quote);
+        self.push(contents);
+        self.push(quote);
+    }
+
+    fn interpolated_string(&mut self, string: &InterpolatedString<S>) {
+        let mut contents = Printer::<S>::new(None);
+        contents.interpolation_parts(&string.0);
+        self.string(&contents.out);
+    }
+
+    fn interpolation_parts(&mut self, parts: &[Spanned<InterpolationPart<S>>]) {
+        for (part, _) in parts {
+            match part {
+                InterpolationPart::Literal(literal) => self.push(literal.as_ref()),
+                InterpolationPart::Variable(name) => {
+                    self.push("@{");
+                    self.push(name.as_ref());
+                    self.push("}");
+                }
+            }
+        }
+    }
+}
+
+fn is_symbol<S>(tt: &TokenTree<S>, symbol: char) -> bool {
+    matches!(tt, TokenTree::Token(Token::Symbol(s)) if *s == symbol)
+}
+
+/// Strips leading and trailing whitespace and comments.
+fn trim<S>(tts: &[Spanned<TokenTree<S>>]) -> &[Spanned<TokenTree<S>>] {
+    let is_junk = |(tt, _): &Spanned<TokenTree<S>>| {
+        matches!(tt, TokenTree::Token(Token::Whitespace | Token::Comment(_)))
+    };
+    let start = tts.iter().position(|tt| !is_junk(tt)).unwrap_or(tts.len());
+    let end = tts
+        .iter()
+        .rposition(|tt| !is_junk(tt))
+        .map_or(start, |i| i + 1);
+    &tts[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::visit_mut::{walk_declaration_mut, VisitMut};
+    use crate::ast::*;
+    use crate::lexer::{Token, TokenTree};
+    use crate::parse;
+
+    #[test]
+    fn test_to_less() {
+        let input = r#"
+            @import (reference)   "theme.less" screen;
+            @color:#fff ;
+            .a > .b,.c:hover::before {
+              color : @color !important;
+              &:extend(.d all);
+              .mixin(1, 2; @x: red);
+              @media (min-width:768px){ width:100% }
+            }
+            @supports not (display: grid) { .e { float: left } }
+            @detached: { background: url(a.png); };
+        "#;
+        let output = parse(input).unwrap().to_less();
+        assert_eq!(
+            output,
+            r#"@import (reference) "theme.less" screen;
+@color: #fff;
+.a > .b, .c:hover::before {
+  color: @color !important;
+  &:extend(.d all);
+  .mixin(1, 2; @x: red);
+  @media (min-width:768px) {
+    width: 100%;
+  }
+}
+@supports not (display: grid) {
+  .e {
+    float: left;
+  }
+}
+@detached: {
+  background: url(a.png);
+}
+"#
+        );
+        assert_eq!(parse(&output).unwrap().to_less(), output);
+
+        // Numbers are printed as they were written
+        let output = parse(".a { margin: .5em 1e3px -1.50px; }")
+            .unwrap()
+            .to_less();
+        assert_eq!(output, ".a {\n  margin: .5em 1e3px -1.50px;\n}\n");
+    }
+
+    #[test]
+    fn test_to_less_with_source() {
+        // Replace `red` with `black`, leaving everything else untouched
+        struct Recolor;
+
+        impl<'src> VisitMut<&'src str> for Recolor {
+            fn visit_declaration_mut(&mut self, declaration: &mut Declaration<&'src str>) {
+                if let Value::ComponentValues(values) = &mut declaration.value {
+                    for (tt, _) in &mut values.0 {
+                        if *tt == TokenTree::Token(Token::Ident("red")) {
+                            *tt = TokenTree::Token(Token::Ident("black"));
+                        }
+                    }
+                }
+                walk_declaration_mut(self, declaration);
+            }
+        }
+
+        let input = ".a{color:red}\n.b {\n    margin:0 auto ;// keep\n    color:  red;\n}";
+        let original = parse(input).unwrap();
+        let mut modified = original.clone();
+        Recolor.visit_stylesheet_mut(&mut modified);
+
+        assert_eq!(
+            modified.to_less_with_source(&original, input),
+            ".a {\n  color: black;\n}\n.b {\n  margin:0 auto ;// keep\n    color: black;\n}"
+        );
+        assert_eq!(original.to_less_with_source(&original, input), input);
+
+        let input = "/* head */\n.a{color:red}\n.b{x:y}\n\n// between\n.c {z:w}\n// tail\n";
+        let original = parse(input).unwrap();
+        let mut modified = original.clone();
+        Recolor.visit_stylesheet_mut(&mut modified);
+
+        assert_eq!(original.to_less_with_source(&original, input), input);
+        assert_eq!(
+            modified.to_less_with_source(&original, input),
+            "/* head */\n.a {\n  color: black;\n}\n.b{x:y}\n\n// between\n.c {z:w}\n// tail\n"
+        );
+    }
+}
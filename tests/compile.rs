@@ -0,0 +1,35 @@
+extern crate less;
+
+use less::{compile, Math, Options};
+
+#[test]
+fn test_math_option() {
+    let options = Options {
+        math: Math::Always,
+        ..Options::default()
+    };
+    let css = compile(".a { b: 1 / 2; }", options).unwrap();
+    assert_eq!(css, ".a {\n  b: 0.5;\n}\n");
+
+    let options = Options {
+        math: Math::Parens,
+        ..Options::default()
+    };
+    let css = compile(".a { b: 1 + 2; c: (1 + 2); }", options).unwrap();
+    assert_eq!(css, ".a {\n  b: 1 + 2;\n  c: 3;\n}\n");
+}
+
+#[test]
+fn test_unsupported() {
+    let error = compile(".a { &:extend(.b); }", Options::default()).unwrap_err();
+    assert_eq!(error.to_string(), "`:extend` is not supported at 5..18");
+
+    let error = compile(".a:extend(.b) { c: d; }", Options::default()).unwrap_err();
+    assert_eq!(error.to_string(), "`:extend` is not supported at 2..13");
+
+    let error = compile("@import \"theme.less\";", Options::default()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "importing LESS files is not supported at 0..21"
+    );
+}
@@ -0,0 +1,23 @@
+extern crate less;
+
+use less::ast::{DeclarationName, Item};
+
+#[test]
+fn test_parse_recovering() {
+    let input = ".a { b: c; }\n.d { e }\n@f: g;\n.h { i: j; }";
+    let (stylesheet, errors) = less::parse_recovering(input);
+    assert_eq!(errors.len(), 1);
+    assert!(less::parse(input).is_err());
+
+    // The items after the broken declaration are kept
+    let stylesheet = stylesheet.unwrap();
+    assert_eq!(stylesheet.items.0.len(), 4);
+    let (Item::Declaration(declaration), span) = &stylesheet.items.0[2] else {
+        panic!("expected a declaration");
+    };
+    assert_eq!(declaration.name, DeclarationName::Variable("f"));
+    assert_eq!(&input[span.start..span.end], "@f: g;");
+    let (rule, _) = &stylesheet.items.0[3];
+    assert!(matches!(rule, Item::QualifiedRule(_)));
+    assert_eq!(rule.block().unwrap().0.len(), 1);
+}
@@ -0,0 +1,68 @@
+extern crate less;
+
+use less::ast::visit::Visit;
+use less::ast::visit_mut::VisitMut;
+use less::ast::{ListOfComponentValues, MapStrings, Span, Spanned, Token, TokenTree};
+
+/// Collects the names of the variables used in values (e.g. `@color`).
+struct VariableUses<'src>(Vec<(&'src str, Span)>);
+
+impl<'src> VariableUses<'src> {
+    fn token_trees(&mut self, tts: &[Spanned<TokenTree<&'src str>>]) {
+        for (i, (tt, span)) in tts.iter().enumerate() {
+            match (tt, tts.get(i + 1)) {
+                (
+                    TokenTree::Token(Token::Symbol('@')),
+                    Some((TokenTree::Token(Token::Ident(name)), end)),
+                ) => self.0.push((name, Span::new(span.start, end.end))),
+                (TokenTree::Tree(_, tts), _) => self.token_trees(tts),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'ast, 'src> Visit<'ast, &'src str> for VariableUses<'src> {
+    fn visit_list_of_component_values(&mut self, values: &'ast ListOfComponentValues<&'src str>) {
+        self.token_trees(&values.0);
+    }
+}
+
+/// Prefixes the unquoted URLs with a CDN.
+struct RewriteUrls;
+
+impl VisitMut<String> for RewriteUrls {
+    fn visit_token_mut(&mut self, token: &mut Token<String>, _span: Span) {
+        if let Token::Url(url) = token {
+            *url = format!("https://cdn.example.com/{url}");
+        }
+    }
+}
+
+#[test]
+fn test_visit() {
+    let input = ".a { color: @color; .b { margin: (@size * 2) @size; } }";
+    let stylesheet = less::parse(input).unwrap();
+    let mut uses = VariableUses(Vec::new());
+    uses.visit_stylesheet(&stylesheet);
+    let uses: Vec<_> = uses
+        .0
+        .into_iter()
+        .map(|(name, span)| (name, &input[span.into_range()]))
+        .collect();
+    assert_eq!(
+        uses,
+        [("color", "@color"), ("size", "@size"), ("size", "@size")]
+    );
+}
+
+#[test]
+fn test_visit_mut() {
+    let input = ".a { background: url(a.png); }";
+    let mut stylesheet = less::parse(input).unwrap().map_strings(&mut String::from);
+    RewriteUrls.visit_stylesheet_mut(&mut stylesheet);
+    assert_eq!(
+        stylesheet.to_less(),
+        ".a {\n  background: url(https://cdn.example.com/a.png);\n}\n"
+    );
+}